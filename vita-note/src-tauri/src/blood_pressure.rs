@@ -0,0 +1,391 @@
+use chrono::{Local, Timelike};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::database::{open_conn, ApiResponse};
+use crate::datetime::{parse_timestamp, parse_timestamp_arg};
+
+pub const ARM_LEFT: &str = "left";
+pub const ARM_RIGHT: &str = "right";
+
+pub const POSTURE_SITTING: &str = "sitting";
+pub const POSTURE_STANDING: &str = "standing";
+pub const POSTURE_LYING: &str = "lying";
+
+// 《中国高血压防治指南（2018 年修订版）》的血压水平分类
+pub const BP_NORMAL: &str = "normal";
+pub const BP_HIGH_NORMAL: &str = "high_normal";
+pub const BP_GRADE_1: &str = "grade_1";
+pub const BP_GRADE_2: &str = "grade_2";
+pub const BP_GRADE_3: &str = "grade_3";
+
+const CATEGORIES: [&str; 5] = [BP_NORMAL, BP_HIGH_NORMAL, BP_GRADE_1, BP_GRADE_2, BP_GRADE_3];
+// 各分类的下限（收缩压, 舒张压），收缩压或舒张压任一达到即归入该类
+const CATEGORY_FROM: [(i32, i32); 4] = [(120, 80), (140, 90), (160, 100), (180, 110)];
+
+// 家庭自测血压的高血压诊断阈值为 135/85 mmHg
+const HOME_SYSTOLIC_LIMIT: i32 = 135;
+const HOME_DIASTOLIC_LIMIT: i32 = 85;
+// 《中国 2 型糖尿病防治指南（2020 年版）》的降压目标为 < 130/80 mmHg
+const DIABETES_SYSTOLIC_TARGET: i32 = 130;
+const DIABETES_DIASTOLIC_TARGET: i32 = 80;
+
+// 按本地时间区分晨起（4:00–12:00）与晚间（18:00–24:00）测量
+const MORNING_HOURS: std::ops::Range<u32> = 4..12;
+const EVENING_HOURS: std::ops::Range<u32> = 18..24;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BloodPressure {
+    pub id: String,
+    pub user_id: String,
+    pub created_at: String,
+    pub measured_at: String,
+    pub systolic: i32,
+    pub diastolic: i32,
+    pub pulse: Option<i32>,
+    pub arm: Option<String>,
+    pub posture: Option<String>,
+    pub device_name: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl BloodPressure {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<BloodPressure> {
+        Ok(BloodPressure {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            created_at: row.get(2)?,
+            measured_at: row.get(3)?,
+            systolic: row.get(4)?,
+            diastolic: row.get(5)?,
+            pulse: row.get(6)?,
+            arm: row.get(7)?,
+            posture: row.get(8)?,
+            device_name: row.get(9)?,
+            notes: row.get(10)?,
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        parse_timestamp_arg("measured_at", &self.measured_at)?;
+        if !(40..=300).contains(&self.systolic) || !(20..=200).contains(&self.diastolic) {
+            return Err(format!("Invalid blood pressure: {}/{}", self.systolic, self.diastolic));
+        }
+        if self.diastolic >= self.systolic {
+            return Err("diastolic must be lower than systolic".to_string());
+        }
+        if matches!(self.pulse, Some(p) if !(20..=250).contains(&p)) {
+            return Err("Invalid pulse".to_string());
+        }
+        match self.arm.as_deref() {
+            None | Some(ARM_LEFT) | Some(ARM_RIGHT) => {}
+            Some(other) => return Err(format!("Unknown arm: {}", other)),
+        }
+        match self.posture.as_deref() {
+            None | Some(POSTURE_SITTING) | Some(POSTURE_STANDING) | Some(POSTURE_LYING) => Ok(()),
+            Some(other) => Err(format!("Unknown posture: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BloodPressureAverage {
+    pub count: usize,
+    pub systolic: Option<f64>,
+    pub diastolic: Option<f64>,
+    pub pulse: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CategoryCount {
+    pub category: String,
+    pub label: String,
+    pub count: usize,
+    pub isolated_systolic: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BloodPressureStats {
+    pub overall: BloodPressureAverage,
+    pub morning: BloodPressureAverage,
+    pub evening: BloodPressureAverage,
+    /// 达到家庭自测高血压阈值（≥ 135/85）的读数占比
+    pub above_home_threshold_percent: f64,
+    /// 未达到糖尿病患者降压目标（< 130/80）的读数占比
+    pub above_diabetes_target_percent: f64,
+    /// 按平均值判断的分类
+    pub category: Option<String>,
+    pub categories: Vec<CategoryCount>,
+}
+
+/// 收缩压与舒张压分属不同级别时以较高者为准
+pub fn blood_pressure_category(systolic: i32, diastolic: i32) -> &'static str {
+    let level = CATEGORY_FROM
+        .iter()
+        .take_while(|(s, d)| systolic >= *s || diastolic >= *d)
+        .count();
+    CATEGORIES[level]
+}
+
+pub fn blood_pressure_category_label(category: &str) -> &'static str {
+    match category {
+        BP_NORMAL => "正常血压",
+        BP_HIGH_NORMAL => "正常高值",
+        BP_GRADE_1 => "1 级高血压（轻度）",
+        BP_GRADE_2 => "2 级高血压（中度）",
+        BP_GRADE_3 => "3 级高血压（重度）",
+        _ => "未知",
+    }
+}
+
+/// 单纯收缩期高血压：收缩压 ≥ 140 且舒张压 < 90
+pub fn is_isolated_systolic(systolic: i32, diastolic: i32) -> bool {
+    systolic >= CATEGORY_FROM[1].0 && diastolic < CATEGORY_FROM[1].1
+}
+
+fn average(readings: &[&BloodPressure]) -> BloodPressureAverage {
+    let mean = |values: Vec<i32>| {
+        if values.is_empty() {
+            None
+        } else {
+            Some(values.iter().sum::<i32>() as f64 / values.len() as f64)
+        }
+    };
+    BloodPressureAverage {
+        count: readings.len(),
+        systolic: mean(readings.iter().map(|r| r.systolic).collect()),
+        diastolic: mean(readings.iter().map(|r| r.diastolic).collect()),
+        pulse: mean(readings.iter().filter_map(|r| r.pulse).collect()),
+    }
+}
+
+fn percent(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64 * 100.0
+    }
+}
+
+pub(crate) fn compute_stats(readings: &[BloodPressure]) -> BloodPressureStats {
+    let all: Vec<&BloodPressure> = readings.iter().collect();
+    let in_hours = |hours: &std::ops::Range<u32>| -> Vec<&BloodPressure> {
+        readings
+            .iter()
+            .filter(|r| {
+                parse_timestamp(&r.measured_at)
+                    .map(|t| hours.contains(&t.with_timezone(&Local).hour()))
+                    .unwrap_or(false)
+            })
+            .collect()
+    };
+
+    let overall = average(&all);
+    let above_home = readings
+        .iter()
+        .filter(|r| r.systolic >= HOME_SYSTOLIC_LIMIT || r.diastolic >= HOME_DIASTOLIC_LIMIT)
+        .count();
+    let above_target = readings
+        .iter()
+        .filter(|r| r.systolic >= DIABETES_SYSTOLIC_TARGET || r.diastolic >= DIABETES_DIASTOLIC_TARGET)
+        .count();
+    let categories = CATEGORIES
+        .iter()
+        .map(|category| {
+            let matching: Vec<&BloodPressure> = readings
+                .iter()
+                .filter(|r| blood_pressure_category(r.systolic, r.diastolic) == *category)
+                .collect();
+            CategoryCount {
+                category: category.to_string(),
+                label: blood_pressure_category_label(category).to_string(),
+                count: matching.len(),
+                isolated_systolic: matching.iter().filter(|r| is_isolated_systolic(r.systolic, r.diastolic)).count(),
+            }
+        })
+        .collect();
+
+    BloodPressureStats {
+        category: match (overall.systolic, overall.diastolic) {
+            (Some(s), Some(d)) => Some(blood_pressure_category(s.round() as i32, d.round() as i32).to_string()),
+            _ => None,
+        },
+        overall,
+        morning: average(&in_hours(&MORNING_HOURS)),
+        evening: average(&in_hours(&EVENING_HOURS)),
+        above_home_threshold_percent: percent(above_home, readings.len()),
+        above_diabetes_target_percent: percent(above_target, readings.len()),
+        categories,
+    }
+}
+
+fn load_range(conn: &Connection, user_id: &str, start_date: &str, end_date: &str) -> Result<Vec<BloodPressure>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM BloodPressure
+            WHERE user_id = ?1 AND measured_at >= ?2 AND measured_at < ?3
+            ORDER BY measured_at DESC"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let readings = stmt
+        .query_map(params![user_id, start_date, end_date], BloodPressure::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(readings)
+}
+
+// ============ Blood Pressure Commands ============
+
+#[tauri::command]
+pub async fn blood_pressure_create(entry: BloodPressure) -> Result<ApiResponse<BloodPressure>, String> {
+    entry.validate()?;
+    let conn = open_conn()?;
+
+    conn.execute(
+        r#"INSERT INTO BloodPressure (id, user_id, created_at, measured_at, systolic, diastolic, pulse,
+            arm, posture, device_name, notes)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"#,
+        params![
+            entry.id, entry.user_id, entry.created_at, entry.measured_at, entry.systolic, entry.diastolic,
+            entry.pulse, entry.arm, entry.posture, entry.device_name, entry.notes
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(entry),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn blood_pressure_get(
+    user_id: String,
+    start_date: String,
+    end_date: String,
+) -> Result<ApiResponse<Vec<BloodPressure>>, String> {
+    let conn = open_conn()?;
+    let items = load_range(&conn, &user_id, &start_date, &end_date)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(items),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn blood_pressure_update(entry: BloodPressure) -> Result<ApiResponse<BloodPressure>, String> {
+    entry.validate()?;
+    let conn = open_conn()?;
+
+    conn.execute(
+        r#"UPDATE BloodPressure SET
+            measured_at = ?2, systolic = ?3, diastolic = ?4, pulse = ?5, arm = ?6, posture = ?7,
+            device_name = ?8, notes = ?9
+        WHERE id = ?1"#,
+        params![
+            entry.id, entry.measured_at, entry.systolic, entry.diastolic, entry.pulse, entry.arm,
+            entry.posture, entry.device_name, entry.notes
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(entry),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn blood_pressure_delete(id: String) -> Result<ApiResponse<String>, String> {
+    let conn = open_conn()?;
+
+    conn.execute("DELETE FROM BloodPressure WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some("Deleted".to_string()),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn blood_pressure_stats(
+    user_id: String,
+    start_date: String,
+    end_date: String,
+) -> Result<ApiResponse<BloodPressureStats>, String> {
+    let conn = open_conn()?;
+    let readings = load_range(&conn, &user_id, &start_date, &end_date)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(compute_stats(&readings)),
+        message: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(systolic: i32, diastolic: i32) -> BloodPressure {
+        BloodPressure {
+            id: format!("{}/{}", systolic, diastolic),
+            user_id: "u1".to_string(),
+            created_at: String::new(),
+            measured_at: "2026-01-02T08:00:00.000Z".to_string(),
+            systolic,
+            diastolic,
+            pulse: None,
+            arm: None,
+            posture: None,
+            device_name: None,
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn categories_start_at_guideline_thresholds() {
+        assert_eq!(blood_pressure_category(119, 79), BP_NORMAL);
+        assert_eq!(blood_pressure_category(120, 70), BP_HIGH_NORMAL);
+        assert_eq!(blood_pressure_category(110, 80), BP_HIGH_NORMAL);
+        assert_eq!(blood_pressure_category(139, 89), BP_HIGH_NORMAL);
+        assert_eq!(blood_pressure_category(140, 89), BP_GRADE_1);
+        assert_eq!(blood_pressure_category(159, 99), BP_GRADE_1);
+        assert_eq!(blood_pressure_category(160, 80), BP_GRADE_2);
+        assert_eq!(blood_pressure_category(179, 109), BP_GRADE_2);
+        assert_eq!(blood_pressure_category(180, 90), BP_GRADE_3);
+        assert_eq!(blood_pressure_category(130, 110), BP_GRADE_3);
+    }
+
+    #[test]
+    fn higher_of_systolic_and_diastolic_wins() {
+        assert_eq!(blood_pressure_category(118, 95), BP_GRADE_1);
+        assert_eq!(blood_pressure_category(165, 75), BP_GRADE_2);
+    }
+
+    #[test]
+    fn isolated_systolic_hypertension() {
+        assert!(is_isolated_systolic(150, 85));
+        assert!(!is_isolated_systolic(150, 90));
+        assert!(!is_isolated_systolic(139, 70));
+    }
+
+    #[test]
+    fn stats_use_rounded_mean_for_overall_category() {
+        let stats = compute_stats(&[reading(118, 76), reading(142, 84)]);
+        assert_eq!(stats.overall.systolic, Some(130.0));
+        assert_eq!(stats.category.as_deref(), Some(BP_HIGH_NORMAL));
+        assert_eq!(stats.above_home_threshold_percent, 50.0);
+        assert_eq!(stats.above_diabetes_target_percent, 50.0);
+        let grade_1 = stats.categories.iter().find(|c| c.category == BP_GRADE_1).unwrap();
+        assert_eq!((grade_1.count, grade_1.isolated_systolic), (1, 1));
+        assert_eq!(compute_stats(&[]).category, None);
+    }
+}
@@ -0,0 +1,387 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::database::{open_conn, ApiResponse, User};
+use crate::datetime::{parse_timestamp, parse_timestamp_arg};
+use crate::enums::{GENDER_FEMALE, GENDER_MALE};
+
+pub const CONDITION_FASTING: &str = "fasting";
+pub const CONDITION_AFTER_MEAL: &str = "after_meal";
+pub const CONDITION_OTHER: &str = "other";
+
+// 《中国成人超重和肥胖预防控制指南》/ WS/T 428-2013 的 BMI 分类
+pub const BMI_UNDERWEIGHT: &str = "underweight";
+pub const BMI_NORMAL: &str = "normal";
+pub const BMI_OVERWEIGHT: &str = "overweight";
+pub const BMI_OBESE: &str = "obese";
+
+const BMI_NORMAL_FROM: f64 = 18.5;
+const BMI_OVERWEIGHT_FROM: f64 = 24.0;
+const BMI_OBESE_FROM: f64 = 28.0;
+// 中心型肥胖：男性腰围 ≥ 90 cm，女性 ≥ 85 cm
+const WAIST_OBESE_MALE: f64 = 90.0;
+const WAIST_OBESE_FEMALE: f64 = 85.0;
+
+const MOVING_AVERAGE_DAYS: i64 = 7;
+const DEFAULT_TREND_DAYS: i64 = 90;
+const MAX_TREND_DAYS: i64 = 730;
+// 每周变化小于该值时视为体重持平，不估算达成时间
+const MIN_WEEKLY_CHANGE: f64 = 0.05;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BodyMeasurement {
+    pub id: String,
+    pub user_id: String,
+    pub created_at: String,
+    pub measured_at: String,
+    pub weight: f64,
+    pub body_fat_percent: Option<f64>,
+    pub muscle_mass: Option<f64>,
+    pub waist_circumference: Option<f64>,
+    pub condition: Option<String>,
+    pub device_name: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl BodyMeasurement {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<BodyMeasurement> {
+        Ok(BodyMeasurement {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            created_at: row.get(2)?,
+            measured_at: row.get(3)?,
+            weight: row.get(4)?,
+            body_fat_percent: row.get(5)?,
+            muscle_mass: row.get(6)?,
+            waist_circumference: row.get(7)?,
+            condition: row.get(8)?,
+            device_name: row.get(9)?,
+            notes: row.get(10)?,
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        parse_timestamp_arg("measured_at", &self.measured_at)?;
+        if !(self.weight > 0.0 && self.weight < 500.0) {
+            return Err(format!("Invalid weight: {}", self.weight));
+        }
+        if matches!(self.body_fat_percent, Some(p) if !(0.0..100.0).contains(&p)) {
+            return Err("body_fat_percent must be between 0 and 100".to_string());
+        }
+        if matches!(self.muscle_mass, Some(m) if m <= 0.0 || m >= self.weight) {
+            return Err("muscle_mass must be positive and less than weight".to_string());
+        }
+        if matches!(self.waist_circumference, Some(w) if w <= 0.0) {
+            return Err("waist_circumference must be positive".to_string());
+        }
+        match self.condition.as_deref() {
+            None | Some(CONDITION_FASTING) | Some(CONDITION_AFTER_MEAL) | Some(CONDITION_OTHER) => Ok(()),
+            Some(other) => Err(format!("Unknown condition: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WeightTrendPoint {
+    pub date: String,
+    /// 当天多次测量取平均
+    pub weight: f64,
+    /// 截至当天的 7 天移动平均
+    pub moving_average: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WeightTrend {
+    pub latest: Option<BodyMeasurement>,
+    pub bmi: Option<f64>,
+    pub bmi_category: Option<String>,
+    pub bmi_label: Option<String>,
+    pub central_obesity: Option<bool>,
+    pub points: Vec<WeightTrendPoint>,
+    /// 移动平均的线性拟合斜率，单位 kg/周，负数表示下降
+    pub weekly_change: Option<f64>,
+    pub target_weight: Option<f64>,
+    pub target_bmi: Option<f64>,
+    /// 距目标还差多少，正数表示需要减重
+    pub to_target: Option<f64>,
+    pub weeks_to_target: Option<f64>,
+}
+
+pub fn bmi(weight: f64, height_cm: f64) -> Option<f64> {
+    if height_cm <= 0.0 {
+        return None;
+    }
+    let meters = height_cm / 100.0;
+    Some(weight / (meters * meters))
+}
+
+pub fn bmi_category(bmi: f64) -> &'static str {
+    if bmi < BMI_NORMAL_FROM {
+        BMI_UNDERWEIGHT
+    } else if bmi < BMI_OVERWEIGHT_FROM {
+        BMI_NORMAL
+    } else if bmi < BMI_OBESE_FROM {
+        BMI_OVERWEIGHT
+    } else {
+        BMI_OBESE
+    }
+}
+
+pub fn bmi_category_label(category: &str) -> &'static str {
+    match category {
+        BMI_UNDERWEIGHT => "体重过低",
+        BMI_NORMAL => "体重正常",
+        BMI_OVERWEIGHT => "超重",
+        BMI_OBESE => "肥胖",
+        _ => "未知",
+    }
+}
+
+fn central_obesity(gender: i32, waist: f64) -> Option<bool> {
+    match gender {
+        GENDER_MALE => Some(waist >= WAIST_OBESE_MALE),
+        GENDER_FEMALE => Some(waist >= WAIST_OBESE_FEMALE),
+        _ => None,
+    }
+}
+
+/// 最近一次测量的体重，供运动消耗等估算使用
+pub(crate) fn latest_weight(conn: &Connection, user_id: &str) -> Result<Option<f64>, String> {
+    conn.query_row(
+        "SELECT weight FROM BodyMeasurements WHERE user_id = ?1 ORDER BY measured_at DESC LIMIT 1",
+        params![user_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+// 最小二乘斜率，x 为天数
+fn slope(points: &[(f64, f64)]) -> Option<f64> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let var_x: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if var_x == 0.0 {
+        return None;
+    }
+    let cov: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    Some(cov / var_x)
+}
+
+pub(crate) fn weight_trend(conn: &Connection, user_id: &str, days: i64, now: DateTime<Utc>) -> Result<WeightTrend, String> {
+    let user = conn
+        .query_row("SELECT * FROM Users WHERE id = ?1", params![user_id], User::from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("User not found: {}", user_id))?;
+
+    // 多取 6 天，使第一天的移动平均也有完整窗口
+    let from = now - Duration::days(days + MOVING_AVERAGE_DAYS - 1);
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM BodyMeasurements
+            WHERE user_id = ?1 AND measured_at >= ?2
+            ORDER BY measured_at ASC"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let measurements: Vec<BodyMeasurement> = stmt
+        .query_map(params![user_id, from.format("%Y-%m-%d").to_string()], BodyMeasurement::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut daily: BTreeMap<NaiveDate, Vec<f64>> = BTreeMap::new();
+    for m in &measurements {
+        if let Some(t) = parse_timestamp(&m.measured_at) {
+            daily.entry(t.with_timezone(&Local).date_naive()).or_default().push(m.weight);
+        }
+    }
+    let daily: Vec<(NaiveDate, f64)> = daily
+        .into_iter()
+        .map(|(date, weights)| (date, weights.iter().sum::<f64>() / weights.len() as f64))
+        .collect();
+
+    let today = now.with_timezone(&Local).date_naive();
+    let first_day = today - Duration::days(days - 1);
+    let points: Vec<WeightTrendPoint> = daily
+        .iter()
+        .filter(|(date, _)| *date >= first_day)
+        .map(|(date, weight)| {
+            let window: Vec<f64> = daily
+                .iter()
+                .filter(|(d, _)| *d <= *date && *d > *date - Duration::days(MOVING_AVERAGE_DAYS))
+                .map(|(_, w)| *w)
+                .collect();
+            WeightTrendPoint {
+                date: date.format("%Y-%m-%d").to_string(),
+                weight: *weight,
+                moving_average: window.iter().sum::<f64>() / window.len() as f64,
+            }
+        })
+        .collect();
+
+    let fitted: Vec<(f64, f64)> = points
+        .iter()
+        .filter_map(|p| {
+            let date = NaiveDate::parse_from_str(&p.date, "%Y-%m-%d").ok()?;
+            Some(((date - first_day).num_days() as f64, p.moving_average))
+        })
+        .collect();
+    let weekly_change = slope(&fitted).map(|per_day| per_day * 7.0);
+
+    // 范围内没有测量时仍然返回最近一次，用于显示当前 BMI
+    let latest = match measurements.last() {
+        Some(m) => Some(m.clone()),
+        None => conn
+            .query_row(
+                "SELECT * FROM BodyMeasurements WHERE user_id = ?1 ORDER BY measured_at DESC LIMIT 1",
+                params![user_id],
+                BodyMeasurement::from_row,
+            )
+            .optional()
+            .map_err(|e| e.to_string())?,
+    };
+    let current = points.last().map(|p| p.moving_average).or(latest.as_ref().map(|m| m.weight));
+    let bmi_value = latest.as_ref().and_then(|m| bmi(m.weight, user.height));
+    let to_target = match (current, user.target_weight) {
+        (Some(current), Some(target)) => Some(current - target),
+        _ => None,
+    };
+    // 只有朝目标方向变化时才估算还需多少周
+    let weeks_to_target = match (to_target, weekly_change) {
+        (Some(gap), Some(change)) if change.abs() >= MIN_WEEKLY_CHANGE && gap * change < 0.0 => {
+            Some(gap.abs() / change.abs())
+        }
+        _ => None,
+    };
+
+    Ok(WeightTrend {
+        bmi: bmi_value,
+        bmi_category: bmi_value.map(|b| bmi_category(b).to_string()),
+        bmi_label: bmi_value.map(|b| bmi_category_label(bmi_category(b)).to_string()),
+        central_obesity: latest
+            .as_ref()
+            .and_then(|m| m.waist_circumference)
+            .and_then(|waist| central_obesity(user.gender, waist)),
+        latest,
+        points,
+        weekly_change,
+        target_weight: user.target_weight,
+        target_bmi: user.target_weight.and_then(|w| bmi(w, user.height)),
+        to_target,
+        weeks_to_target,
+    })
+}
+
+// ============ Body Measurement Commands ============
+
+#[tauri::command]
+pub async fn body_measurement_create(entry: BodyMeasurement) -> Result<ApiResponse<BodyMeasurement>, String> {
+    entry.validate()?;
+    let conn = open_conn()?;
+
+    conn.execute(
+        r#"INSERT INTO BodyMeasurements (id, user_id, created_at, measured_at, weight, body_fat_percent,
+            muscle_mass, waist_circumference, condition, device_name, notes)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"#,
+        params![
+            entry.id, entry.user_id, entry.created_at, entry.measured_at, entry.weight, entry.body_fat_percent,
+            entry.muscle_mass, entry.waist_circumference, entry.condition, entry.device_name, entry.notes
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(entry),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn body_measurements_get(
+    user_id: String,
+    start_date: String,
+    end_date: String,
+) -> Result<ApiResponse<Vec<BodyMeasurement>>, String> {
+    let conn = open_conn()?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM BodyMeasurements
+            WHERE user_id = ?1 AND measured_at >= ?2 AND measured_at < ?3
+            ORDER BY measured_at DESC"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let items: Vec<BodyMeasurement> = stmt
+        .query_map(params![user_id, start_date, end_date], BodyMeasurement::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(items),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn body_measurement_update(entry: BodyMeasurement) -> Result<ApiResponse<BodyMeasurement>, String> {
+    entry.validate()?;
+    let conn = open_conn()?;
+
+    conn.execute(
+        r#"UPDATE BodyMeasurements SET
+            measured_at = ?2, weight = ?3, body_fat_percent = ?4, muscle_mass = ?5,
+            waist_circumference = ?6, condition = ?7, device_name = ?8, notes = ?9
+        WHERE id = ?1"#,
+        params![
+            entry.id, entry.measured_at, entry.weight, entry.body_fat_percent, entry.muscle_mass,
+            entry.waist_circumference, entry.condition, entry.device_name, entry.notes
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(entry),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn body_measurement_delete(id: String) -> Result<ApiResponse<String>, String> {
+    let conn = open_conn()?;
+
+    conn.execute("DELETE FROM BodyMeasurements WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some("Deleted".to_string()),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn body_weight_trend(user_id: String, days: Option<i64>) -> Result<ApiResponse<WeightTrend>, String> {
+    let days = days.unwrap_or(DEFAULT_TREND_DAYS);
+    if !(1..=MAX_TREND_DAYS).contains(&days) {
+        return Err(format!("days must be between 1 and {}", MAX_TREND_DAYS));
+    }
+    let conn = open_conn()?;
+    let trend = weight_trend(&conn, &user_id, days, Utc::now())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(trend),
+        message: None,
+    })
+}
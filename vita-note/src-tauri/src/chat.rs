@@ -0,0 +1,419 @@
+use chrono::{Local, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use crate::chat_tools::{execute_tool, tool_specs, ToolContext, MAX_TOOL_ROUNDS, TOOLS_PROMPT};
+use crate::conversations::{load_conversation, mode_prompt, touch_conversation};
+use crate::database::{insert_chat_message, open_conn, ApiResponse, ChatMessage};
+use crate::datetime::format_timestamp;
+use crate::health_context::{build_health_context, is_local_provider, load_settings};
+use crate::knowledge::build_knowledge_context;
+use crate::llm::{
+    is_masked_key, provider_for, stream_complete, supports_tools, ChatRequest, LlmMessage, LlmProviderConfig,
+    ROLE_TOOL,
+};
+
+pub const ROLE_USER: &str = "user";
+pub const ROLE_ASSISTANT: &str = "assistant";
+
+pub const CHAT_STREAM_EVENT: &str = "chat-stream";
+
+// 每次请求携带的历史消息条数
+const HISTORY_TURNS: i64 = 20;
+
+const SYSTEM_PROMPT: &str = "你是 VitaNote 的糖尿病健康助手。请用简洁、友善的中文回答，\
+基于循证医学给出饮食、运动和血糖管理建议；涉及用药调整时提醒用户咨询医生，不要给出处方。";
+
+pub(crate) fn load_default_provider(conn: &Connection) -> Result<LlmProviderConfig, String> {
+    conn.query_row(
+        "SELECT * FROM LlmProviders ORDER BY is_default DESC, created_at ASC LIMIT 1",
+        params![],
+        LlmProviderConfig::from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| "No LLM provider configured".to_string())
+}
+
+pub(crate) fn load_history(
+    conn: &Connection,
+    user_id: &str,
+    conversation_id: Option<&str>,
+    take: i64,
+) -> Result<Vec<ChatMessage>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM ChatMessages
+            WHERE user_id = ?1 AND conversation_id IS ?2 AND role IN ('user', 'assistant')
+            ORDER BY created_at DESC
+            LIMIT ?3"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let mut messages: Vec<ChatMessage> = stmt
+        .query_map(params![user_id, conversation_id, take], ChatMessage::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    messages.reverse();
+    Ok(messages)
+}
+
+pub(crate) fn new_message(
+    user_id: &str,
+    conversation_id: Option<&str>,
+    role: &str,
+    content: String,
+    model: Option<String>,
+) -> ChatMessage {
+    ChatMessage {
+        id: Uuid::new_v4().to_string(),
+        user_id: user_id.to_string(),
+        created_at: format_timestamp(&Utc::now()),
+        role: role.to_string(),
+        content,
+        model,
+        conversation_id: conversation_id.map(str::to_string),
+        is_partial: false,
+        citations: Vec::new(),
+    }
+}
+
+pub(crate) fn to_llm_messages(history: &[ChatMessage]) -> Vec<LlmMessage> {
+    history
+        .iter()
+        .map(|m| LlmMessage::text(&m.role, m.content.clone()))
+        .collect()
+}
+
+/// 流式回复的增量事件，前端按 message_id 拼接；done 为 true 时表示回复结束
+#[derive(Debug, Serialize, Clone)]
+pub struct ChatStreamEvent {
+    pub message_id: String,
+    pub conversation_id: Option<String>,
+    pub delta: String,
+    pub done: bool,
+    pub is_partial: bool,
+    pub error: Option<String>,
+}
+
+// 进行中的流式回复，按助手消息 id 保存取消信号
+fn active_streams() -> &'static Mutex<HashMap<String, watch::Sender<bool>>> {
+    static ACTIVE_STREAMS: OnceLock<Mutex<HashMap<String, watch::Sender<bool>>>> = OnceLock::new();
+    ACTIVE_STREAMS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn register_stream(message_id: &str) -> watch::Receiver<bool> {
+    let (sender, receiver) = watch::channel(false);
+    if let Ok(mut streams) = active_streams().lock() {
+        streams.insert(message_id.to_string(), sender);
+    }
+    receiver
+}
+
+fn unregister_stream(message_id: &str) {
+    if let Ok(mut streams) = active_streams().lock() {
+        streams.remove(message_id);
+    }
+}
+
+// ============ LLM Provider Commands ============
+
+#[tauri::command]
+pub async fn llm_provider_save(config: LlmProviderConfig) -> Result<ApiResponse<LlmProviderConfig>, String> {
+    provider_for(&config)?;
+    let conn = open_conn()?;
+
+    // 前端回传的是脱敏后的密钥时保留原值
+    let api_key = match config.api_key.as_deref() {
+        Some(key) if is_masked_key(key) => conn
+            .query_row("SELECT api_key FROM LlmProviders WHERE id = ?1", params![config.id], |row| {
+                row.get::<_, Option<String>>(0)
+            })
+            .optional()
+            .map_err(|e| e.to_string())?
+            .flatten(),
+        _ => config.api_key.clone(),
+    };
+
+    if config.is_default {
+        conn.execute("UPDATE LlmProviders SET is_default = 0", params![])
+            .map_err(|e| e.to_string())?;
+    }
+
+    conn.execute(
+        r#"INSERT OR REPLACE INTO LlmProviders (id, name, kind, base_url, api_key, model, timeout_secs,
+            max_retries, max_tokens, temperature, response_path, is_default, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)"#,
+        params![
+            config.id, config.name, config.kind, config.base_url, api_key, config.model,
+            config.timeout_secs, config.max_retries, config.max_tokens, config.temperature,
+            config.response_path, if config.is_default { 1i32 } else { 0i32 }, config.created_at
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(LlmProviderConfig { api_key, ..config }.masked()),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn llm_providers_get() -> Result<ApiResponse<Vec<LlmProviderConfig>>, String> {
+    let conn = open_conn()?;
+
+    let mut stmt = conn
+        .prepare("SELECT * FROM LlmProviders ORDER BY created_at ASC")
+        .map_err(|e| e.to_string())?;
+    let providers: Vec<LlmProviderConfig> = stmt
+        .query_map(params![], LlmProviderConfig::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .map(LlmProviderConfig::masked)
+        .collect();
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(providers),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn llm_provider_delete(id: String) -> Result<ApiResponse<String>, String> {
+    let conn = open_conn()?;
+
+    conn.execute("DELETE FROM LlmProviders WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some("Deleted".to_string()),
+        message: None,
+    })
+}
+
+// ============ Chat Commands ============
+
+#[tauri::command]
+pub async fn chat_send(
+    app: AppHandle,
+    user_id: String,
+    conversation_id: Option<String>,
+    text: String,
+) -> Result<ApiResponse<ChatMessage>, String> {
+    if text.trim().is_empty() {
+        return Err("Message must not be empty".to_string());
+    }
+
+    // 数据库连接不跨越 await，请求前后分别打开
+    let (config, mut request, categories, citations) = {
+        let conn = open_conn()?;
+        let config = load_default_provider(&conn)?;
+
+        let mut system = SYSTEM_PROMPT.to_string();
+        if let Some(id) = conversation_id.as_deref() {
+            let conversation = load_conversation(&conn, id)?
+                .filter(|c| c.user_id == user_id)
+                .ok_or_else(|| format!("Conversation not found: {}", id))?;
+            system.push('\n');
+            system.push_str(mode_prompt(&conversation.mode));
+        }
+
+        // 附带用户自己的健康数据，发送哪些类别由隐私设置决定
+        let settings = load_settings(&conn, &user_id)?;
+        let local_model = is_local_provider(&config);
+        if let Some(context) = build_health_context(&conn, &user_id, &settings, local_model, Utc::now())? {
+            system.push_str("\n\n");
+            system.push_str(&context);
+        }
+
+        // 附带知识库中与问题相关的段落，回答按编号标注出处
+        let mut citations = Vec::new();
+        if let Some((knowledge, sources)) = build_knowledge_context(&conn, &text)? {
+            system.push_str("\n\n");
+            system.push_str(&knowledge);
+            citations = sources;
+        }
+
+        let categories = settings.allowed_categories(local_model);
+        let tools = if supports_tools(&config) { tool_specs(&categories) } else { Vec::new() };
+        if !tools.is_empty() {
+            system.push_str("\n\n");
+            system.push_str(TOOLS_PROMPT);
+            system.push_str(&format!("当前时间：{}", Local::now().to_rfc3339_opts(SecondsFormat::Secs, false)));
+        }
+
+        let user_message = new_message(&user_id, conversation_id.as_deref(), ROLE_USER, text, None);
+        insert_chat_message(&conn, &user_message)?;
+        if let Some(id) = conversation_id.as_deref() {
+            touch_conversation(&conn, id, Some(&user_message.content))?;
+        }
+
+        let history = load_history(&conn, &user_id, conversation_id.as_deref(), HISTORY_TURNS)?;
+        let request = ChatRequest {
+            system: Some(system),
+            messages: to_llm_messages(&history),
+            tools,
+            disable_tool_calls: false,
+        };
+        (config, request, categories, citations)
+    };
+
+    // 助手消息的 id 在请求前生成，前端据此拼接增量并调用 chat_cancel；
+    // 先登记取消句柄再发出第一个事件，前端拿到 id 后立即取消也能生效
+    let mut reply = new_message(&user_id, conversation_id.as_deref(), ROLE_ASSISTANT, String::new(), None);
+    let cancel = register_stream(&reply.id);
+    let emit = |delta: &str, done: bool, is_partial: bool, error: Option<String>| {
+        let _ = app.emit(
+            CHAT_STREAM_EVENT,
+            ChatStreamEvent {
+                message_id: reply.id.clone(),
+                conversation_id: conversation_id.clone(),
+                delta: delta.to_string(),
+                done,
+                is_partial,
+                error,
+            },
+        );
+    };
+    emit("", false, false, None);
+
+    let mut tool_ctx = ToolContext {
+        app: &app,
+        user_id: &user_id,
+        message_id: &reply.id,
+        categories,
+        cancel: cancel.clone(),
+    };
+
+    // 模型返回工具调用时执行工具并把结果交回模型，直到得到不含工具调用的回复
+    let mut content = String::new();
+    let mut model = config.model.clone();
+    let mut cancelled = false;
+    let mut error: Option<String> = None;
+    for round in 0..=MAX_TOOL_ROUNDS {
+        // 最后一轮不再允许调用工具，让模型根据已有的工具结果作答
+        if round == MAX_TOOL_ROUNDS {
+            request.disable_tool_calls = true;
+        }
+
+        // 多轮的文本之间空一行
+        let separate = !content.is_empty();
+        let mut first_delta = true;
+        let result = stream_complete(&config, &request, cancel.clone(), |delta| {
+            if first_delta && separate {
+                emit("\n\n", false, false, None);
+            }
+            first_delta = false;
+            emit(delta, false, false, None);
+        })
+        .await;
+
+        let outcome = match result {
+            Ok(outcome) => outcome,
+            Err(e) if content.is_empty() => {
+                unregister_stream(&reply.id);
+                emit("", true, false, Some(e.clone()));
+                return Err(e);
+            }
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        };
+
+        if separate && !outcome.content.is_empty() {
+            content.push_str("\n\n");
+        }
+        content.push_str(&outcome.content);
+        model = outcome.model;
+        if outcome.cancelled {
+            cancelled = true;
+            break;
+        }
+        if outcome.error.is_some() {
+            error = outcome.error;
+            break;
+        }
+        if outcome.tool_calls.is_empty() || request.disable_tool_calls {
+            break;
+        }
+
+        request.messages.push(LlmMessage {
+            role: ROLE_ASSISTANT.to_string(),
+            content: outcome.content,
+            tool_calls: outcome.tool_calls.clone(),
+            tool_call_id: None,
+        });
+        for call in &outcome.tool_calls {
+            let result = execute_tool(&mut tool_ctx, call).await;
+            request.messages.push(LlmMessage {
+                role: ROLE_TOOL.to_string(),
+                content: result,
+                tool_calls: Vec::new(),
+                tool_call_id: Some(call.id.clone()),
+            });
+        }
+    }
+    unregister_stream(&reply.id);
+
+    let is_partial = cancelled || error.is_some();
+    emit("", true, is_partial, error.clone());
+    if let Some(e) = &error {
+        eprintln!("Chat stream interrupted: {}", e);
+    }
+
+    // 只保存最终内容；什么都没收到时不留下空的助手消息
+    if content.is_empty() {
+        return match error {
+            Some(e) => Err(e),
+            None => Ok(ApiResponse {
+                success: true,
+                data: None,
+                message: Some("Cancelled".to_string()),
+            }),
+        };
+    }
+
+    reply.content = content;
+    reply.model = Some(model);
+    reply.is_partial = is_partial;
+    reply.citations = citations;
+    reply.created_at = format_timestamp(&Utc::now());
+
+    let conn = open_conn()?;
+    insert_chat_message(&conn, &reply)?;
+    if let Some(id) = conversation_id.as_deref() {
+        touch_conversation(&conn, id, None)?;
+    }
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(reply),
+        message: error,
+    })
+}
+
+#[tauri::command]
+pub async fn chat_cancel(message_id: String) -> Result<ApiResponse<bool>, String> {
+    let cancelled = match active_streams().lock() {
+        Ok(streams) => streams
+            .get(&message_id)
+            .map(|sender| sender.send(true).is_ok())
+            .unwrap_or(false),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(cancelled),
+        message: None,
+    })
+}
@@ -0,0 +1,586 @@
+use chrono::{DateTime, Local, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{oneshot, watch};
+use uuid::Uuid;
+
+use crate::database::{
+    blood_glucose_create, food_entry_create, medication_mark_taken, open_conn, ApiResponse, BloodGlucose, FoodEntry,
+    Medication,
+};
+use crate::datetime::{format_timestamp, parse_local_timestamp, parse_timestamp};
+use crate::enums::{meal_type_label, measurement_time_label, ENTRY_SOURCE_MANUAL, MEAL_SNACK, MEASUREMENT_RANDOM};
+use crate::health_context::{CATEGORY_GLUCOSE, CATEGORY_MEDICATIONS, CATEGORY_NUTRITION};
+use crate::llm::{wait_for_cancel, ToolCall, ToolSpec};
+use crate::meal_pairing::load_readings;
+use crate::sick_days::load_sick_periods;
+use crate::targets::load_targets;
+
+pub const TOOL_CONFIRM_EVENT: &str = "chat-tool-confirm";
+
+pub const TOOL_QUERY_FOOD: &str = "query_food_entries";
+pub const TOOL_GLUCOSE_STATS: &str = "glucose_stats";
+pub const TOOL_MEDICATION_SCHEDULE: &str = "medication_schedule";
+pub const TOOL_LOG_FOOD: &str = "log_food_entry";
+pub const TOOL_LOG_GLUCOSE: &str = "log_blood_glucose";
+pub const TOOL_MARK_MEDICATION: &str = "mark_medication_taken";
+
+// 一次回复中最多执行的工具调用轮数，避免模型反复调用
+pub(crate) const MAX_TOOL_ROUNDS: usize = 5;
+// 用户未响应确认请求时按拒绝处理
+const CONFIRM_TIMEOUT_SECS: u64 = 300;
+const MAX_QUERY_ROWS: usize = 100;
+// 血糖仪可测范围，超出时多半是单位或输入错误
+const MIN_GLUCOSE: f64 = 1.1;
+const MAX_GLUCOSE: f64 = 33.3;
+
+pub(crate) const TOOLS_PROMPT: &str = "你可以调用工具查询用户的饮食、血糖和用药记录，或替用户记录饮食、血糖和服药。\
+记录类工具会先请用户确认，被拒绝时不要重复尝试。时间参数使用带时区的 ISO 8601 格式。";
+
+/// 写入操作执行前发给前端的确认请求，前端调用 chat_tool_confirm 回复
+#[derive(Debug, Serialize, Clone)]
+pub struct ToolConfirmRequest {
+    pub confirmation_id: String,
+    pub message_id: String,
+    pub tool: String,
+    pub arguments: Value,
+    pub summary: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RangeArgs {
+    start: String,
+    end: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LogFoodArgs {
+    meal_type: i32,
+    meal_time: String,
+    food_name: String,
+    quantity: f64,
+    calories: f64,
+    carbohydrates: f64,
+    #[serde(default)]
+    protein: f64,
+    #[serde(default)]
+    fat: f64,
+    gi: Option<f64>,
+    notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LogGlucoseArgs {
+    value: f64,
+    measurement_time: i32,
+    measured_at: String,
+    notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MarkTakenArgs {
+    medication_id: String,
+    taken_at: Option<String>,
+}
+
+fn range_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "start": { "type": "string", "description": "开始时间（ISO 8601）" },
+            "end": { "type": "string", "description": "结束时间（ISO 8601）" }
+        },
+        "required": ["start", "end"]
+    })
+}
+
+/// 本次对话可用的工具；查询类工具受健康数据隐私设置约束
+pub(crate) fn tool_specs(categories: &[&str]) -> Vec<ToolSpec> {
+    let mut tools = Vec::new();
+
+    if categories.contains(&CATEGORY_NUTRITION) {
+        tools.push(ToolSpec {
+            name: TOOL_QUERY_FOOD.to_string(),
+            description: "查询时间范围内的饮食记录".to_string(),
+            parameters: range_schema(),
+        });
+    }
+    if categories.contains(&CATEGORY_GLUCOSE) {
+        tools.push(ToolSpec {
+            name: TOOL_GLUCOSE_STATS.to_string(),
+            description: "查询时间范围内的血糖读数与统计（平均值、范围、TIR、低血糖次数、各时段平均）".to_string(),
+            parameters: range_schema(),
+        });
+    }
+    if categories.contains(&CATEGORY_MEDICATIONS) {
+        tools.push(ToolSpec {
+            name: TOOL_MEDICATION_SCHEDULE.to_string(),
+            description: "查询时间范围内的用药计划和服药情况，结果中的 id 可用于标记服药".to_string(),
+            parameters: range_schema(),
+        });
+    }
+
+    tools.push(ToolSpec {
+        name: TOOL_LOG_FOOD.to_string(),
+        description: "记录一条饮食，需要用户确认".to_string(),
+        parameters: json!({
+            "type": "object",
+            "properties": {
+                "meal_type": { "type": "integer", "description": "0 早餐，1 午餐，2 晚餐，3 加餐" },
+                "meal_time": { "type": "string", "description": "进餐时间（ISO 8601）" },
+                "food_name": { "type": "string" },
+                "quantity": { "type": "number", "description": "份量（克）" },
+                "calories": { "type": "number", "description": "热量（kcal）" },
+                "carbohydrates": { "type": "number", "description": "碳水化合物（克）" },
+                "protein": { "type": "number", "description": "蛋白质（克）" },
+                "fat": { "type": "number", "description": "脂肪（克）" },
+                "gi": { "type": "number", "description": "升糖指数" },
+                "notes": { "type": "string" }
+            },
+            "required": ["meal_type", "meal_time", "food_name", "quantity", "calories", "carbohydrates"]
+        }),
+    });
+    tools.push(ToolSpec {
+        name: TOOL_LOG_GLUCOSE.to_string(),
+        description: "记录一次血糖测量，需要用户确认".to_string(),
+        parameters: json!({
+            "type": "object",
+            "properties": {
+                "value": { "type": "number", "description": "血糖值（mmol/L）" },
+                "measurement_time": {
+                    "type": "integer",
+                    "description": "0 空腹，1 餐前，2 餐后1h，3 餐后2h，4 睡前，5 夜间，6 随机"
+                },
+                "measured_at": { "type": "string", "description": "测量时间（ISO 8601）" },
+                "notes": { "type": "string" }
+            },
+            "required": ["value", "measurement_time", "measured_at"]
+        }),
+    });
+    tools.push(ToolSpec {
+        name: TOOL_MARK_MEDICATION.to_string(),
+        description: "把一次用药计划标记为已服用，需要用户确认".to_string(),
+        parameters: json!({
+            "type": "object",
+            "properties": {
+                "medication_id": { "type": "string", "description": "medication_schedule 返回的 id" },
+                "taken_at": { "type": "string", "description": "实际服药时间（ISO 8601），默认为当前时间" }
+            },
+            "required": ["medication_id"]
+        }),
+    });
+    tools
+}
+
+fn parse_args<T: serde::de::DeserializeOwned>(call: &ToolCall) -> Result<T, String> {
+    serde_json::from_value(call.arguments.clone()).map_err(|e| format!("Invalid arguments for {}: {}", call.name, e))
+}
+
+fn parse_time_arg(name: &str, value: &str) -> Result<DateTime<Utc>, String> {
+    parse_local_timestamp(value).ok_or_else(|| format!("Invalid {}: {}", name, value))
+}
+
+fn parse_range(call: &ToolCall) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let args: RangeArgs = parse_args(call)?;
+    let start = parse_time_arg("start", &args.start)?;
+    let end = parse_time_arg("end", &args.end)?;
+    if start > end {
+        return Err("start must not be after end".to_string());
+    }
+    Ok((start, end))
+}
+
+// 返回给模型的时间使用本地时间，便于直接回答用户
+fn local_time(dt: &DateTime<Utc>) -> String {
+    dt.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()
+}
+
+fn query_food(conn: &Connection, user_id: &str, call: &ToolCall) -> Result<Value, String> {
+    let (start, end) = parse_range(call)?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM FoodEntries
+            WHERE user_id = ?1 AND meal_time >= ?2 AND meal_time <= ?3
+            ORDER BY meal_time ASC"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let entries: Vec<(DateTime<Utc>, FoodEntry)> = stmt
+        .query_map(
+            params![
+                user_id,
+                start.format("%Y-%m-%d").to_string(),
+                (end + chrono::Duration::days(1)).format("%Y-%m-%d").to_string()
+            ],
+            FoodEntry::from_row,
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|entry| Some((parse_timestamp(&entry.meal_time)?, entry)))
+        .filter(|(t, _)| *t >= start && *t <= end)
+        .collect();
+
+    let items: Vec<Value> = entries
+        .iter()
+        .take(MAX_QUERY_ROWS)
+        .map(|(t, e)| {
+            json!({
+                "time": local_time(t),
+                "meal": meal_type_label(e.meal_type),
+                "food_name": e.food_name,
+                "quantity_g": e.quantity,
+                "calories": e.calories,
+                "carbohydrates": e.carbohydrates,
+                "protein": e.protein,
+                "fat": e.fat,
+            })
+        })
+        .collect();
+
+    Ok(json!({
+        "count": entries.len(),
+        "truncated": entries.len() > MAX_QUERY_ROWS,
+        "total_calories": entries.iter().map(|(_, e)| e.calories).sum::<f64>(),
+        "total_carbohydrates": entries.iter().map(|(_, e)| e.carbohydrates).sum::<f64>(),
+        "entries": items,
+    }))
+}
+
+fn glucose_stats(conn: &Connection, user_id: &str, call: &ToolCall) -> Result<Value, String> {
+    let (start, end) = parse_range(call)?;
+    let readings = load_readings(conn, user_id, start, end)?;
+    if readings.is_empty() {
+        return Ok(json!({ "count": 0 }));
+    }
+
+    let targets = load_targets(conn, user_id)?;
+    let sick = load_sick_periods(conn, user_id)?;
+    let current = targets.at(end);
+    let values: Vec<f64> = readings.iter().map(|(_, r)| r.value).collect();
+    let count = values.len() as f64;
+    let mut by_time: BTreeMap<i32, Vec<(f64, bool)>> = BTreeMap::new();
+    for (t, reading) in &readings {
+        by_time
+            .entry(reading.measurement_time)
+            .or_default()
+            .push((reading.value, targets.in_target(*t, reading)));
+    }
+    let per_time: Vec<Value> = by_time
+        .iter()
+        .map(|(time, values)| {
+            let (low, high) = current.range_for(*time);
+            json!({
+                "measurement_time": measurement_time_label(*time),
+                "count": values.len(),
+                "mean": values.iter().map(|(v, _)| v).sum::<f64>() / values.len() as f64,
+                "target": format!("{:.1}–{:.1}", low, high),
+                "in_target_percent": values.iter().filter(|(_, ok)| *ok).count() as f64 / values.len() as f64 * 100.0,
+            })
+        })
+        .collect();
+    let recent: Vec<Value> = readings
+        .iter()
+        .rev()
+        .take(MAX_QUERY_ROWS)
+        .map(|(t, r)| {
+            json!({
+                "time": local_time(t),
+                "value": r.value,
+                "measurement_time": measurement_time_label(r.measurement_time),
+                "sick_day": sick.contains(*t),
+            })
+        })
+        .collect();
+
+    Ok(json!({
+        "unit": "mmol/L",
+        "count": values.len(),
+        "mean": values.iter().sum::<f64>() / count,
+        "min": values.iter().cloned().fold(f64::INFINITY, f64::min),
+        "max": values.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+        "time_in_range": current.tir_label(),
+        "time_in_range_percent": readings.iter().filter(|(t, r)| targets.at(*t).in_tir(r.value)).count() as f64 / count * 100.0,
+        "hypo_count": readings.iter().filter(|(t, r)| targets.at(*t).is_hypo(r.value)).count(),
+        "sick_day_count": readings.iter().filter(|(t, _)| sick.contains(*t)).count(),
+        "by_measurement_time": per_time,
+        "recent_readings": recent,
+    }))
+}
+
+fn medication_schedule(conn: &Connection, user_id: &str, call: &ToolCall) -> Result<Value, String> {
+    let (start, end) = parse_range(call)?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM Medications
+            WHERE user_id = ?1 AND scheduled_time >= ?2 AND scheduled_time <= ?3
+            ORDER BY scheduled_time ASC"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let doses: Vec<(DateTime<Utc>, Medication)> = stmt
+        .query_map(
+            params![
+                user_id,
+                start.format("%Y-%m-%d").to_string(),
+                (end + chrono::Duration::days(1)).format("%Y-%m-%d").to_string()
+            ],
+            Medication::from_row,
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|m| Some((parse_timestamp(&m.scheduled_time)?, m)))
+        .filter(|(t, _)| *t >= start && *t <= end)
+        .collect();
+
+    let items: Vec<Value> = doses
+        .iter()
+        .take(MAX_QUERY_ROWS)
+        .map(|(t, m)| {
+            json!({
+                "id": m.id,
+                "drug_name": m.drug_name,
+                "dose": m.dose,
+                "unit": m.unit,
+                "scheduled_time": local_time(t),
+                "is_taken": m.is_taken,
+                "actual_time": m.actual_time.as_deref().and_then(parse_timestamp).map(|t| local_time(&t)),
+            })
+        })
+        .collect();
+
+    Ok(json!({
+        "count": doses.len(),
+        "taken": doses.iter().filter(|(_, m)| m.is_taken).count(),
+        "doses": items,
+    }))
+}
+
+fn food_entry_from_args(user_id: &str, args: LogFoodArgs) -> Result<FoodEntry, String> {
+    if !(0..=MEAL_SNACK).contains(&args.meal_type) {
+        return Err(format!("Invalid meal_type: {}", args.meal_type));
+    }
+    if args.food_name.trim().is_empty() {
+        return Err("food_name must not be empty".to_string());
+    }
+    if [args.quantity, args.calories, args.carbohydrates, args.protein, args.fat].iter().any(|v| *v < 0.0) {
+        return Err("Nutrition values must not be negative".to_string());
+    }
+    let meal_time = parse_time_arg("meal_time", &args.meal_time)?;
+
+    Ok(FoodEntry {
+        id: Uuid::new_v4().to_string(),
+        user_id: user_id.to_string(),
+        created_at: format_timestamp(&Utc::now()),
+        meal_type: args.meal_type,
+        meal_time: format_timestamp(&meal_time),
+        food_name: args.food_name.trim().to_string(),
+        quantity: args.quantity,
+        calories: args.calories,
+        carbohydrates: args.carbohydrates,
+        protein: args.protein,
+        fat: args.fat,
+        gi: args.gi,
+        gl: args.gi.map(|gi| gi * args.carbohydrates / 100.0),
+        source: ENTRY_SOURCE_MANUAL,
+        image_path: None,
+        notes: args.notes,
+    })
+}
+
+fn blood_glucose_from_args(user_id: &str, args: LogGlucoseArgs) -> Result<BloodGlucose, String> {
+    if !(0..=MEASUREMENT_RANDOM).contains(&args.measurement_time) {
+        return Err(format!("Invalid measurement_time: {}", args.measurement_time));
+    }
+    if !(MIN_GLUCOSE..=MAX_GLUCOSE).contains(&args.value) {
+        return Err(format!("Glucose value out of range (mmol/L): {}", args.value));
+    }
+    let measured_at = parse_time_arg("measured_at", &args.measured_at)?;
+
+    Ok(BloodGlucose {
+        id: Uuid::new_v4().to_string(),
+        user_id: user_id.to_string(),
+        created_at: format_timestamp(&Utc::now()),
+        value: args.value,
+        measurement_time: args.measurement_time,
+        measurement_time_exact: Some(format_timestamp(&measured_at)),
+        before_meal_glucose: None,
+        after_meal_glucose: None,
+        related_meal: None,
+        notes: args.notes,
+        device_name: None,
+        device_serial: None,
+    })
+}
+
+// 确认请求按 confirmation_id 等待前端回复
+fn pending_confirmations() -> &'static Mutex<HashMap<String, oneshot::Sender<bool>>> {
+    static PENDING: OnceLock<Mutex<HashMap<String, oneshot::Sender<bool>>>> = OnceLock::new();
+    PENDING.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 通知前端确认写入操作并等待回复；超时或回复被取消时视为拒绝
+async fn request_confirmation(ctx: &mut ToolContext<'_>, call: &ToolCall, summary: String) -> Result<bool, String> {
+    let confirmation_id = Uuid::new_v4().to_string();
+    let (sender, receiver) = oneshot::channel();
+    pending_confirmations()
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(confirmation_id.clone(), sender);
+
+    let _ = ctx.app.emit(
+        TOOL_CONFIRM_EVENT,
+        ToolConfirmRequest {
+            confirmation_id: confirmation_id.clone(),
+            message_id: ctx.message_id.to_string(),
+            tool: call.name.clone(),
+            arguments: call.arguments.clone(),
+            summary,
+        },
+    );
+
+    let approved = tokio::select! {
+        reply = receiver => reply.unwrap_or(false),
+        _ = tokio::time::sleep(Duration::from_secs(CONFIRM_TIMEOUT_SECS)) => false,
+        _ = wait_for_cancel(&mut ctx.cancel) => false,
+    };
+
+    if let Ok(mut pending) = pending_confirmations().lock() {
+        pending.remove(&confirmation_id);
+    }
+    Ok(approved)
+}
+
+pub(crate) struct ToolContext<'a> {
+    pub app: &'a AppHandle,
+    pub user_id: &'a str,
+    pub message_id: &'a str,
+    pub categories: Vec<&'static str>,
+    pub cancel: watch::Receiver<bool>,
+}
+
+fn declined() -> Value {
+    json!({ "status": "declined", "message": "用户拒绝了这次操作" })
+}
+
+async fn run_tool(ctx: &mut ToolContext<'_>, call: &ToolCall) -> Result<Value, String> {
+    let allowed = |category: &str| {
+        if ctx.categories.contains(&category) {
+            Ok(())
+        } else {
+            Err(format!("{} is disabled by the user's privacy settings", call.name))
+        }
+    };
+
+    match call.name.as_str() {
+        TOOL_QUERY_FOOD => {
+            allowed(CATEGORY_NUTRITION)?;
+            query_food(&open_conn()?, ctx.user_id, call)
+        }
+        TOOL_GLUCOSE_STATS => {
+            allowed(CATEGORY_GLUCOSE)?;
+            glucose_stats(&open_conn()?, ctx.user_id, call)
+        }
+        TOOL_MEDICATION_SCHEDULE => {
+            allowed(CATEGORY_MEDICATIONS)?;
+            medication_schedule(&open_conn()?, ctx.user_id, call)
+        }
+        TOOL_LOG_FOOD => {
+            let entry = food_entry_from_args(ctx.user_id, parse_args(call)?)?;
+            let summary = format!(
+                "记录{}：{} {:.0} g，{:.0} kcal，碳水 {:.0} g（{}）",
+                meal_type_label(entry.meal_type),
+                entry.food_name,
+                entry.quantity,
+                entry.calories,
+                entry.carbohydrates,
+                parse_timestamp(&entry.meal_time).map(|t| local_time(&t)).unwrap_or_default()
+            );
+            if !request_confirmation(ctx, call, summary).await? {
+                return Ok(declined());
+            }
+            let created = food_entry_create(entry).await?;
+            Ok(json!({ "status": "created", "id": created.data.map(|e| e.id) }))
+        }
+        TOOL_LOG_GLUCOSE => {
+            let reading = blood_glucose_from_args(ctx.user_id, parse_args(call)?)?;
+            let summary = format!(
+                "记录血糖：{} {:.1} mmol/L（{}）",
+                measurement_time_label(reading.measurement_time),
+                reading.value,
+                reading.measured_at().map(|t| local_time(&t)).unwrap_or_default()
+            );
+            if !request_confirmation(ctx, call, summary).await? {
+                return Ok(declined());
+            }
+            let created = blood_glucose_create(reading).await?;
+            Ok(json!({ "status": "created", "id": created.data.map(|r| r.id) }))
+        }
+        TOOL_MARK_MEDICATION => {
+            let args: MarkTakenArgs = parse_args(call)?;
+            let taken_at = match args.taken_at.as_deref() {
+                Some(value) => parse_time_arg("taken_at", value)?,
+                None => Utc::now(),
+            };
+            // 只能标记当前用户自己的用药
+            let medication = open_conn()?
+                .query_row(
+                    "SELECT * FROM Medications WHERE id = ?1 AND user_id = ?2",
+                    params![args.medication_id, ctx.user_id],
+                    Medication::from_row,
+                )
+                .optional()
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("Medication not found: {}", args.medication_id))?;
+
+            let summary = format!(
+                "标记已服药：{} {} {}（{}）",
+                medication.drug_name,
+                medication.dose,
+                medication.unit,
+                local_time(&taken_at)
+            );
+            if !request_confirmation(ctx, call, summary).await? {
+                return Ok(declined());
+            }
+            medication_mark_taken(medication.id.clone(), format_timestamp(&taken_at)).await?;
+            Ok(json!({ "status": "updated", "id": medication.id }))
+        }
+        other => Err(format!("Unknown tool: {}", other)),
+    }
+}
+
+/// 执行一次工具调用，返回给模型的 JSON 文本；错误同样返回给模型，由模型向用户说明
+pub(crate) async fn execute_tool(ctx: &mut ToolContext<'_>, call: &ToolCall) -> String {
+    match run_tool(ctx, call).await {
+        Ok(result) => result.to_string(),
+        Err(e) => {
+            eprintln!("Tool {} failed: {}", call.name, e);
+            json!({ "status": "error", "message": e }).to_string()
+        }
+    }
+}
+
+// ============ Chat Tool Commands ============
+
+#[tauri::command]
+pub async fn chat_tool_confirm(confirmation_id: String, approved: bool) -> Result<ApiResponse<bool>, String> {
+    let sender = pending_confirmations()
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&confirmation_id);
+    let delivered = match sender {
+        Some(sender) => sender.send(approved).is_ok(),
+        None => false,
+    };
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(delivered),
+        message: None,
+    })
+}
@@ -0,0 +1,288 @@
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::database::{open_conn, ApiResponse, ChatMessage};
+use crate::datetime::format_timestamp;
+
+// 对话模式（DESIGN.md 2.7.4）
+pub const MODE_SUMMARY: &str = "summary";
+pub const MODE_QA: &str = "qa";
+pub const MODE_COACHING: &str = "coaching";
+
+pub const DEFAULT_TITLE: &str = "新对话";
+
+// 首条消息自动生成标题时截取的字符数
+const AUTO_TITLE_CHARS: usize = 20;
+const MAX_PAGE_SIZE: i64 = 200;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Conversation {
+    pub id: String,
+    pub user_id: String,
+    pub title: String,
+    pub mode: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub is_pinned: bool,
+    pub is_archived: bool,
+}
+
+impl Conversation {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Conversation> {
+        Ok(Conversation {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            title: row.get(2)?,
+            mode: row.get(3)?,
+            created_at: row.get(4)?,
+            updated_at: row.get(5)?,
+            is_pinned: row.get::<_, i32>(6)? == 1,
+            is_archived: row.get::<_, i32>(7)? == 1,
+        })
+    }
+}
+
+/// 按游标分页的消息；items 按时间升序，next_cursor 为本页最早一条消息的 id，没有更早的消息时为空
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChatMessagePage {
+    pub items: Vec<ChatMessage>,
+    pub next_cursor: Option<String>,
+}
+
+fn validate_mode(mode: &str) -> Result<(), String> {
+    match mode {
+        MODE_SUMMARY | MODE_QA | MODE_COACHING => Ok(()),
+        other => Err(format!("Unknown conversation mode: {}", other)),
+    }
+}
+
+/// 不同模式在通用系统提示词之后追加的说明
+pub(crate) fn mode_prompt(mode: &str) -> &'static str {
+    match mode {
+        MODE_SUMMARY => "当前为总结模式：围绕用户这一天或这一周的饮食、运动、睡眠和血糖做回顾，指出亮点和需要改进的地方。",
+        MODE_COACHING => "当前为指导模式：主动引导用户补全记录，一次只提一个问题，帮助用户养成规律记录和自我管理的习惯。",
+        _ => "当前为问答模式：直接回答用户的问题。",
+    }
+}
+
+pub(crate) fn load_conversation(conn: &Connection, id: &str) -> Result<Option<Conversation>, String> {
+    conn.query_row("SELECT * FROM Conversations WHERE id = ?1", params![id], Conversation::from_row)
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+/// 新消息写入后刷新对话的更新时间；标题仍为默认值时用首条用户消息生成标题
+pub(crate) fn touch_conversation(conn: &Connection, id: &str, user_text: Option<&str>) -> Result<(), String> {
+    let now = format_timestamp(&Utc::now());
+    conn.execute("UPDATE Conversations SET updated_at = ?1 WHERE id = ?2", params![now, id])
+        .map_err(|e| e.to_string())?;
+
+    if let Some(text) = user_text {
+        let title: String = text.trim().chars().take(AUTO_TITLE_CHARS).collect();
+        if !title.is_empty() {
+            conn.execute(
+                "UPDATE Conversations SET title = ?1 WHERE id = ?2 AND title = ?3",
+                params![title, id, DEFAULT_TITLE],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+fn require_updated(changed: usize, id: &str) -> Result<(), String> {
+    if changed == 0 {
+        return Err(format!("Conversation not found: {}", id));
+    }
+    Ok(())
+}
+
+// ============ Conversation Commands ============
+
+#[tauri::command]
+pub async fn conversation_create(
+    user_id: String,
+    title: Option<String>,
+    mode: String,
+) -> Result<ApiResponse<Conversation>, String> {
+    validate_mode(&mode)?;
+    let conn = open_conn()?;
+
+    let now = format_timestamp(&Utc::now());
+    let title = title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| DEFAULT_TITLE.to_string());
+    let conversation = Conversation {
+        id: Uuid::new_v4().to_string(),
+        user_id,
+        title,
+        mode,
+        created_at: now.clone(),
+        updated_at: now,
+        is_pinned: false,
+        is_archived: false,
+    };
+
+    conn.execute(
+        r#"INSERT INTO Conversations (id, user_id, title, mode, created_at, updated_at, is_pinned, is_archived)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, 0)"#,
+        params![
+            conversation.id, conversation.user_id, conversation.title, conversation.mode,
+            conversation.created_at, conversation.updated_at
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(conversation),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn conversations_get(
+    user_id: String,
+    include_archived: bool,
+) -> Result<ApiResponse<Vec<Conversation>>, String> {
+    let conn = open_conn()?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM Conversations
+            WHERE user_id = ?1 AND (?2 = 1 OR is_archived = 0)
+            ORDER BY is_pinned DESC, updated_at DESC"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let conversations: Vec<Conversation> = stmt
+        .query_map(
+            params![user_id, if include_archived { 1i32 } else { 0i32 }],
+            Conversation::from_row,
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(conversations),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn conversation_rename(id: String, title: String) -> Result<ApiResponse<String>, String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err("Title must not be empty".to_string());
+    }
+    let conn = open_conn()?;
+
+    let changed = conn
+        .execute("UPDATE Conversations SET title = ?1 WHERE id = ?2", params![title, id])
+        .map_err(|e| e.to_string())?;
+    require_updated(changed, &id)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some("Updated".to_string()),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn conversation_archive(id: String, archived: bool) -> Result<ApiResponse<String>, String> {
+    let conn = open_conn()?;
+
+    let changed = conn
+        .execute(
+            "UPDATE Conversations SET is_archived = ?1 WHERE id = ?2",
+            params![if archived { 1i32 } else { 0i32 }, id],
+        )
+        .map_err(|e| e.to_string())?;
+    require_updated(changed, &id)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some("Updated".to_string()),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn conversation_pin(id: String, pinned: bool) -> Result<ApiResponse<String>, String> {
+    let conn = open_conn()?;
+
+    let changed = conn
+        .execute(
+            "UPDATE Conversations SET is_pinned = ?1 WHERE id = ?2",
+            params![if pinned { 1i32 } else { 0i32 }, id],
+        )
+        .map_err(|e| e.to_string())?;
+    require_updated(changed, &id)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some("Updated".to_string()),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn conversation_delete(id: String) -> Result<ApiResponse<String>, String> {
+    let mut conn = open_conn()?;
+
+    // 对话与其消息一起删除
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM ChatMessages WHERE conversation_id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM Conversations WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some("Deleted".to_string()),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn conversation_messages_get(
+    conversation_id: String,
+    cursor: Option<String>,
+    limit: i64,
+) -> Result<ApiResponse<ChatMessagePage>, String> {
+    let conn = open_conn()?;
+    let limit = limit.clamp(1, MAX_PAGE_SIZE);
+
+    // 游标为上一页最早一条消息的 id，按 (created_at, id) 向前翻页，同一时间戳的消息也不会重复或遗漏
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM ChatMessages
+            WHERE conversation_id = ?1
+              AND (?2 IS NULL OR (created_at, id) < (SELECT created_at, id FROM ChatMessages WHERE id = ?2))
+            ORDER BY created_at DESC, id DESC
+            LIMIT ?3"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let mut items: Vec<ChatMessage> = stmt
+        .query_map(params![conversation_id, cursor, limit + 1], ChatMessage::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let has_more = items.len() as i64 > limit;
+    items.truncate(limit as usize);
+    let next_cursor = if has_more { items.last().map(|m| m.id.clone()) } else { None };
+    items.reverse();
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(ChatMessagePage { items, next_cursor }),
+        message: None,
+    })
+}
@@ -1,7 +1,17 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use rusqlite::{Connection, params};
 use std::fs;
 
+use crate::datetime::parse_timestamp;
+use crate::glucose_checks::{
+    cancel_post_meal_checks, complete_matching_check, revert_completed_checks, schedule_post_meal_checks,
+};
+use crate::ketones::raise_dka_warnings;
+use crate::knowledge::KnowledgeCitation;
+use crate::meal_pairing::repair_around;
+use crate::reminders::{acknowledge_by_source, SOURCE_MEDICATION};
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct User {
     pub id: String,
@@ -20,6 +30,34 @@ pub struct User {
     pub target_hb_a1c: Option<f64>,
     pub target_calories: Option<f64>,
     pub target_carbohydrates: Option<f64>,
+    /// 预产期与分娩日期，用于妊娠期模式和孕周计算
+    pub expected_due_date: Option<String>,
+    pub delivery_date: Option<String>,
+}
+
+impl User {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<User> {
+        Ok(User {
+            id: row.get(0)?,
+            username: row.get(1)?,
+            email: row.get(2)?,
+            phone: row.get(3)?,
+            password_hash: row.get(4)?,
+            created_at: row.get(5)?,
+            birthday: row.get(6)?,
+            gender: row.get(7)?,
+            height: row.get(8)?,
+            diabetes_type: row.get(9)?,
+            diagnosis_date: row.get(10)?,
+            treatment_plan: row.get(11)?,
+            target_weight: row.get(12)?,
+            target_hb_a1c: row.get(13)?,
+            target_calories: row.get(14)?,
+            target_carbohydrates: row.get(15)?,
+            expected_due_date: row.get(16)?,
+            delivery_date: row.get(17)?,
+        })
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -42,6 +80,29 @@ pub struct FoodEntry {
     pub notes: Option<String>,
 }
 
+impl FoodEntry {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<FoodEntry> {
+        Ok(FoodEntry {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            created_at: row.get(2)?,
+            meal_type: row.get(3)?,
+            meal_time: row.get(4)?,
+            food_name: row.get(5)?,
+            quantity: row.get(6)?,
+            calories: row.get(7)?,
+            carbohydrates: row.get(8)?,
+            protein: row.get(9)?,
+            fat: row.get(10)?,
+            gi: row.get(11)?,
+            gl: row.get(12)?,
+            source: row.get(13)?,
+            image_path: row.get(14)?,
+            notes: row.get(15)?,
+        })
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BloodGlucose {
     pub id: String,
@@ -58,6 +119,30 @@ pub struct BloodGlucose {
     pub device_serial: Option<String>,
 }
 
+impl BloodGlucose {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<BloodGlucose> {
+        Ok(BloodGlucose {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            created_at: row.get(2)?,
+            value: row.get(3)?,
+            measurement_time: row.get(4)?,
+            measurement_time_exact: row.get(5)?,
+            before_meal_glucose: row.get(6)?,
+            after_meal_glucose: row.get(7)?,
+            related_meal: row.get(8)?,
+            notes: row.get(9)?,
+            device_name: row.get(10)?,
+            device_serial: row.get(11)?,
+        })
+    }
+
+    /// 实际测量时间，未填写精确时间时以记录创建时间为准
+    pub(crate) fn measured_at(&self) -> Option<DateTime<Utc>> {
+        parse_timestamp(self.measurement_time_exact.as_deref().unwrap_or(&self.created_at))
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Medication {
     pub id: String,
@@ -76,6 +161,27 @@ pub struct Medication {
     pub notes: Option<String>,
 }
 
+impl Medication {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Medication> {
+        Ok(Medication {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            created_at: row.get(2)?,
+            drug_name: row.get(3)?,
+            r#type: row.get(4)?,
+            dose: row.get(5)?,
+            unit: row.get(6)?,
+            timing: row.get(7)?,
+            insulin_type: row.get(8)?,
+            insulin_duration: row.get(9)?,
+            scheduled_time: row.get(10)?,
+            actual_time: row.get(11)?,
+            is_taken: row.get::<_, i32>(12)? == 1,
+            notes: row.get(13)?,
+        })
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ChatMessage {
     pub id: String,
@@ -84,6 +190,32 @@ pub struct ChatMessage {
     pub role: String,
     pub content: String,
     pub model: Option<String>,
+    pub conversation_id: Option<String>,
+    // 流式回复被取消或中断时只保存了部分内容
+    #[serde(default)]
+    pub is_partial: bool,
+    // 回答引用的知识库段落，按 JSON 保存
+    #[serde(default)]
+    pub citations: Vec<KnowledgeCitation>,
+}
+
+impl ChatMessage {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ChatMessage> {
+        Ok(ChatMessage {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            created_at: row.get(2)?,
+            role: row.get(3)?,
+            content: row.get(4)?,
+            model: row.get(5)?,
+            conversation_id: row.get(6)?,
+            is_partial: row.get::<_, i32>(7)? == 1,
+            citations: row
+                .get::<_, Option<String>>(8)?
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+        })
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -103,7 +235,7 @@ pub struct ApiResponse<T> {
 
 use tauri::Manager;
 
-fn open_conn() -> Result<Connection, String> {
+pub(crate) fn data_dir() -> std::path::PathBuf {
     // 尝试使用 Tauri 应用目录，如果失败则使用本地数据目录
     let app_data_dir = if let Some(app_handle) = try_get_app_handle() {
         app_handle.path().app_data_dir().ok()
@@ -120,8 +252,11 @@ fn open_conn() -> Result<Connection, String> {
     if let Err(e) = fs::create_dir_all(&data_dir) {
         eprintln!("Failed to create data directory: {}", e);
     }
-    
-    let db_path = data_dir.join("vitanote.db");
+    data_dir
+}
+
+pub(crate) fn open_conn() -> Result<Connection, String> {
+    let db_path = data_dir().join("vitanote.db");
     eprintln!("Database path: {:?}", db_path);
     Connection::open(&db_path).map_err(|e| e.to_string())
 }
@@ -134,11 +269,11 @@ pub fn set_app_handle(handle: tauri::AppHandle) {
     let _ = APP_HANDLE.set(handle);
 }
 
-fn try_get_app_handle() -> Option<&'static tauri::AppHandle> {
+pub(crate) fn try_get_app_handle() -> Option<&'static tauri::AppHandle> {
     APP_HANDLE.get()
 }
 
-fn create_tables(conn: &Connection) -> Result<(), String> {
+pub(crate) fn create_tables(conn: &Connection) -> Result<(), String> {
     let queries = vec![
         r#"
         CREATE TABLE IF NOT EXISTS Users (
@@ -157,7 +292,9 @@ fn create_tables(conn: &Connection) -> Result<(), String> {
             target_weight REAL,
             target_hbA1c REAL,
             target_calories REAL,
-            target_carbohydrates REAL
+            target_carbohydrates REAL,
+            expected_due_date TEXT,
+            delivery_date TEXT
         )
         "#,
         r#"
@@ -225,15 +362,451 @@ fn create_tables(conn: &Connection) -> Result<(), String> {
             role TEXT NOT NULL,
             content TEXT NOT NULL,
             model TEXT,
+            conversation_id TEXT,
+            is_partial INTEGER NOT NULL DEFAULT 0,
+            citations TEXT,
+            FOREIGN KEY (user_id) REFERENCES Users(id)
+        )
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS ReminderRules (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            title TEXT NOT NULL,
+            body TEXT,
+            trigger_type TEXT NOT NULL,
+            time_of_day TEXT,
+            offset_minutes INTEGER,
+            interval_minutes INTEGER,
+            meal_type INTEGER,
+            measurement_time INTEGER,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            FOREIGN KEY (user_id) REFERENCES Users(id)
+        )
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS ReminderDeliveries (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            rule_id TEXT,
+            source_type TEXT NOT NULL,
+            source_id TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            title TEXT NOT NULL,
+            body TEXT,
+            due_at TEXT NOT NULL,
+            delivered_at TEXT NOT NULL,
+            acknowledged_at TEXT,
+            status TEXT NOT NULL,
+            is_catch_up INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY (user_id) REFERENCES Users(id)
+        )
+        "#,
+        r#"
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_reminder_deliveries_source
+            ON ReminderDeliveries (source_type, source_id, due_at)
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS GlucoseCheckPreferences (
+            user_id TEXT PRIMARY KEY,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            check_1h INTEGER NOT NULL DEFAULT 1,
+            check_2h INTEGER NOT NULL DEFAULT 1,
+            window_minutes INTEGER NOT NULL DEFAULT 20,
+            include_snacks INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY (user_id) REFERENCES Users(id)
+        )
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS GlucoseChecks (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            food_entry_id TEXT NOT NULL,
+            meal_type INTEGER NOT NULL,
+            meal_time TEXT NOT NULL,
+            measurement_time INTEGER NOT NULL,
+            due_at TEXT NOT NULL,
+            window_start TEXT NOT NULL,
+            window_end TEXT NOT NULL,
+            status TEXT NOT NULL,
+            completed_by TEXT,
+            completed_at TEXT,
+            FOREIGN KEY (user_id) REFERENCES Users(id)
+        )
+        "#,
+        r#"
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_glucose_checks_meal
+            ON GlucoseChecks (user_id, meal_time, measurement_time)
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS MealPairingSettings (
+            user_id TEXT PRIMARY KEY,
+            pre_meal_window_minutes INTEGER NOT NULL DEFAULT 60,
+            post_1h_start_minutes INTEGER NOT NULL DEFAULT 40,
+            post_1h_end_minutes INTEGER NOT NULL DEFAULT 90,
+            post_2h_start_minutes INTEGER NOT NULL DEFAULT 90,
+            post_2h_end_minutes INTEGER NOT NULL DEFAULT 150,
             FOREIGN KEY (user_id) REFERENCES Users(id)
         )
         "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS MealGlucosePairs (
+            glucose_id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            meal_time TEXT NOT NULL,
+            meal_type INTEGER NOT NULL,
+            role TEXT NOT NULL,
+            minutes_from_meal INTEGER NOT NULL,
+            paired_at TEXT NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES Users(id),
+            FOREIGN KEY (glucose_id) REFERENCES BloodGlucose(id) ON DELETE CASCADE
+        )
+        "#,
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_meal_glucose_pairs_meal
+            ON MealGlucosePairs (user_id, meal_time)
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS LlmProviders (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            base_url TEXT NOT NULL,
+            api_key TEXT,
+            model TEXT NOT NULL,
+            timeout_secs INTEGER NOT NULL DEFAULT 60,
+            max_retries INTEGER NOT NULL DEFAULT 2,
+            max_tokens INTEGER NOT NULL DEFAULT 1024,
+            temperature REAL,
+            response_path TEXT,
+            is_default INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL
+        )
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS Conversations (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            mode TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            is_pinned INTEGER NOT NULL DEFAULT 0,
+            is_archived INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY (user_id) REFERENCES Users(id)
+        )
+        "#,
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_conversations_user
+            ON Conversations (user_id, updated_at)
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS HealthContextSettings (
+            user_id TEXT PRIMARY KEY,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            share_profile INTEGER NOT NULL DEFAULT 1,
+            share_glucose INTEGER NOT NULL DEFAULT 1,
+            share_medications INTEGER NOT NULL DEFAULT 1,
+            share_nutrition INTEGER NOT NULL DEFAULT 1,
+            local_share_all INTEGER NOT NULL DEFAULT 1,
+            lookback_days INTEGER NOT NULL DEFAULT 7,
+            token_budget INTEGER NOT NULL DEFAULT 800,
+            share_body INTEGER NOT NULL DEFAULT 1,
+            share_activity INTEGER NOT NULL DEFAULT 1,
+            FOREIGN KEY (user_id) REFERENCES Users(id)
+        )
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS KnowledgeDocuments (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            source_path TEXT NOT NULL UNIQUE,
+            content TEXT NOT NULL,
+            imported_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )
+        "#,
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS KnowledgeChunks USING fts5(
+            document_id UNINDEXED,
+            chunk_index UNINDEXED,
+            heading,
+            body,
+            tokenize = 'trigram'
+        )
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS Summaries (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            period_type TEXT NOT NULL,
+            period_start TEXT NOT NULL,
+            period_end TEXT NOT NULL,
+            generated_at TEXT NOT NULL,
+            data TEXT NOT NULL,
+            prose TEXT,
+            model TEXT,
+            UNIQUE (user_id, period_type, period_start),
+            FOREIGN KEY (user_id) REFERENCES Users(id)
+        )
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS BodyMeasurements (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            measured_at TEXT NOT NULL,
+            weight REAL NOT NULL,
+            body_fat_percent REAL,
+            muscle_mass REAL,
+            waist_circumference REAL,
+            condition TEXT,
+            device_name TEXT,
+            notes TEXT,
+            FOREIGN KEY (user_id) REFERENCES Users(id)
+        )
+        "#,
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_body_measurements_user
+            ON BodyMeasurements (user_id, measured_at)
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS ExerciseEntries (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            exercise_type TEXT NOT NULL,
+            start_time TEXT NOT NULL,
+            duration_minutes INTEGER NOT NULL,
+            intensity TEXT NOT NULL,
+            calories REAL,
+            pre_glucose_id TEXT,
+            post_glucose_id TEXT,
+            notes TEXT,
+            FOREIGN KEY (user_id) REFERENCES Users(id),
+            FOREIGN KEY (pre_glucose_id) REFERENCES BloodGlucose(id) ON DELETE SET NULL,
+            FOREIGN KEY (post_glucose_id) REFERENCES BloodGlucose(id) ON DELETE SET NULL
+        )
+        "#,
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_exercise_entries_user
+            ON ExerciseEntries (user_id, start_time)
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS ExerciseSettings (
+            user_id TEXT PRIMARY KEY,
+            weekly_goal_minutes INTEGER NOT NULL DEFAULT 150,
+            FOREIGN KEY (user_id) REFERENCES Users(id)
+        )
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS BloodPressure (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            measured_at TEXT NOT NULL,
+            systolic INTEGER NOT NULL,
+            diastolic INTEGER NOT NULL,
+            pulse INTEGER,
+            arm TEXT,
+            posture TEXT,
+            device_name TEXT,
+            notes TEXT,
+            FOREIGN KEY (user_id) REFERENCES Users(id)
+        )
+        "#,
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_blood_pressure_user
+            ON BloodPressure (user_id, measured_at)
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS LabResults (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            analyte_code TEXT NOT NULL,
+            collected_at TEXT NOT NULL,
+            value REAL NOT NULL,
+            unit TEXT NOT NULL,
+            original_value REAL,
+            original_unit TEXT,
+            flag TEXT,
+            lab_name TEXT,
+            notes TEXT,
+            FOREIGN KEY (user_id) REFERENCES Users(id)
+        )
+        "#,
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_lab_results_user
+            ON LabResults (user_id, analyte_code, collected_at)
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS KetoneReadings (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            measured_at TEXT NOT NULL,
+            sample_type TEXT NOT NULL,
+            value REAL,
+            urine_level INTEGER,
+            notes TEXT,
+            FOREIGN KEY (user_id) REFERENCES Users(id)
+        )
+        "#,
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_ketone_readings_user
+            ON KetoneReadings (user_id, measured_at)
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS DkaWarnings (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            ketone_id TEXT NOT NULL UNIQUE,
+            glucose_id TEXT,
+            glucose_value REAL,
+            ketone_level TEXT NOT NULL,
+            severity TEXT NOT NULL,
+            message TEXT NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES Users(id),
+            FOREIGN KEY (ketone_id) REFERENCES KetoneReadings(id) ON DELETE CASCADE
+        )
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS JournalEntries (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            recorded_at TEXT NOT NULL,
+            symptoms TEXT,
+            mood INTEGER,
+            stress INTEGER,
+            sleep_minutes INTEGER,
+            sleep_quality INTEGER,
+            is_ill INTEGER NOT NULL DEFAULT 0,
+            notes TEXT,
+            FOREIGN KEY (user_id) REFERENCES Users(id)
+        )
+        "#,
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_journal_entries_user
+            ON JournalEntries (user_id, recorded_at)
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS Goals (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            metric TEXT NOT NULL,
+            operator TEXT NOT NULL,
+            target REAL NOT NULL,
+            period TEXT NOT NULL,
+            meal_type INTEGER,
+            label TEXT,
+            start_date TEXT NOT NULL,
+            end_date TEXT,
+            is_active INTEGER NOT NULL DEFAULT 1,
+            FOREIGN KEY (user_id) REFERENCES Users(id)
+        )
+        "#,
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_goals_user
+            ON Goals (user_id, created_at)
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS GlucoseTargetProfiles (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            preset TEXT,
+            tir_low REAL NOT NULL,
+            tir_high REAL NOT NULL,
+            tir_goal_percent REAL NOT NULL,
+            ranges TEXT,
+            effective_from TEXT NOT NULL,
+            notes TEXT,
+            FOREIGN KEY (user_id) REFERENCES Users(id)
+        )
+        "#,
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_glucose_target_profiles_user
+            ON GlucoseTargetProfiles (user_id, effective_from)
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS SickDayEpisodes (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            ended_at TEXT,
+            glucose_interval_minutes INTEGER NOT NULL,
+            ketone_interval_minutes INTEGER NOT NULL,
+            uses_insulin INTEGER NOT NULL DEFAULT 0,
+            notes TEXT,
+            FOREIGN KEY (user_id) REFERENCES Users(id)
+        )
+        "#,
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_sick_day_episodes_user
+            ON SickDayEpisodes (user_id, started_at)
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS SchedulerState (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )
+        "#,
     ];
 
     for query in queries {
         conn.execute(query, params![]).map_err(|e| e.to_string())?;
     }
 
+    // 旧版本创建的表缺少后来新增的列
+    let columns = vec![
+        ("ChatMessages", "conversation_id", "TEXT"),
+        ("ChatMessages", "is_partial", "INTEGER NOT NULL DEFAULT 0"),
+        ("ChatMessages", "citations", "TEXT"),
+        ("Users", "expected_due_date", "TEXT"),
+        ("Users", "delivery_date", "TEXT"),
+        ("HealthContextSettings", "share_body", "INTEGER NOT NULL DEFAULT 1"),
+        ("HealthContextSettings", "share_activity", "INTEGER NOT NULL DEFAULT 1"),
+    ];
+
+    for (table, column, definition) in columns {
+        add_column_if_missing(conn, table, column, definition)?;
+    }
+
+    // 依赖迁移新增列的索引放在迁移之后创建
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_chat_messages_conversation ON ChatMessages (conversation_id, created_at)",
+        params![],
+    )
+    .map_err(|e| e.to_string())?;
+
+    crate::search::create_search_index(conn)?;
+
+    Ok(())
+}
+
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({})", table))
+        .map_err(|e| e.to_string())?;
+    let exists = stmt
+        .query_map(params![], |row| row.get::<_, String>(1))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .any(|name| name == column);
+
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), params![])
+            .map_err(|e| e.to_string())?;
+    }
+
     Ok(())
 }
 
@@ -260,13 +833,14 @@ pub async fn user_create(user: User) -> Result<ApiResponse<User>, String> {
     conn.execute(
         r#"INSERT INTO Users (id, username, email, phone, password_hash, created_at, 
             birthday, gender, height, diabetes_type, diagnosis_date, treatment_plan,
-            target_weight, target_hbA1c, target_calories, target_carbohydrates)
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)"#,
+            target_weight, target_hbA1c, target_calories, target_carbohydrates, expected_due_date,
+            delivery_date)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)"#,
         params![
             user.id, user.username, user.email, user.phone, user.password_hash,
             user.created_at, user.birthday, user.gender, user.height, user.diabetes_type,
             user.diagnosis_date, user.treatment_plan, user.target_weight, user.target_hb_a1c,
-            user.target_calories, user.target_carbohydrates
+            user.target_calories, user.target_carbohydrates, user.expected_due_date, user.delivery_date
         ],
     )
     .map_err(|e| e.to_string())?;
@@ -285,26 +859,7 @@ pub async fn user_get_by_email(email: String) -> Result<ApiResponse<Option<User>
     let user = conn.query_row(
         "SELECT * FROM Users WHERE email = ?1",
         params![email],
-        |row| {
-            Ok(User {
-                id: row.get(0)?,
-                username: row.get(1)?,
-                email: row.get(2)?,
-                phone: row.get(3)?,
-                password_hash: row.get(4)?,
-                created_at: row.get(5)?,
-                birthday: row.get(6)?,
-                gender: row.get(7)?,
-                height: row.get(8)?,
-                diabetes_type: row.get(9)?,
-                diagnosis_date: row.get(10)?,
-                treatment_plan: row.get(11)?,
-                target_weight: row.get(12)?,
-                target_hb_a1c: row.get(13)?,
-                target_calories: row.get(14)?,
-                target_carbohydrates: row.get(15)?,
-            })
-        },
+        User::from_row,
     )
     .ok();
 
@@ -349,26 +904,7 @@ pub async fn user_get_by_id(id: String) -> Result<ApiResponse<Option<User>>, Str
     let user = conn.query_row(
         "SELECT * FROM Users WHERE id = ?1",
         params![id],
-        |row: &rusqlite::Row<'_>| {
-            Ok(User {
-                id: row.get(0)?,
-                username: row.get(1)?,
-                email: row.get(2)?,
-                phone: row.get(3)?,
-                password_hash: row.get(4)?,
-                created_at: row.get(5)?,
-                birthday: row.get(6)?,
-                gender: row.get(7)?,
-                height: row.get(8)?,
-                diabetes_type: row.get(9)?,
-                diagnosis_date: row.get(10)?,
-                treatment_plan: row.get(11)?,
-                target_weight: row.get(12)?,
-                target_hb_a1c: row.get(13)?,
-                target_calories: row.get(14)?,
-                target_carbohydrates: row.get(15)?,
-            })
-        },
+        User::from_row,
     )
     .ok();
 
@@ -397,6 +933,14 @@ pub async fn food_entry_create(entry: FoodEntry) -> Result<ApiResponse<FoodEntry
     )
     .map_err(|e| e.to_string())?;
 
+    // 记录正餐后自动安排餐后血糖检测，失败不影响饮食记录本身
+    if let Err(e) = schedule_post_meal_checks(&conn, &entry) {
+        eprintln!("Failed to schedule post-meal glucose checks: {}", e);
+    }
+    if let Err(e) = repair_around(&conn, &entry.user_id, &entry.meal_time) {
+        eprintln!("Failed to pair glucose readings with meal: {}", e);
+    }
+
     Ok(ApiResponse {
         success: true,
         data: Some(entry),
@@ -427,26 +971,7 @@ pub async fn food_entry_get_by_user(
     let items: Vec<FoodEntry> = stmt
         .query_map(
             params![user_id, start_date, end_date, page_size, offset],
-            |row: &rusqlite::Row<'_>| {
-                Ok(FoodEntry {
-                    id: row.get(0)?,
-                    user_id: row.get(1)?,
-                    created_at: row.get(2)?,
-                    meal_type: row.get(3)?,
-                    meal_time: row.get(4)?,
-                    food_name: row.get(5)?,
-                    quantity: row.get(6)?,
-                    calories: row.get(7)?,
-                    carbohydrates: row.get(8)?,
-                    protein: row.get(9)?,
-                    fat: row.get(10)?,
-                    gi: row.get(11)?,
-                    gl: row.get(12)?,
-                    source: row.get(13)?,
-                    image_path: row.get(14)?,
-                    notes: row.get(15)?,
-                })
-            },
+            FoodEntry::from_row,
         )
         .map_err(|e: rusqlite::Error| e.to_string())?
         .filter_map(|r: Result<FoodEntry, rusqlite::Error>| r.ok())
@@ -477,9 +1002,21 @@ pub async fn food_entry_get_by_user(
 pub async fn food_entry_delete(id: String) -> Result<ApiResponse<String>, String> {
     let conn = open_conn()?;
     
+    let entry = conn
+        .query_row("SELECT * FROM FoodEntries WHERE id = ?1", params![id], FoodEntry::from_row)
+        .ok();
     conn.execute("DELETE FROM FoodEntries WHERE id = ?1", params![id])
         .map_err(|e: rusqlite::Error| e.to_string())?;
 
+    if let Some(entry) = entry {
+        if let Err(e) = cancel_post_meal_checks(&conn, &entry) {
+            eprintln!("Failed to cancel post-meal glucose checks: {}", e);
+        }
+        if let Err(e) = repair_around(&conn, &entry.user_id, &entry.meal_time) {
+            eprintln!("Failed to pair glucose readings with meal: {}", e);
+        }
+    }
+
     Ok(ApiResponse {
         success: true,
         data: Some("Deleted".to_string()),
@@ -506,6 +1043,17 @@ pub async fn blood_glucose_create(entry: BloodGlucose) -> Result<ApiResponse<Blo
     )
     .map_err(|e: rusqlite::Error| e.to_string())?;
 
+    if let Err(e) = complete_matching_check(&conn, &entry) {
+        eprintln!("Failed to complete post-meal glucose check: {}", e);
+    }
+    let measured_at = entry.measurement_time_exact.as_deref().unwrap_or(&entry.created_at);
+    if let Err(e) = repair_around(&conn, &entry.user_id, measured_at) {
+        eprintln!("Failed to pair glucose reading with meal: {}", e);
+    }
+    if let Err(e) = raise_dka_warnings(&conn, &entry.user_id, measured_at) {
+        eprintln!("Failed to check ketone warnings: {}", e);
+    }
+
     Ok(ApiResponse {
         success: true,
         data: Some(entry),
@@ -536,22 +1084,7 @@ pub async fn blood_glucose_get_by_user(
     let items: Vec<BloodGlucose> = stmt
         .query_map(
             params![user_id, start_date, end_date, page_size, offset],
-            |row: &rusqlite::Row<'_>| {
-                Ok(BloodGlucose {
-                    id: row.get(0)?,
-                    user_id: row.get(1)?,
-                    created_at: row.get(2)?,
-                    value: row.get(3)?,
-                    measurement_time: row.get(4)?,
-                    measurement_time_exact: row.get(5)?,
-                    before_meal_glucose: row.get(6)?,
-                    after_meal_glucose: row.get(7)?,
-                    related_meal: row.get(8)?,
-                    notes: row.get(9)?,
-                    device_name: row.get(10)?,
-                    device_serial: row.get(11)?,
-                })
-            },
+            BloodGlucose::from_row,
         )
         .map_err(|e: rusqlite::Error| e.to_string())?
         .filter_map(|r: Result<BloodGlucose, rusqlite::Error>| r.ok())
@@ -582,8 +1115,34 @@ pub async fn blood_glucose_get_by_user(
 pub async fn blood_glucose_delete(id: String) -> Result<ApiResponse<String>, String> {
     let conn = open_conn()?;
     
+    let entry = conn
+        .query_row("SELECT * FROM BloodGlucose WHERE id = ?1", params![id], BloodGlucose::from_row)
+        .ok();
+    // 连接未开启外键约束，配对记录以及运动记录、酮症提醒里的血糖关联需要手动清理
+    conn.execute("DELETE FROM MealGlucosePairs WHERE glucose_id = ?1", params![id])
+        .map_err(|e: rusqlite::Error| e.to_string())?;
+    conn.execute(
+        r#"UPDATE ExerciseEntries SET
+            pre_glucose_id = CASE WHEN pre_glucose_id = ?1 THEN NULL ELSE pre_glucose_id END,
+            post_glucose_id = CASE WHEN post_glucose_id = ?1 THEN NULL ELSE post_glucose_id END
+        WHERE pre_glucose_id = ?1 OR post_glucose_id = ?1"#,
+        params![id],
+    )
+    .map_err(|e: rusqlite::Error| e.to_string())?;
+    conn.execute("UPDATE DkaWarnings SET glucose_id = NULL WHERE glucose_id = ?1", params![id])
+        .map_err(|e: rusqlite::Error| e.to_string())?;
     conn.execute("DELETE FROM BloodGlucose WHERE id = ?1", params![id])
         .map_err(|e: rusqlite::Error| e.to_string())?;
+    if let Err(e) = revert_completed_checks(&conn, &id) {
+        eprintln!("Failed to revert glucose checks: {}", e);
+    }
+
+    if let Some(entry) = entry {
+        let measured_at = entry.measurement_time_exact.as_deref().unwrap_or(&entry.created_at);
+        if let Err(e) = repair_around(&conn, &entry.user_id, measured_at) {
+            eprintln!("Failed to pair glucose readings with meal: {}", e);
+        }
+    }
 
     Ok(ApiResponse {
         success: true,
@@ -640,24 +1199,7 @@ pub async fn medication_get_by_user(
     let items: Vec<Medication> = stmt
         .query_map(
             params![user_id, start_date, end_date, page_size, offset],
-            |row: &rusqlite::Row<'_>| {
-                Ok(Medication {
-                    id: row.get(0)?,
-                    user_id: row.get(1)?,
-                    created_at: row.get(2)?,
-                    drug_name: row.get(3)?,
-                    r#type: row.get(4)?,
-                    dose: row.get(5)?,
-                    unit: row.get(6)?,
-                    timing: row.get(7)?,
-                    insulin_type: row.get(8)?,
-                    insulin_duration: row.get(9)?,
-                    scheduled_time: row.get(10)?,
-                    actual_time: row.get(11)?,
-                    is_taken: row.get::<_, i32>(12)? == 1,
-                    notes: row.get(13)?,
-                })
-            },
+            Medication::from_row,
         )
         .map_err(|e: rusqlite::Error| e.to_string())?
         .filter_map(|r: Result<Medication, rusqlite::Error>| r.ok())
@@ -694,6 +1236,9 @@ pub async fn medication_mark_taken(id: String, actual_time: String) -> Result<Ap
     )
     .map_err(|e: rusqlite::Error| e.to_string())?;
 
+    // 服药后自动确认对应的用药提醒
+    acknowledge_by_source(&conn, SOURCE_MEDICATION, &id, &actual_time)?;
+
     Ok(ApiResponse {
         success: true,
         data: Some("Marked as taken".to_string()),
@@ -717,19 +1262,32 @@ pub async fn medication_delete(id: String) -> Result<ApiResponse<String>, String
 
 // ============ Chat Message Commands ============
 
-#[tauri::command]
-pub async fn chat_message_create(message: ChatMessage) -> Result<ApiResponse<String>, String> {
-    let conn = open_conn()?;
-    
+pub(crate) fn insert_chat_message(conn: &Connection, message: &ChatMessage) -> Result<(), String> {
+    let citations = if message.citations.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(&message.citations).map_err(|e| e.to_string())?)
+    };
     conn.execute(
-        r#"INSERT INTO ChatMessages (id, user_id, created_at, role, content, model)
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6)"#,
+        r#"INSERT INTO ChatMessages (id, user_id, created_at, role, content, model, conversation_id, is_partial,
+            citations)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"#,
         params![
-            message.id, message.user_id, message.created_at, message.role, message.content, message.model
+            message.id, message.user_id, message.created_at, message.role, message.content, message.model,
+            message.conversation_id, if message.is_partial { 1i32 } else { 0i32 }, citations
         ],
     )
     .map_err(|e: rusqlite::Error| e.to_string())?;
 
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn chat_message_create(message: ChatMessage) -> Result<ApiResponse<String>, String> {
+    let conn = open_conn()?;
+    
+    insert_chat_message(&conn, &message)?;
+
     Ok(ApiResponse {
         success: true,
         data: Some("Created".to_string()),
@@ -755,16 +1313,7 @@ pub async fn chat_message_get_history(
     let messages: Vec<ChatMessage> = stmt
         .query_map(
             params![user_id, take],
-            |row: &rusqlite::Row<'_>| {
-                Ok(ChatMessage {
-                    id: row.get(0)?,
-                    user_id: row.get(1)?,
-                    created_at: row.get(2)?,
-                    role: row.get(3)?,
-                    content: row.get(4)?,
-                    model: row.get(5)?,
-                })
-            },
+            ChatMessage::from_row,
         )
         .map_err(|e: rusqlite::Error| e.to_string())?
         .filter_map(|r: Result<ChatMessage, rusqlite::Error>| r.ok())
@@ -0,0 +1,59 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, TimeZone, Utc};
+
+// 前端统一使用 toISOString() 写入时间（UTC，毫秒精度），这里兼容不带时区的旧数据
+pub(crate) fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+
+    for fmt in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(value, fmt) {
+            return Some(naive.and_utc());
+        }
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|naive| naive.and_utc())
+}
+
+/// 与 `parse_timestamp` 相同，但不带时区的时间按本地时间理解（用于用户或模型输入的时间）
+pub(crate) fn parse_local_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+
+    let naive = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(value, fmt).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })?;
+    Local.from_local_datetime(&naive).earliest().map(|dt| dt.with_timezone(&Utc))
+}
+
+/// 本地日期零点对应的 UTC 时间；夏令时跳过零点时取当天最早的有效时间
+pub(crate) fn local_midnight(date: NaiveDate) -> DateTime<Utc> {
+    let naive = date.and_time(NaiveTime::MIN);
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| naive.and_utc())
+}
+
+pub(crate) fn parse_date_arg(name: &str, value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| format!("Invalid {}: {}", name, value))
+}
+
+pub(crate) fn parse_timestamp_arg(name: &str, value: &str) -> Result<DateTime<Utc>, String> {
+    parse_timestamp(value).ok_or_else(|| format!("Invalid {}: {}", name, value))
+}
+
+// 与 JavaScript Date.toISOString() 的格式保持一致，保证字符串比较与时间顺序相同
+pub(crate) fn format_timestamp(dt: &DateTime<Utc>) -> String {
+    dt.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
@@ -0,0 +1,81 @@
+// 与 VitaNote.Shared.Enums 保持一致，数据库中以整数存储
+
+// Gender
+pub const GENDER_MALE: i32 = 1;
+pub const GENDER_FEMALE: i32 = 2;
+
+// DiabetesType
+pub const DIABETES_TYPE1: i32 = 1;
+pub const DIABETES_TYPE2: i32 = 2;
+pub const DIABETES_GESTATIONAL: i32 = 3;
+
+// TreatmentPlan
+pub const TREATMENT_DIET_ONLY: i32 = 0;
+pub const TREATMENT_ORAL_MEDICATION: i32 = 1;
+pub const TREATMENT_INSULIN: i32 = 2;
+pub const TREATMENT_COMBINED: i32 = 3;
+
+// MealType
+pub const MEAL_BREAKFAST: i32 = 0;
+pub const MEAL_LUNCH: i32 = 1;
+pub const MEAL_DINNER: i32 = 2;
+pub const MEAL_SNACK: i32 = 3;
+
+// EntrySource
+pub const ENTRY_SOURCE_MANUAL: i32 = 0;
+
+// MeasurementTimeType
+pub const MEASUREMENT_FASTING: i32 = 0;
+pub const MEASUREMENT_BEFORE_MEAL: i32 = 1;
+pub const MEASUREMENT_AFTER_MEAL_1H: i32 = 2;
+pub const MEASUREMENT_AFTER_MEAL_2H: i32 = 3;
+pub const MEASUREMENT_BEFORE_BED: i32 = 4;
+pub const MEASUREMENT_NIGHT: i32 = 5;
+pub const MEASUREMENT_RANDOM: i32 = 6;
+
+// InsulinType
+pub const INSULIN_RAPID_ACTING: i32 = 0;
+pub const INSULIN_SHORT_ACTING: i32 = 1;
+pub const INSULIN_INTERMEDIATE: i32 = 2;
+pub const INSULIN_LONG_ACTING: i32 = 3;
+pub const INSULIN_PREMIXED: i32 = 4;
+
+pub fn meal_type_label(value: i32) -> &'static str {
+    match value {
+        MEAL_BREAKFAST => "早餐",
+        MEAL_LUNCH => "午餐",
+        MEAL_DINNER => "晚餐",
+        _ => "加餐",
+    }
+}
+
+pub fn measurement_time_label(value: i32) -> &'static str {
+    match value {
+        MEASUREMENT_FASTING => "空腹",
+        MEASUREMENT_BEFORE_MEAL => "餐前",
+        MEASUREMENT_AFTER_MEAL_1H => "餐后1h",
+        MEASUREMENT_AFTER_MEAL_2H => "餐后2h",
+        MEASUREMENT_BEFORE_BED => "睡前",
+        MEASUREMENT_NIGHT => "夜间",
+        _ => "随机",
+    }
+}
+
+pub fn diabetes_type_label(value: i32) -> &'static str {
+    match value {
+        DIABETES_TYPE1 => "1型糖尿病",
+        DIABETES_TYPE2 => "2型糖尿病",
+        DIABETES_GESTATIONAL => "妊娠糖尿病",
+        _ => "糖尿病（类型未填写）",
+    }
+}
+
+pub fn treatment_plan_label(value: i32) -> &'static str {
+    match value {
+        TREATMENT_DIET_ONLY => "饮食控制",
+        TREATMENT_ORAL_MEDICATION => "口服药治疗",
+        TREATMENT_INSULIN => "胰岛素治疗",
+        TREATMENT_COMBINED => "口服药联合胰岛素",
+        _ => "治疗方案未填写",
+    }
+}
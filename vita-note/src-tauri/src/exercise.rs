@@ -0,0 +1,437 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::body::latest_weight;
+use crate::database::{open_conn, ApiResponse};
+use crate::datetime::{local_midnight, parse_date_arg, parse_timestamp, parse_timestamp_arg};
+
+pub const INTENSITY_LIGHT: &str = "light";
+pub const INTENSITY_MODERATE: &str = "moderate";
+pub const INTENSITY_VIGOROUS: &str = "vigorous";
+
+pub const EXERCISE_WALKING: &str = "walking";
+pub const EXERCISE_RUNNING: &str = "running";
+pub const EXERCISE_CYCLING: &str = "cycling";
+pub const EXERCISE_SWIMMING: &str = "swimming";
+pub const EXERCISE_YOGA: &str = "yoga";
+pub const EXERCISE_TAI_CHI: &str = "tai_chi";
+pub const EXERCISE_DANCING: &str = "dancing";
+pub const EXERCISE_STRENGTH: &str = "strength";
+pub const EXERCISE_BALL_GAMES: &str = "ball_games";
+pub const EXERCISE_OTHER: &str = "other";
+
+/// 各运动在低、中、高强度下的 MET 值，取自 2011 版 Compendium of Physical Activities
+const MET_TABLE: [(&str, [f64; 3]); 10] = [
+    (EXERCISE_WALKING, [2.8, 3.5, 5.0]),
+    (EXERCISE_RUNNING, [7.0, 9.8, 11.5]),
+    (EXERCISE_CYCLING, [4.0, 6.8, 10.0]),
+    (EXERCISE_SWIMMING, [5.8, 7.0, 9.8]),
+    (EXERCISE_YOGA, [2.5, 3.0, 4.0]),
+    (EXERCISE_TAI_CHI, [3.0, 4.0, 5.0]),
+    (EXERCISE_DANCING, [4.5, 5.5, 7.3]),
+    (EXERCISE_STRENGTH, [3.5, 5.0, 6.0]),
+    (EXERCISE_BALL_GAMES, [4.5, 6.5, 8.0]),
+    (EXERCISE_OTHER, [3.0, 4.0, 6.0]),
+];
+
+const DEFAULT_WEEKLY_GOAL_MINUTES: i32 = 150;
+const DEFAULT_SUMMARY_WEEKS: i64 = 4;
+const MAX_SUMMARY_WEEKS: i64 = 52;
+const MAX_DURATION_MINUTES: i32 = 24 * 60;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExerciseEntry {
+    pub id: String,
+    pub user_id: String,
+    pub created_at: String,
+    pub exercise_type: String,
+    pub start_time: String,
+    pub duration_minutes: i32,
+    pub intensity: String,
+    /// 手表等设备给出的消耗优先，未提供时按 MET 估算
+    pub calories: Option<f64>,
+    pub pre_glucose_id: Option<String>,
+    pub post_glucose_id: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl ExerciseEntry {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ExerciseEntry> {
+        Ok(ExerciseEntry {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            created_at: row.get(2)?,
+            exercise_type: row.get(3)?,
+            start_time: row.get(4)?,
+            duration_minutes: row.get(5)?,
+            intensity: row.get(6)?,
+            calories: row.get(7)?,
+            pre_glucose_id: row.get(8)?,
+            post_glucose_id: row.get(9)?,
+            notes: row.get(10)?,
+        })
+    }
+
+    fn validate(&self, conn: &Connection) -> Result<(), String> {
+        parse_timestamp_arg("start_time", &self.start_time)?;
+        if !MET_TABLE.iter().any(|(t, _)| *t == self.exercise_type) {
+            return Err(format!("Unknown exercise_type: {}", self.exercise_type));
+        }
+        intensity_index(&self.intensity)?;
+        if self.duration_minutes <= 0 || self.duration_minutes > MAX_DURATION_MINUTES {
+            return Err(format!("Invalid duration_minutes: {}", self.duration_minutes));
+        }
+        if matches!(self.calories, Some(c) if c < 0.0) {
+            return Err("calories must not be negative".to_string());
+        }
+        for (name, glucose_id) in [("pre_glucose_id", &self.pre_glucose_id), ("post_glucose_id", &self.post_glucose_id)] {
+            let Some(glucose_id) = glucose_id else { continue };
+            let owner: Option<String> = conn
+                .query_row("SELECT user_id FROM BloodGlucose WHERE id = ?1", params![glucose_id], |row| row.get(0))
+                .optional()
+                .map_err(|e| e.to_string())?;
+            if owner.as_deref() != Some(self.user_id.as_str()) {
+                return Err(format!("Invalid {}: {}", name, glucose_id));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExerciseSettings {
+    pub user_id: String,
+    pub weekly_goal_minutes: i32,
+}
+
+impl ExerciseSettings {
+    pub fn defaults(user_id: &str) -> ExerciseSettings {
+        ExerciseSettings {
+            user_id: user_id.to_string(),
+            weekly_goal_minutes: DEFAULT_WEEKLY_GOAL_MINUTES,
+        }
+    }
+
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ExerciseSettings> {
+        Ok(ExerciseSettings {
+            user_id: row.get(0)?,
+            weekly_goal_minutes: row.get(1)?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WeeklyActivity {
+    /// 周一的本地日期
+    pub week_start: String,
+    pub sessions: usize,
+    pub total_minutes: i32,
+    pub light_minutes: i32,
+    pub moderate_minutes: i32,
+    pub vigorous_minutes: i32,
+    /// 高强度按 2 倍折算成中等强度分钟数，与周目标比较
+    pub goal_minutes: i32,
+    pub goal_met: bool,
+    pub calories: f64,
+    /// 同时关联了运动前后血糖的记录，血糖平均变化
+    pub average_glucose_change: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ActivitySummary {
+    pub user_id: String,
+    pub weekly_goal_minutes: i32,
+    pub weeks: Vec<WeeklyActivity>,
+}
+
+fn intensity_index(intensity: &str) -> Result<usize, String> {
+    match intensity {
+        INTENSITY_LIGHT => Ok(0),
+        INTENSITY_MODERATE => Ok(1),
+        INTENSITY_VIGOROUS => Ok(2),
+        other => Err(format!("Unknown intensity: {}", other)),
+    }
+}
+
+pub fn met_value(exercise_type: &str, intensity: &str) -> Option<f64> {
+    let index = intensity_index(intensity).ok()?;
+    MET_TABLE.iter().find(|(t, _)| *t == exercise_type).map(|(_, mets)| mets[index])
+}
+
+/// kcal = MET × 体重 (kg) × 时长 (h)
+pub fn estimate_calories(exercise_type: &str, intensity: &str, duration_minutes: i32, weight: f64) -> Option<f64> {
+    met_value(exercise_type, intensity).map(|met| met * weight * duration_minutes as f64 / 60.0)
+}
+
+pub(crate) fn load_settings(conn: &Connection, user_id: &str) -> Result<ExerciseSettings, String> {
+    let settings = conn
+        .query_row(
+            "SELECT * FROM ExerciseSettings WHERE user_id = ?1",
+            params![user_id],
+            ExerciseSettings::from_row,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    Ok(settings.unwrap_or_else(|| ExerciseSettings::defaults(user_id)))
+}
+
+// 没有记录过体重时无法估算，保留为空
+fn with_estimated_calories(conn: &Connection, mut entry: ExerciseEntry) -> Result<ExerciseEntry, String> {
+    if entry.calories.is_none() {
+        if let Some(weight) = latest_weight(conn, &entry.user_id)? {
+            entry.calories = estimate_calories(&entry.exercise_type, &entry.intensity, entry.duration_minutes, weight)
+                .map(|kcal| kcal.round());
+        }
+    }
+    Ok(entry)
+}
+
+fn glucose_value(conn: &Connection, id: &Option<String>) -> Result<Option<f64>, String> {
+    let Some(id) = id else {
+        return Ok(None);
+    };
+    conn.query_row("SELECT value FROM BloodGlucose WHERE id = ?1", params![id], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// 统计包含 date 的一周及之前共 weeks 周的运动量，按时间正序返回
+pub(crate) fn activity_summary(
+    conn: &Connection,
+    user_id: &str,
+    date: NaiveDate,
+    weeks: i64,
+) -> Result<ActivitySummary, String> {
+    let settings = load_settings(conn, user_id)?;
+    let first_week = week_start(date) - Duration::weeks(weeks - 1);
+    let from = local_midnight(first_week);
+    let to = local_midnight(week_start(date) + Duration::weeks(1));
+
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM ExerciseEntries
+            WHERE user_id = ?1 AND start_time >= ?2 AND start_time <= ?3
+            ORDER BY start_time ASC"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let entries: Vec<(DateTime<Utc>, ExerciseEntry)> = stmt
+        .query_map(
+            params![
+                user_id,
+                from.format("%Y-%m-%d").to_string(),
+                (to + Duration::days(1)).format("%Y-%m-%d").to_string()
+            ],
+            ExerciseEntry::from_row,
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|entry| Some((parse_timestamp(&entry.start_time)?, entry)))
+        .filter(|(t, _)| *t >= from && *t < to)
+        .collect();
+
+    let mut summary_weeks = Vec::new();
+    for i in 0..weeks {
+        let start = first_week + Duration::weeks(i);
+        let in_week: Vec<&ExerciseEntry> = entries
+            .iter()
+            .filter(|(t, _)| week_start(t.with_timezone(&Local).date_naive()) == start)
+            .map(|(_, entry)| entry)
+            .collect();
+
+        let minutes_at = |intensity: &str| {
+            in_week.iter().filter(|e| e.intensity == intensity).map(|e| e.duration_minutes).sum::<i32>()
+        };
+        let light_minutes = minutes_at(INTENSITY_LIGHT);
+        let moderate_minutes = minutes_at(INTENSITY_MODERATE);
+        let vigorous_minutes = minutes_at(INTENSITY_VIGOROUS);
+        let goal_minutes = moderate_minutes + vigorous_minutes * 2;
+
+        let mut changes = Vec::new();
+        for entry in &in_week {
+            if let (Some(pre), Some(post)) =
+                (glucose_value(conn, &entry.pre_glucose_id)?, glucose_value(conn, &entry.post_glucose_id)?)
+            {
+                changes.push(post - pre);
+            }
+        }
+
+        summary_weeks.push(WeeklyActivity {
+            week_start: start.format("%Y-%m-%d").to_string(),
+            sessions: in_week.len(),
+            total_minutes: light_minutes + moderate_minutes + vigorous_minutes,
+            light_minutes,
+            moderate_minutes,
+            vigorous_minutes,
+            goal_minutes,
+            goal_met: goal_minutes >= settings.weekly_goal_minutes,
+            calories: in_week.iter().filter_map(|e| e.calories).sum(),
+            average_glucose_change: if changes.is_empty() {
+                None
+            } else {
+                Some(changes.iter().sum::<f64>() / changes.len() as f64)
+            },
+        });
+    }
+
+    Ok(ActivitySummary {
+        user_id: user_id.to_string(),
+        weekly_goal_minutes: settings.weekly_goal_minutes,
+        weeks: summary_weeks,
+    })
+}
+
+// ============ Exercise Commands ============
+
+#[tauri::command]
+pub async fn exercise_entry_create(entry: ExerciseEntry) -> Result<ApiResponse<ExerciseEntry>, String> {
+    let conn = open_conn()?;
+    entry.validate(&conn)?;
+    let entry = with_estimated_calories(&conn, entry)?;
+
+    conn.execute(
+        r#"INSERT INTO ExerciseEntries (id, user_id, created_at, exercise_type, start_time, duration_minutes,
+            intensity, calories, pre_glucose_id, post_glucose_id, notes)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"#,
+        params![
+            entry.id, entry.user_id, entry.created_at, entry.exercise_type, entry.start_time,
+            entry.duration_minutes, entry.intensity, entry.calories, entry.pre_glucose_id,
+            entry.post_glucose_id, entry.notes
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(entry),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn exercise_entries_get(
+    user_id: String,
+    start_date: String,
+    end_date: String,
+) -> Result<ApiResponse<Vec<ExerciseEntry>>, String> {
+    let conn = open_conn()?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM ExerciseEntries
+            WHERE user_id = ?1 AND start_time >= ?2 AND start_time < ?3
+            ORDER BY start_time DESC"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let items: Vec<ExerciseEntry> = stmt
+        .query_map(params![user_id, start_date, end_date], ExerciseEntry::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(items),
+        message: None,
+    })
+}
+
+/// 修改类型、强度或时长后，清空 calories 即可按新数据重新估算
+#[tauri::command]
+pub async fn exercise_entry_update(entry: ExerciseEntry) -> Result<ApiResponse<ExerciseEntry>, String> {
+    let conn = open_conn()?;
+    entry.validate(&conn)?;
+    let entry = with_estimated_calories(&conn, entry)?;
+
+    conn.execute(
+        r#"UPDATE ExerciseEntries SET
+            exercise_type = ?2, start_time = ?3, duration_minutes = ?4, intensity = ?5, calories = ?6,
+            pre_glucose_id = ?7, post_glucose_id = ?8, notes = ?9
+        WHERE id = ?1"#,
+        params![
+            entry.id, entry.exercise_type, entry.start_time, entry.duration_minutes, entry.intensity,
+            entry.calories, entry.pre_glucose_id, entry.post_glucose_id, entry.notes
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(entry),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn exercise_entry_delete(id: String) -> Result<ApiResponse<String>, String> {
+    let conn = open_conn()?;
+
+    conn.execute("DELETE FROM ExerciseEntries WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some("Deleted".to_string()),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn exercise_settings_get(user_id: String) -> Result<ApiResponse<ExerciseSettings>, String> {
+    let conn = open_conn()?;
+    let settings = load_settings(&conn, &user_id)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(settings),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn exercise_settings_update(settings: ExerciseSettings) -> Result<ApiResponse<ExerciseSettings>, String> {
+    if settings.weekly_goal_minutes <= 0 {
+        return Err("weekly_goal_minutes must be positive".to_string());
+    }
+    let conn = open_conn()?;
+
+    conn.execute(
+        "INSERT OR REPLACE INTO ExerciseSettings (user_id, weekly_goal_minutes) VALUES (?1, ?2)",
+        params![settings.user_id, settings.weekly_goal_minutes],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(settings),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn exercise_activity_summary(
+    user_id: String,
+    date: Option<String>,
+    weeks: Option<i64>,
+) -> Result<ApiResponse<ActivitySummary>, String> {
+    let date = match date {
+        Some(date) => parse_date_arg("date", &date)?,
+        None => Local::now().date_naive(),
+    };
+    let weeks = weeks.unwrap_or(DEFAULT_SUMMARY_WEEKS);
+    if !(1..=MAX_SUMMARY_WEEKS).contains(&weeks) {
+        return Err(format!("weeks must be between 1 and {}", MAX_SUMMARY_WEEKS));
+    }
+    let conn = open_conn()?;
+    let summary = activity_summary(&conn, &user_id, date, weeks)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(summary),
+        message: None,
+    })
+}
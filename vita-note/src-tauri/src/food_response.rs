@@ -0,0 +1,143 @@
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::database::{open_conn, ApiResponse};
+use crate::datetime::parse_timestamp_arg;
+use crate::meal_pairing::meal_responses;
+
+// 未指定时间范围时统计最近 90 天
+const DEFAULT_RANGE_DAYS: i64 = 90;
+
+// 双侧 95% 置信区间的 t 分布临界值，df = 1..30，更大样本取 1.96
+const T_CRITICAL_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160, 2.145, 2.131,
+    2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FoodResponseStat {
+    pub food_key: String,
+    pub food_name: String,
+    pub sample_count: i64,
+    pub mean_rise: f64,
+    pub std_dev: Option<f64>,
+    pub ci_low: Option<f64>,
+    pub ci_high: Option<f64>,
+    pub mean_carbohydrates: f64,
+}
+
+/// 食物名称归一化：去掉份量备注、统一大小写与空白，使“米饭”“米饭（一碗）”归为同一种食物
+pub(crate) fn normalize_food_name(name: &str) -> String {
+    let mut base = name.trim();
+    for (open, close) in [('(', ')'), ('（', '）'), ('[', ']')] {
+        if base.ends_with(close) {
+            if let Some(idx) = base.rfind(open) {
+                base = base[..idx].trim_end();
+            }
+        }
+    }
+    base.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn t_critical(df: usize) -> f64 {
+    if df == 0 {
+        return f64::NAN;
+    }
+    T_CRITICAL_95.get(df - 1).copied().unwrap_or(1.96)
+}
+
+pub(crate) fn summarize(food_key: String, food_name: String, rises: &[f64], carbs: &[f64]) -> FoodResponseStat {
+    let n = rises.len();
+    let mean = rises.iter().sum::<f64>() / n as f64;
+    let mean_carbohydrates = carbs.iter().sum::<f64>() / carbs.len().max(1) as f64;
+
+    let (std_dev, ci_low, ci_high) = if n >= 2 {
+        let variance = rises.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+        let sd = variance.sqrt();
+        let half_width = t_critical(n - 1) * sd / (n as f64).sqrt();
+        (Some(sd), Some(mean - half_width), Some(mean + half_width))
+    } else {
+        (None, None, None)
+    };
+
+    FoodResponseStat {
+        food_key,
+        food_name,
+        sample_count: n as i64,
+        mean_rise: mean,
+        std_dev,
+        ci_low,
+        ci_high,
+        mean_carbohydrates,
+    }
+}
+
+/// 按食物汇总餐后血糖升幅；一餐含多种食物时，该餐升幅计入每种食物的样本，碳水只计该食物自身
+pub(crate) fn rank_foods(
+    conn: &Connection,
+    user_id: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    min_samples: i64,
+) -> Result<Vec<FoodResponseStat>, String> {
+    // key -> (展示名称, 升幅样本, 碳水样本)
+    let mut groups: BTreeMap<String, (String, Vec<f64>, Vec<f64>)> = BTreeMap::new();
+    for response in meal_responses(conn, user_id, from, to)? {
+        let Some(rise) = response.rise else { continue };
+        // 同一餐里重复出现的食物合并为一个样本
+        let mut foods: Vec<(String, &str, f64)> = Vec::new();
+        for (name, carbs) in response.meal.food_names.iter().zip(&response.meal.food_carbohydrates) {
+            let key = normalize_food_name(name);
+            if key.is_empty() {
+                continue;
+            }
+            match foods.iter_mut().find(|(k, ..)| *k == key) {
+                Some(food) => food.2 += carbs,
+                None => foods.push((key, name.trim(), *carbs)),
+            }
+        }
+        for (key, name, carbs) in foods {
+            let group = groups.entry(key).or_insert_with(|| (name.to_string(), Vec::new(), Vec::new()));
+            group.1.push(rise);
+            group.2.push(carbs);
+        }
+    }
+
+    let mut stats: Vec<FoodResponseStat> = groups
+        .into_iter()
+        .filter(|(_, (_, rises, _))| rises.len() as i64 >= min_samples.max(1))
+        .map(|(key, (name, rises, carbs))| summarize(key, name, &rises, &carbs))
+        .collect();
+    stats.sort_by(|a, b| b.mean_rise.total_cmp(&a.mean_rise));
+    Ok(stats)
+}
+
+// ============ Food Response Commands ============
+
+/// 时间范围可选：未指定 end_date 时截止到当前，未指定 start_date 时取截止前 DEFAULT_RANGE_DAYS 天
+#[tauri::command]
+pub async fn food_response_ranking(
+    user_id: String,
+    min_samples: i64,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<ApiResponse<Vec<FoodResponseStat>>, String> {
+    let conn = open_conn()?;
+    let end = match end_date {
+        Some(end_date) => parse_timestamp_arg("end_date", &end_date)?,
+        None => Utc::now(),
+    };
+    let start = match start_date {
+        Some(start_date) => parse_timestamp_arg("start_date", &start_date)?,
+        None => end - Duration::days(DEFAULT_RANGE_DAYS),
+    };
+    let ranking = rank_foods(&conn, &user_id, start, end, min_samples)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(ranking),
+        message: None,
+    })
+}
@@ -0,0 +1,555 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::database::{open_conn, ApiResponse, User};
+use crate::datetime::{format_timestamp, local_midnight, parse_date_arg, parse_timestamp};
+use crate::enums::{measurement_time_label, MEASUREMENT_AFTER_MEAL_1H, MEASUREMENT_AFTER_MEAL_2H, MEASUREMENT_FASTING};
+use crate::ketones::{load_ketones, KetoneReading, KETONE_NORMAL};
+use crate::logbook::csv_field;
+use crate::meal_pairing::load_readings;
+use crate::sick_days::load_sick_periods;
+use crate::targets::load_targets;
+
+// 预产期按末次月经后 280 天推算
+const PREGNANCY_DAYS: i64 = 280;
+// 过了预产期仍未登记分娩时，最多按 42 周计算
+const POST_TERM_DAYS: i64 = 14;
+const MAX_WEEK: i64 = (PREGNANCY_DAYS + POST_TERM_DAYS) / 7;
+// 妊娠期建议至少每周查一次晨起尿酮
+const KETONE_CHECK_DAYS: i64 = 7;
+
+/// 一次妊娠的日期范围，均为本地日期；end 为妊娠结束后的第一天
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Pregnancy {
+    pub start: NaiveDate,
+    pub due: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl Pregnancy {
+    pub(crate) fn from_user(user: &User) -> Option<Pregnancy> {
+        let due = parse_day(user.expected_due_date.as_deref()?)?;
+        let end = match user.delivery_date.as_deref().and_then(parse_day) {
+            Some(delivered) => delivered + Duration::days(1),
+            None => due + Duration::days(POST_TERM_DAYS + 1),
+        };
+        Some(Pregnancy {
+            start: due - Duration::days(PREGNANCY_DAYS),
+            due,
+            end,
+        })
+    }
+
+    pub(crate) fn contains(&self, date: NaiveDate) -> bool {
+        date >= self.start && date < self.end
+    }
+
+    /// 孕周与天数，例如孕 24+3 周返回 (24, 3)
+    pub(crate) fn week_of(&self, date: NaiveDate) -> (i64, i64) {
+        let days = (date - self.start).num_days();
+        (days.div_euclid(7), days.rem_euclid(7))
+    }
+
+    fn week_start(&self, week: i64) -> NaiveDate {
+        self.start + Duration::weeks(week)
+    }
+
+    /// 妊娠期的起止时刻 [start, end)
+    pub(crate) fn bounds(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        (local_midnight(self.start), local_midnight(self.end))
+    }
+}
+
+/// 接受 YYYY-MM-DD 或 ISO 时间戳，时间戳按本地日期取日
+fn parse_day(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .or_else(|| parse_timestamp(value).map(|t| t.with_timezone(&Local).date_naive()))
+}
+
+pub fn trimester(week: i64) -> i32 {
+    match week {
+        i64::MIN..=13 => 1,
+        14..=27 => 2,
+        _ => 3,
+    }
+}
+
+pub(crate) fn week_label(week: i64, day: i64) -> String {
+    format!("孕 {}+{} 周", week, day)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GestationalStatus {
+    pub user_id: String,
+    pub date: String,
+    pub pregnancy_start: String,
+    pub expected_due_date: String,
+    pub is_pregnant: bool,
+    pub week: i64,
+    pub day: i64,
+    pub label: String,
+    pub trimester: i32,
+    pub days_to_due: i64,
+    pub last_ketone_at: Option<String>,
+    /// 近 7 天没有酮体记录
+    pub ketone_check_due: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GestationalReading {
+    pub glucose_id: String,
+    pub measured_at: String,
+    pub measurement_time: i32,
+    pub label: String,
+    pub value: f64,
+    pub target_low: f64,
+    pub target_high: f64,
+    pub in_target: bool,
+    /// 病假日期间的读数照常列出，但不计入均值与达标统计
+    #[serde(default)]
+    pub is_sick_day: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GestationalWeek {
+    pub week: i64,
+    pub trimester: i32,
+    /// 本孕周的本地日期范围，end_date 包含在内
+    pub start_date: String,
+    pub end_date: String,
+    pub readings: Vec<GestationalReading>,
+    pub fasting_mean: Option<f64>,
+    pub post_1h_mean: Option<f64>,
+    pub post_2h_mean: Option<f64>,
+    pub in_target_percent: Option<f64>,
+    pub above_target_count: usize,
+    pub below_target_count: usize,
+    pub ketones: Vec<KetoneReading>,
+    pub elevated_ketone_count: usize,
+    #[serde(default)]
+    pub sick_day_count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GestationalReport {
+    pub user_id: String,
+    pub pregnancy_start: String,
+    pub expected_due_date: String,
+    pub current_week: i64,
+    /// 只列出有血糖或酮体记录的孕周，按孕周正序
+    pub weeks: Vec<GestationalWeek>,
+}
+
+fn load_pregnancy(conn: &Connection, user_id: &str) -> Result<Pregnancy, String> {
+    let user = conn
+        .query_row("SELECT * FROM Users WHERE id = ?1", params![user_id], User::from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("User not found: {}", user_id))?;
+    Pregnancy::from_user(&user).ok_or_else(|| "expected_due_date is not set".to_string())
+}
+
+fn mean_of(readings: &[GestationalReading], measurement_time: i32) -> Option<f64> {
+    let values: Vec<f64> = readings
+        .iter()
+        .filter(|r| r.measurement_time == measurement_time && !r.is_sick_day)
+        .map(|r| r.value)
+        .collect();
+    if values.is_empty() {
+        None
+    } else {
+        Some((values.iter().sum::<f64>() / values.len() as f64 * 10.0).round() / 10.0)
+    }
+}
+
+/// 按孕周汇总 [from_week, to_week] 的血糖与酮体，血糖按当时适用的目标判断是否达标；
+/// 病假日期间的读数单独标记，不计入统计
+pub(crate) fn build_weekly_report(
+    conn: &Connection,
+    user_id: &str,
+    from_week: i64,
+    to_week: i64,
+    today: NaiveDate,
+) -> Result<GestationalReport, String> {
+    let pregnancy = load_pregnancy(conn, user_id)?;
+    let targets = load_targets(conn, user_id)?;
+    let sick = load_sick_periods(conn, user_id)?;
+    let from = local_midnight(pregnancy.week_start(from_week).max(pregnancy.start));
+    let to = local_midnight(pregnancy.week_start(to_week + 1).min(pregnancy.end));
+    let readings: Vec<_> = load_readings(conn, user_id, from, to)?
+        .into_iter()
+        .filter(|(t, _)| *t < to)
+        .collect();
+    let ketones: Vec<_> = load_ketones(conn, user_id, from, to)?
+        .into_iter()
+        .filter(|(t, _)| *t < to)
+        .collect();
+    let week_of = |t: &DateTime<Utc>| pregnancy.week_of(t.with_timezone(&Local).date_naive()).0;
+
+    let mut weeks = Vec::new();
+    for week in from_week..=to_week {
+        let in_week: Vec<GestationalReading> = readings
+            .iter()
+            .filter(|(t, _)| week_of(t) == week)
+            .map(|(t, r)| {
+                let (low, high) = targets.at(*t).range_for(r.measurement_time);
+                GestationalReading {
+                    glucose_id: r.id.clone(),
+                    measured_at: t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string(),
+                    measurement_time: r.measurement_time,
+                    label: measurement_time_label(r.measurement_time).to_string(),
+                    value: r.value,
+                    target_low: low,
+                    target_high: high,
+                    in_target: r.value >= low && r.value <= high,
+                    is_sick_day: sick.contains(*t),
+                }
+            })
+            .collect();
+        let week_ketones: Vec<KetoneReading> = ketones
+            .iter()
+            .filter(|(t, _)| week_of(t) == week)
+            .map(|(_, k)| k.clone())
+            .collect();
+        if in_week.is_empty() && week_ketones.is_empty() {
+            continue;
+        }
+
+        let counted: Vec<&GestationalReading> = in_week.iter().filter(|r| !r.is_sick_day).collect();
+        let start = pregnancy.week_start(week);
+        weeks.push(GestationalWeek {
+            week,
+            trimester: trimester(week),
+            start_date: start.format("%Y-%m-%d").to_string(),
+            end_date: (start + Duration::days(6)).format("%Y-%m-%d").to_string(),
+            fasting_mean: mean_of(&in_week, MEASUREMENT_FASTING),
+            post_1h_mean: mean_of(&in_week, MEASUREMENT_AFTER_MEAL_1H),
+            post_2h_mean: mean_of(&in_week, MEASUREMENT_AFTER_MEAL_2H),
+            in_target_percent: if counted.is_empty() {
+                None
+            } else {
+                Some(counted.iter().filter(|r| r.in_target).count() as f64 / counted.len() as f64 * 100.0)
+            },
+            above_target_count: counted.iter().filter(|r| r.value > r.target_high).count(),
+            below_target_count: counted.iter().filter(|r| r.value < r.target_low).count(),
+            elevated_ketone_count: week_ketones.iter().filter(|k| k.level() != KETONE_NORMAL).count(),
+            sick_day_count: in_week.len() - counted.len(),
+            readings: in_week,
+            ketones: week_ketones,
+        });
+    }
+
+    Ok(GestationalReport {
+        user_id: user_id.to_string(),
+        pregnancy_start: pregnancy.start.format("%Y-%m-%d").to_string(),
+        expected_due_date: pregnancy.due.format("%Y-%m-%d").to_string(),
+        current_week: pregnancy.week_of(today).0,
+        weeks,
+    })
+}
+
+/// 给产科团队的周报：先列每周汇总，再列逐条读数
+pub(crate) fn weekly_report_csv(report: &GestationalReport) -> String {
+    let mut csv = String::from("\u{FEFF}");
+    let mut push_row = |fields: Vec<String>| {
+        csv.push_str(&fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+        csv.push_str("\r\n");
+    };
+    let optional = |value: Option<f64>| value.map(|v| format!("{:.1}", v)).unwrap_or_default();
+
+    push_row(vec![format!("预产期 {}", report.expected_due_date)]);
+    push_row(
+        [
+            "孕周",
+            "日期",
+            "读数",
+            "空腹均值",
+            "餐后1h均值",
+            "餐后2h均值",
+            "达标率(%)",
+            "高于目标",
+            "低于目标",
+            "酮体异常",
+            "病假日读数",
+        ]
+        .iter()
+        .map(|h| h.to_string())
+        .collect(),
+    );
+    for week in &report.weeks {
+        push_row(vec![
+            week.week.to_string(),
+            format!("{} 至 {}", week.start_date, week.end_date),
+            week.readings.len().to_string(),
+            optional(week.fasting_mean),
+            optional(week.post_1h_mean),
+            optional(week.post_2h_mean),
+            week.in_target_percent.map(|p| format!("{:.0}", p)).unwrap_or_default(),
+            week.above_target_count.to_string(),
+            week.below_target_count.to_string(),
+            week.elevated_ketone_count.to_string(),
+            week.sick_day_count.to_string(),
+        ]);
+    }
+
+    push_row(Vec::new());
+    push_row(
+        ["孕周", "时间", "时段", "血糖(mmol/L)", "目标范围", "是否达标", "病假日"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect(),
+    );
+    for week in &report.weeks {
+        for reading in &week.readings {
+            push_row(vec![
+                week.week.to_string(),
+                reading.measured_at.clone(),
+                reading.label.clone(),
+                format!("{:.1}", reading.value),
+                format!("{:.1}–{:.1}", reading.target_low, reading.target_high),
+                if reading.in_target { "是" } else { "否" }.to_string(),
+                if reading.is_sick_day { "是" } else { "" }.to_string(),
+            ]);
+        }
+    }
+    csv
+}
+
+fn week_range(
+    pregnancy: &Pregnancy,
+    from_week: Option<i64>,
+    to_week: Option<i64>,
+    today: NaiveDate,
+) -> Result<(i64, i64), String> {
+    let last_day = today.min(pregnancy.end - Duration::days(1));
+    let from_week = from_week.unwrap_or(0);
+    let to_week = to_week.unwrap_or_else(|| pregnancy.week_of(last_day).0.clamp(0, MAX_WEEK));
+    if !(0..=MAX_WEEK).contains(&from_week) || !(0..=MAX_WEEK).contains(&to_week) || from_week > to_week {
+        return Err(format!("Invalid gestational week range: {}–{}", from_week, to_week));
+    }
+    Ok((from_week, to_week))
+}
+
+// ============ Gestational Commands ============
+
+/// 单独保存预产期与分娩日期；资料编辑页提交的用户信息不包含这两项，不能经由 user_update 修改
+#[tauri::command]
+pub async fn user_pregnancy_update(
+    user_id: String,
+    expected_due_date: Option<String>,
+    delivery_date: Option<String>,
+) -> Result<ApiResponse<User>, String> {
+    let due = expected_due_date
+        .as_deref()
+        .map(|value| parse_date_arg("expected_due_date", value))
+        .transpose()?;
+    let delivered = delivery_date
+        .as_deref()
+        .map(|value| parse_date_arg("delivery_date", value))
+        .transpose()?;
+    if let Some(delivered) = delivered {
+        let due = due.ok_or_else(|| "delivery_date requires expected_due_date".to_string())?;
+        let start = due - Duration::days(PREGNANCY_DAYS);
+        if delivered < start || delivered > due + Duration::days(POST_TERM_DAYS) {
+            return Err(format!("delivery_date is outside the pregnancy: {}", delivered));
+        }
+    }
+    let conn = open_conn()?;
+
+    let updated = conn
+        .execute(
+            "UPDATE Users SET expected_due_date = ?2, delivery_date = ?3 WHERE id = ?1",
+            params![
+                user_id,
+                due.map(|d| d.format("%Y-%m-%d").to_string()),
+                delivered.map(|d| d.format("%Y-%m-%d").to_string())
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("User not found: {}", user_id));
+    }
+    let user = conn
+        .query_row("SELECT * FROM Users WHERE id = ?1", params![user_id], User::from_row)
+        .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(user),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn gestational_status(user_id: String, date: Option<String>) -> Result<ApiResponse<GestationalStatus>, String> {
+    let date = match date {
+        Some(date) => parse_date_arg("date", &date)?,
+        None => Local::now().date_naive(),
+    };
+    let conn = open_conn()?;
+    let pregnancy = load_pregnancy(&conn, &user_id)?;
+
+    let day_end = local_midnight(date + Duration::days(1));
+    let last_ketone = load_ketones(&conn, &user_id, local_midnight(pregnancy.start), day_end)?
+        .into_iter()
+        .filter(|(t, _)| *t < day_end)
+        .map(|(t, _)| t)
+        .next_back();
+    let (week, day) = pregnancy.week_of(date);
+    let is_pregnant = pregnancy.contains(date);
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(GestationalStatus {
+            user_id,
+            date: date.format("%Y-%m-%d").to_string(),
+            pregnancy_start: pregnancy.start.format("%Y-%m-%d").to_string(),
+            expected_due_date: pregnancy.due.format("%Y-%m-%d").to_string(),
+            is_pregnant,
+            week,
+            day,
+            label: week_label(week, day),
+            trimester: trimester(week),
+            days_to_due: (pregnancy.due - date).num_days(),
+            last_ketone_at: last_ketone.map(|t| format_timestamp(&t)),
+            ketone_check_due: is_pregnant
+                && last_ketone.map(|t| day_end - t > Duration::days(KETONE_CHECK_DAYS)).unwrap_or(true),
+        }),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn gestational_weekly_report(
+    user_id: String,
+    from_week: Option<i64>,
+    to_week: Option<i64>,
+) -> Result<ApiResponse<GestationalReport>, String> {
+    let conn = open_conn()?;
+    let today = Local::now().date_naive();
+    let (from_week, to_week) = week_range(&load_pregnancy(&conn, &user_id)?, from_week, to_week, today)?;
+    let report = build_weekly_report(&conn, &user_id, from_week, to_week, today)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(report),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn gestational_weekly_report_export(
+    user_id: String,
+    from_week: Option<i64>,
+    to_week: Option<i64>,
+    dest: String,
+) -> Result<ApiResponse<String>, String> {
+    let conn = open_conn()?;
+    let today = Local::now().date_naive();
+    let (from_week, to_week) = week_range(&load_pregnancy(&conn, &user_id)?, from_week, to_week, today)?;
+    let report = build_weekly_report(&conn, &user_id, from_week, to_week, today)?;
+    fs::write(&dest, weekly_report_csv(&report)).map_err(|e| format!("{}: {}", dest, e))?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(dest),
+        message: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::{DIABETES_GESTATIONAL, GENDER_FEMALE};
+
+    fn d(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn user(expected_due_date: Option<&str>, delivery_date: Option<&str>) -> User {
+        User {
+            id: "u1".to_string(),
+            username: "test".to_string(),
+            email: "test@example.com".to_string(),
+            phone: None,
+            password_hash: String::new(),
+            created_at: String::new(),
+            birthday: None,
+            gender: GENDER_FEMALE,
+            height: 0.0,
+            diabetes_type: DIABETES_GESTATIONAL,
+            diagnosis_date: None,
+            treatment_plan: 0,
+            target_weight: None,
+            target_hb_a1c: None,
+            target_calories: None,
+            target_carbohydrates: None,
+            expected_due_date: expected_due_date.map(str::to_string),
+            delivery_date: delivery_date.map(str::to_string),
+        }
+    }
+
+    fn pregnancy(delivery_date: Option<&str>) -> Pregnancy {
+        Pregnancy::from_user(&user(Some("2026-10-08"), delivery_date)).unwrap()
+    }
+
+    #[test]
+    fn pregnancy_starts_280_days_before_due_date() {
+        let pregnancy = pregnancy(None);
+        assert_eq!(pregnancy.start, d("2026-01-01"));
+        assert_eq!(pregnancy.due, d("2026-10-08"));
+        // 未登记分娩日期时按预产期后 14 天结束
+        assert_eq!(pregnancy.end, d("2026-10-23"));
+        assert!(Pregnancy::from_user(&user(None, None)).is_none());
+    }
+
+    #[test]
+    fn delivery_date_ends_pregnancy() {
+        let pregnancy = pregnancy(Some("2026-09-20"));
+        assert!(pregnancy.contains(d("2026-09-20")));
+        assert!(!pregnancy.contains(d("2026-09-21")));
+        assert!(!pregnancy.contains(d("2025-12-31")));
+    }
+
+    #[test]
+    fn week_and_day_of_pregnancy() {
+        let pregnancy = pregnancy(None);
+        assert_eq!(pregnancy.week_of(d("2026-01-01")), (0, 0));
+        assert_eq!(pregnancy.week_of(d("2026-01-07")), (0, 6));
+        assert_eq!(pregnancy.week_of(d("2026-01-08")), (1, 0));
+        assert_eq!(pregnancy.week_of(d("2026-06-21")), (24, 3));
+        assert_eq!(pregnancy.week_of(d("2026-10-08")), (40, 0));
+        assert_eq!(pregnancy.week_of(d("2025-12-31")), (-1, 6));
+        assert_eq!(week_label(24, 3), "孕 24+3 周");
+    }
+
+    #[test]
+    fn trimesters_by_completed_weeks() {
+        assert_eq!(trimester(0), 1);
+        assert_eq!(trimester(13), 1);
+        assert_eq!(trimester(14), 2);
+        assert_eq!(trimester(27), 2);
+        assert_eq!(trimester(28), 3);
+        assert_eq!(trimester(42), 3);
+    }
+
+    #[test]
+    fn accepts_timestamps_as_due_date() {
+        let due_at = "2026-10-08T12:00:00.000Z";
+        let pregnancy = Pregnancy::from_user(&user(Some(due_at), None)).unwrap();
+        assert_eq!(pregnancy.due, parse_timestamp(due_at).unwrap().with_timezone(&Local).date_naive());
+    }
+
+    #[test]
+    fn report_range_defaults_to_current_week() {
+        let pregnancy = pregnancy(None);
+        assert_eq!(week_range(&pregnancy, None, None, d("2026-06-21")), Ok((0, 24)));
+        // 分娩后默认截止到妊娠最后一周
+        assert_eq!(week_range(&pregnancy, None, None, d("2027-01-01")), Ok((0, 42)));
+        assert!(week_range(&pregnancy, Some(10), Some(5), d("2026-06-21")).is_err());
+        assert!(week_range(&pregnancy, None, Some(MAX_WEEK + 1), d("2026-06-21")).is_err());
+    }
+}
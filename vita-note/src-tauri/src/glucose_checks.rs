@@ -0,0 +1,398 @@
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::database::{open_conn, ApiResponse, BloodGlucose, FoodEntry};
+use crate::datetime::{format_timestamp, parse_timestamp};
+use crate::enums::{MEAL_SNACK, MEASUREMENT_AFTER_MEAL_1H, MEASUREMENT_AFTER_MEAL_2H};
+use crate::reminders::{acknowledge_by_source, SOURCE_GLUCOSE_CHECK};
+
+pub const CHECK_PENDING: &str = "pending";
+pub const CHECK_COMPLETED: &str = "completed";
+pub const CHECK_MISSED: &str = "missed";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GlucoseCheckPreferences {
+    pub user_id: String,
+    pub enabled: bool,
+    pub check_1h: bool,
+    pub check_2h: bool,
+    pub window_minutes: i32,
+    pub include_snacks: bool,
+}
+
+impl GlucoseCheckPreferences {
+    pub fn defaults(user_id: &str) -> GlucoseCheckPreferences {
+        GlucoseCheckPreferences {
+            user_id: user_id.to_string(),
+            enabled: true,
+            check_1h: true,
+            check_2h: true,
+            window_minutes: 20,
+            include_snacks: false,
+        }
+    }
+
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<GlucoseCheckPreferences> {
+        Ok(GlucoseCheckPreferences {
+            user_id: row.get(0)?,
+            enabled: row.get::<_, i32>(1)? == 1,
+            check_1h: row.get::<_, i32>(2)? == 1,
+            check_2h: row.get::<_, i32>(3)? == 1,
+            window_minutes: row.get(4)?,
+            include_snacks: row.get::<_, i32>(5)? == 1,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GlucoseCheck {
+    pub id: String,
+    pub user_id: String,
+    pub created_at: String,
+    pub food_entry_id: String,
+    pub meal_type: i32,
+    pub meal_time: String,
+    pub measurement_time: i32,
+    pub due_at: String,
+    pub window_start: String,
+    pub window_end: String,
+    pub status: String,
+    pub completed_by: Option<String>,
+    pub completed_at: Option<String>,
+}
+
+impl GlucoseCheck {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<GlucoseCheck> {
+        Ok(GlucoseCheck {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            created_at: row.get(2)?,
+            food_entry_id: row.get(3)?,
+            meal_type: row.get(4)?,
+            meal_time: row.get(5)?,
+            measurement_time: row.get(6)?,
+            due_at: row.get(7)?,
+            window_start: row.get(8)?,
+            window_end: row.get(9)?,
+            status: row.get(10)?,
+            completed_by: row.get(11)?,
+            completed_at: row.get(12)?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GlucoseCheckReport {
+    pub checks: Vec<GlucoseCheck>,
+    pub completed: i64,
+    pub missed: i64,
+    pub pending: i64,
+    pub completion_rate: Option<f64>,
+}
+
+pub(crate) fn load_preferences(conn: &Connection, user_id: &str) -> Result<GlucoseCheckPreferences, String> {
+    let prefs = conn
+        .query_row(
+            "SELECT * FROM GlucoseCheckPreferences WHERE user_id = ?1",
+            params![user_id],
+            GlucoseCheckPreferences::from_row,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    Ok(prefs.unwrap_or_else(|| GlucoseCheckPreferences::defaults(user_id)))
+}
+
+// ============ Food Entry Hook ============
+
+/// 记录餐食后按用户偏好安排餐后 1h / 2h 血糖检测；同一餐的多条食物只安排一次
+pub(crate) fn schedule_post_meal_checks(conn: &Connection, entry: &FoodEntry) -> Result<Vec<GlucoseCheck>, String> {
+    let prefs = load_preferences(conn, &entry.user_id)?;
+    if !prefs.enabled || (entry.meal_type == MEAL_SNACK && !prefs.include_snacks) {
+        return Ok(Vec::new());
+    }
+    let Some(meal_time) = parse_timestamp(&entry.meal_time) else {
+        return Ok(Vec::new());
+    };
+
+    let mut offsets = Vec::new();
+    if prefs.check_1h {
+        offsets.push((MEASUREMENT_AFTER_MEAL_1H, 60));
+    }
+    if prefs.check_2h {
+        offsets.push((MEASUREMENT_AFTER_MEAL_2H, 120));
+    }
+
+    let window = Duration::minutes(prefs.window_minutes.max(0) as i64);
+    let now = Utc::now();
+    let created_at = format_timestamp(&now);
+    let mut scheduled = Vec::new();
+    for (measurement_time, offset_minutes) in offsets {
+        let due_at = meal_time + Duration::minutes(offset_minutes);
+        // 补记的餐食不再安排已经过了时间的检测
+        if due_at <= now {
+            continue;
+        }
+        let check = GlucoseCheck {
+            id: format!("{}:{}", entry.id, measurement_time),
+            user_id: entry.user_id.clone(),
+            created_at: created_at.clone(),
+            food_entry_id: entry.id.clone(),
+            meal_type: entry.meal_type,
+            meal_time: format_timestamp(&meal_time),
+            measurement_time,
+            due_at: format_timestamp(&due_at),
+            window_start: format_timestamp(&(due_at - window)),
+            window_end: format_timestamp(&(due_at + window)),
+            status: CHECK_PENDING.to_string(),
+            completed_by: None,
+            completed_at: None,
+        };
+
+        let inserted = conn
+            .execute(
+                r#"INSERT OR IGNORE INTO GlucoseChecks (id, user_id, created_at, food_entry_id, meal_type,
+                    meal_time, measurement_time, due_at, window_start, window_end, status,
+                    completed_by, completed_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)"#,
+                params![
+                    check.id, check.user_id, check.created_at, check.food_entry_id, check.meal_type,
+                    check.meal_time, check.measurement_time, check.due_at, check.window_start,
+                    check.window_end, check.status, check.completed_by, check.completed_at
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        if inserted == 1 {
+            scheduled.push(check);
+        }
+    }
+
+    Ok(scheduled)
+}
+
+/// 删除饮食记录时取消它安排的未完成检测；同一餐还有其他食物时由剩下的记录重新安排
+pub(crate) fn cancel_post_meal_checks(conn: &Connection, entry: &FoodEntry) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM GlucoseChecks WHERE food_entry_id = ?1 AND status != ?2",
+        params![entry.id, CHECK_COMPLETED],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let remaining = conn
+        .query_row(
+            "SELECT * FROM FoodEntries WHERE user_id = ?1 AND meal_time = ?2 AND id != ?3 LIMIT 1",
+            params![entry.user_id, entry.meal_time, entry.id],
+            FoodEntry::from_row,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if let Some(other) = remaining {
+        schedule_post_meal_checks(conn, &other)?;
+    }
+    Ok(())
+}
+
+// ============ Blood Glucose Hook ============
+
+/// 新增餐后血糖时，自动完成时间窗口内最接近的同类型待测任务
+pub(crate) fn complete_matching_check(conn: &Connection, reading: &BloodGlucose) -> Result<Option<String>, String> {
+    if reading.measurement_time != MEASUREMENT_AFTER_MEAL_1H && reading.measurement_time != MEASUREMENT_AFTER_MEAL_2H {
+        return Ok(None);
+    }
+    let Some(measured_at) = reading.measured_at() else {
+        return Ok(None);
+    };
+    let measured = format_timestamp(&measured_at);
+
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM GlucoseChecks
+            WHERE user_id = ?1 AND measurement_time = ?2 AND status != ?3
+                AND window_start <= ?4 AND window_end >= ?4"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let nearest = stmt
+        .query_map(
+            params![reading.user_id, reading.measurement_time, CHECK_COMPLETED, measured],
+            GlucoseCheck::from_row,
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|check| {
+            let due_at = parse_timestamp(&check.due_at)?;
+            Some(((measured_at - due_at).num_seconds().abs(), check))
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, check)| check);
+
+    let Some(check) = nearest else {
+        return Ok(None);
+    };
+
+    conn.execute(
+        "UPDATE GlucoseChecks SET status = ?2, completed_by = ?3, completed_at = ?4 WHERE id = ?1",
+        params![check.id, CHECK_COMPLETED, reading.id, measured],
+    )
+    .map_err(|e| e.to_string())?;
+    acknowledge_by_source(conn, SOURCE_GLUCOSE_CHECK, &check.id, &measured)?;
+
+    Ok(Some(check.id))
+}
+
+/// 完成检测的血糖记录被删除后，检测恢复为待测；时间窗口已过的直接记为错过
+pub(crate) fn revert_completed_checks(conn: &Connection, glucose_id: &str) -> Result<(), String> {
+    conn.execute(
+        r#"UPDATE GlucoseChecks SET
+            status = CASE WHEN window_end < ?2 THEN ?3 ELSE ?4 END,
+            completed_by = NULL, completed_at = NULL
+        WHERE completed_by = ?1"#,
+        params![glucose_id, format_timestamp(&Utc::now()), CHECK_MISSED, CHECK_PENDING],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 将已过时间窗口仍未完成的检测标记为错过，返回本次新标记的记录
+pub(crate) fn mark_missed_checks(conn: &Connection, now: DateTime<Utc>) -> Result<Vec<GlucoseCheck>, String> {
+    let now = format_timestamp(&now);
+
+    let mut stmt = conn
+        .prepare("SELECT * FROM GlucoseChecks WHERE status = ?1 AND window_end < ?2")
+        .map_err(|e| e.to_string())?;
+    let missed: Vec<GlucoseCheck> = stmt
+        .query_map(params![CHECK_PENDING, now], GlucoseCheck::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    conn.execute(
+        "UPDATE GlucoseChecks SET status = ?1 WHERE status = ?2 AND window_end < ?3",
+        params![CHECK_MISSED, CHECK_PENDING, now],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(missed
+        .into_iter()
+        .map(|mut check| {
+            check.status = CHECK_MISSED.to_string();
+            check
+        })
+        .collect())
+}
+
+/// 到期但尚未完成的检测，由提醒调度器发送通知
+pub(crate) fn pending_checks_due(
+    conn: &Connection,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<GlucoseCheck>, String> {
+    let mut stmt = conn
+        .prepare("SELECT * FROM GlucoseChecks WHERE status = ?1 AND due_at > ?2 AND due_at <= ?3")
+        .map_err(|e| e.to_string())?;
+    let checks = stmt
+        .query_map(
+            params![CHECK_PENDING, format_timestamp(&from), format_timestamp(&to)],
+            GlucoseCheck::from_row,
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(checks)
+}
+
+// ============ Glucose Check Commands ============
+
+#[tauri::command]
+pub async fn glucose_check_preferences_get(user_id: String) -> Result<ApiResponse<GlucoseCheckPreferences>, String> {
+    let conn = open_conn()?;
+    let prefs = load_preferences(&conn, &user_id)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(prefs),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn glucose_check_preferences_update(
+    prefs: GlucoseCheckPreferences,
+) -> Result<ApiResponse<GlucoseCheckPreferences>, String> {
+    if prefs.window_minutes < 0 {
+        return Err("window_minutes must not be negative".to_string());
+    }
+    let conn = open_conn()?;
+
+    conn.execute(
+        r#"INSERT OR REPLACE INTO GlucoseCheckPreferences (user_id, enabled, check_1h, check_2h,
+            window_minutes, include_snacks)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)"#,
+        params![
+            prefs.user_id,
+            if prefs.enabled { 1i32 } else { 0i32 },
+            if prefs.check_1h { 1i32 } else { 0i32 },
+            if prefs.check_2h { 1i32 } else { 0i32 },
+            prefs.window_minutes,
+            if prefs.include_snacks { 1i32 } else { 0i32 }
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(prefs),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn glucose_checks_report(
+    user_id: String,
+    start_date: String,
+    end_date: String,
+) -> Result<ApiResponse<GlucoseCheckReport>, String> {
+    let conn = open_conn()?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM GlucoseChecks
+            WHERE user_id = ?1 AND due_at >= ?2 AND due_at < ?3
+            ORDER BY due_at DESC"#,
+        )
+        .map_err(|e| e.to_string())?;
+    // 只读报告不改写状态；调度器尚未处理的过期检测在这里按错过展示
+    let now = format_timestamp(&Utc::now());
+    let checks: Vec<GlucoseCheck> = stmt
+        .query_map(params![user_id, start_date, end_date], GlucoseCheck::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .map(|mut check| {
+            if check.status == CHECK_PENDING && check.window_end < now {
+                check.status = CHECK_MISSED.to_string();
+            }
+            check
+        })
+        .collect();
+
+    let count = |status: &str| checks.iter().filter(|c| c.status == status).count() as i64;
+    let completed = count(CHECK_COMPLETED);
+    let missed = count(CHECK_MISSED);
+    let pending = count(CHECK_PENDING);
+    let completion_rate = if completed + missed > 0 {
+        Some(completed as f64 / (completed + missed) as f64)
+    } else {
+        None
+    };
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(GlucoseCheckReport {
+            checks,
+            completed,
+            missed,
+            pending,
+            completion_rate,
+        }),
+        message: None,
+    })
+}
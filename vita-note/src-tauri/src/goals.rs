@@ -0,0 +1,637 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::database::{open_conn, ApiResponse, FoodEntry, Medication};
+use crate::datetime::{local_midnight, parse_date_arg, parse_timestamp};
+use crate::enums::{meal_type_label, MEAL_BREAKFAST, MEAL_SNACK, MEASUREMENT_AFTER_MEAL_2H, MEASUREMENT_FASTING};
+use crate::health_context::{
+    CATEGORY_ACTIVITY, CATEGORY_BODY, CATEGORY_GLUCOSE, CATEGORY_MEDICATIONS, CATEGORY_NUTRITION, CATEGORY_PROFILE,
+};
+use crate::meal_pairing::load_readings;
+use crate::sick_days::load_sick_periods;
+use crate::summaries::{GoalAttainment, PERIOD_DAILY, PERIOD_WEEKLY};
+use crate::targets::load_targets;
+
+pub const METRIC_GLUCOSE_MEAN: &str = "glucose_mean";
+pub const METRIC_FASTING_GLUCOSE: &str = "fasting_glucose";
+pub const METRIC_POSTPRANDIAL_GLUCOSE: &str = "postprandial_glucose";
+pub const METRIC_TIME_IN_RANGE: &str = "time_in_range";
+pub const METRIC_GLUCOSE_CHECKS: &str = "glucose_checks";
+pub const METRIC_CALORIES: &str = "calories";
+pub const METRIC_CARBOHYDRATES: &str = "carbohydrates";
+pub const METRIC_MEAL_CARBOHYDRATES: &str = "meal_carbohydrates";
+pub const METRIC_WEIGHT: &str = "weight";
+pub const METRIC_EXERCISE_MINUTES: &str = "exercise_minutes";
+pub const METRIC_EXERCISE_SESSIONS: &str = "exercise_sessions";
+pub const METRIC_MEDICATION_ADHERENCE: &str = "medication_adherence";
+
+pub const OP_LT: &str = "lt";
+pub const OP_LTE: &str = "lte";
+pub const OP_GT: &str = "gt";
+pub const OP_GTE: &str = "gte";
+
+/// (指标, 名称, 单位, 数据类别)
+const METRICS: [(&str, &str, &str, &str); 12] = [
+    (METRIC_GLUCOSE_MEAN, "平均血糖", "mmol/L", CATEGORY_GLUCOSE),
+    (METRIC_FASTING_GLUCOSE, "平均空腹血糖", "mmol/L", CATEGORY_GLUCOSE),
+    (METRIC_POSTPRANDIAL_GLUCOSE, "平均餐后2h血糖", "mmol/L", CATEGORY_GLUCOSE),
+    (METRIC_TIME_IN_RANGE, "TIR", "%", CATEGORY_GLUCOSE),
+    (METRIC_GLUCOSE_CHECKS, "血糖检测次数", "次", CATEGORY_GLUCOSE),
+    (METRIC_CALORIES, "日均热量", "kcal", CATEGORY_NUTRITION),
+    (METRIC_CARBOHYDRATES, "日均碳水", "g", CATEGORY_NUTRITION),
+    (METRIC_MEAL_CARBOHYDRATES, "单餐碳水", "g", CATEGORY_NUTRITION),
+    (METRIC_WEIGHT, "体重", "kg", CATEGORY_BODY),
+    (METRIC_EXERCISE_MINUTES, "运动时长", "分钟", CATEGORY_ACTIVITY),
+    (METRIC_EXERCISE_SESSIONS, "运动次数", "次", CATEGORY_ACTIVITY),
+    (METRIC_MEDICATION_ADHERENCE, "用药依从率", "%", CATEGORY_MEDICATIONS),
+];
+
+// 连续达标天数最多回溯一年
+const MAX_LOOKBACK_DAYS: i64 = 366;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Goal {
+    pub id: String,
+    pub user_id: String,
+    pub created_at: String,
+    pub metric: String,
+    pub operator: String,
+    pub target: f64,
+    /// 统计周期：daily 或 weekly（周一开始）
+    pub period: String,
+    /// 仅用于单餐碳水，为空时统计所有餐次
+    pub meal_type: Option<i32>,
+    pub label: Option<String>,
+    /// 生效日期（本地日期），end_date 为空表示长期有效
+    pub start_date: String,
+    pub end_date: Option<String>,
+    pub is_active: bool,
+}
+
+impl Goal {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Goal> {
+        Ok(Goal {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            created_at: row.get(2)?,
+            metric: row.get(3)?,
+            operator: row.get(4)?,
+            target: row.get(5)?,
+            period: row.get(6)?,
+            meal_type: row.get(7)?,
+            label: row.get(8)?,
+            start_date: row.get(9)?,
+            end_date: row.get(10)?,
+            is_active: row.get::<_, i32>(11)? == 1,
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if metric_info(&self.metric).is_none() {
+            return Err(format!("Unknown goal metric: {}", self.metric));
+        }
+        operator_symbol(&self.operator)?;
+        if self.period != PERIOD_DAILY && self.period != PERIOD_WEEKLY {
+            return Err(format!("Unknown goal period: {}", self.period));
+        }
+        if !self.target.is_finite() || self.target < 0.0 {
+            return Err(format!("Invalid goal target: {}", self.target));
+        }
+        match self.meal_type {
+            Some(_) if self.metric != METRIC_MEAL_CARBOHYDRATES => {
+                return Err("meal_type only applies to meal_carbohydrates".to_string())
+            }
+            Some(meal) if !(MEAL_BREAKFAST..=MEAL_SNACK).contains(&meal) => {
+                return Err(format!("Invalid meal_type: {}", meal))
+            }
+            _ => {}
+        }
+        let start = parse_date_arg("start_date", &self.start_date)?;
+        if let Some(end) = &self.end_date {
+            if parse_date_arg("end_date", end)? < start {
+                return Err("end_date must not be before start_date".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// 在 date 当天是否生效
+    fn applies_on(&self, date: NaiveDate) -> bool {
+        let date = date.format("%Y-%m-%d").to_string();
+        self.is_active
+            && self.start_date <= date
+            && self.end_date.as_deref().map(|end| date.as_str() <= end).unwrap_or(true)
+    }
+
+    fn display_label(&self) -> String {
+        if let Some(label) = &self.label {
+            return label.clone();
+        }
+        let (_, name, unit, _) = metric_info(&self.metric).unwrap_or(("", "", "", ""));
+        let name = match self.meal_type {
+            Some(meal) => format!("{}{}", meal_type_label(meal), name),
+            None => name.to_string(),
+        };
+        format!("{} {} {} {}", name, operator_symbol(&self.operator).unwrap_or("?"), self.target, unit)
+    }
+
+    fn is_met(&self, value: f64) -> bool {
+        match self.operator.as_str() {
+            OP_LT => value < self.target,
+            OP_LTE => value <= self.target,
+            OP_GT => value > self.target,
+            _ => value >= self.target,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GoalProgress {
+    pub goal: Goal,
+    pub label: String,
+    pub unit: String,
+    /// 包含 date 的统计周期 [period_start, period_end)
+    pub period_start: String,
+    pub period_end: String,
+    /// 周期内没有相关记录时为空，视为未达标
+    pub current_value: Option<f64>,
+    pub met: bool,
+    /// 周期尚未结束
+    pub in_progress: bool,
+    /// 截至当前周期连续达标的周期数；进行中的周期未达标时不中断
+    pub current_streak: usize,
+    pub best_streak: usize,
+}
+
+fn metric_info(metric: &str) -> Option<(&'static str, &'static str, &'static str, &'static str)> {
+    METRICS.iter().find(|(m, ..)| *m == metric).copied()
+}
+
+fn operator_symbol(operator: &str) -> Result<&'static str, String> {
+    match operator {
+        OP_LT => Ok("<"),
+        OP_LTE => Ok("≤"),
+        OP_GT => Ok(">"),
+        OP_GTE => Ok("≥"),
+        other => Err(format!("Unknown goal operator: {}", other)),
+    }
+}
+
+/// 返回包含 date 的周期 [start, end)，周从周一开始
+fn period_bounds(period: &str, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    if period == PERIOD_WEEKLY {
+        let start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+        (start, start + Duration::days(7))
+    } else {
+        (date, date + Duration::days(1))
+    }
+}
+
+/// 按周期先后给出是否达成，返回 (当前连续达成, 最长连续达成)；进行中的周期尚未达成时不打断当前连续
+fn streaks(outcomes: &[bool], in_progress: bool) -> (usize, usize) {
+    let mut best = 0;
+    let mut run = 0;
+    for met in outcomes {
+        run = if *met { run + 1 } else { 0 };
+        best = best.max(run);
+    }
+    let last_met = outcomes.last().copied().unwrap_or(false);
+    let skip = if in_progress && !last_met { 1 } else { 0 };
+    let current = outcomes.iter().rev().skip(skip).take_while(|met| **met).count();
+    (current, best)
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+fn percent(part: usize, total: usize) -> Option<f64> {
+    if total == 0 {
+        None
+    } else {
+        Some(part as f64 / total as f64 * 100.0)
+    }
+}
+
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+/// 一次性读取评估某个指标所需的记录，按时间正序
+enum MetricRecords {
+    /// (时间, 测量时段, 数值, 是否在当时适用的 TIR 范围内)
+    Glucose(Vec<(DateTime<Utc>, i32, f64, bool)>),
+    Food(Vec<(DateTime<Utc>, FoodEntry)>),
+    Weight(Vec<(DateTime<Utc>, f64)>),
+    Exercise(Vec<(DateTime<Utc>, i32)>),
+    Doses(Vec<(DateTime<Utc>, bool)>),
+}
+
+fn load_records(
+    conn: &Connection,
+    user_id: &str,
+    metric: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<MetricRecords, String> {
+    let from_date = from.format("%Y-%m-%d").to_string();
+    let to_date = (to + Duration::days(1)).format("%Y-%m-%d").to_string();
+    let records = match metric {
+        METRIC_CALORIES | METRIC_CARBOHYDRATES | METRIC_MEAL_CARBOHYDRATES => {
+            let mut stmt = conn
+                .prepare("SELECT * FROM FoodEntries WHERE user_id = ?1 AND meal_time >= ?2 AND meal_time <= ?3")
+                .map_err(|e| e.to_string())?;
+            let entries = stmt
+                .query_map(params![user_id, from_date, to_date], FoodEntry::from_row)
+                .map_err(|e| e.to_string())?
+                .filter_map(|r| r.ok())
+                .filter_map(|entry| Some((parse_timestamp(&entry.meal_time)?, entry)))
+                .collect();
+            MetricRecords::Food(entries)
+        }
+        // 体重取周期结束前最近一次测量，因此不限起点
+        METRIC_WEIGHT => {
+            let mut stmt = conn
+                .prepare(
+                    r#"SELECT measured_at, weight FROM BodyMeasurements
+                    WHERE user_id = ?1 AND measured_at <= ?2
+                    ORDER BY measured_at ASC"#,
+                )
+                .map_err(|e| e.to_string())?;
+            let weights = stmt
+                .query_map(params![user_id, to_date], |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)))
+                .map_err(|e| e.to_string())?
+                .filter_map(|r| r.ok())
+                .filter_map(|(time, weight)| Some((parse_timestamp(&time)?, weight)))
+                .collect();
+            MetricRecords::Weight(weights)
+        }
+        METRIC_EXERCISE_MINUTES | METRIC_EXERCISE_SESSIONS => {
+            let mut stmt = conn
+                .prepare(
+                    r#"SELECT start_time, duration_minutes FROM ExerciseEntries
+                    WHERE user_id = ?1 AND start_time >= ?2 AND start_time <= ?3"#,
+                )
+                .map_err(|e| e.to_string())?;
+            let sessions = stmt
+                .query_map(params![user_id, from_date, to_date], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)?))
+                })
+                .map_err(|e| e.to_string())?
+                .filter_map(|r| r.ok())
+                .filter_map(|(time, minutes)| Some((parse_timestamp(&time)?, minutes)))
+                .collect();
+            MetricRecords::Exercise(sessions)
+        }
+        METRIC_MEDICATION_ADHERENCE => {
+            let mut stmt = conn
+                .prepare(
+                    r#"SELECT * FROM Medications
+                    WHERE user_id = ?1 AND scheduled_time >= ?2 AND scheduled_time <= ?3"#,
+                )
+                .map_err(|e| e.to_string())?;
+            let doses = stmt
+                .query_map(params![user_id, from_date, to_date], Medication::from_row)
+                .map_err(|e| e.to_string())?
+                .filter_map(|r| r.ok())
+                .filter_map(|dose| Some((parse_timestamp(&dose.scheduled_time)?, dose.is_taken)))
+                .collect();
+            MetricRecords::Doses(doses)
+        }
+        // 病假日期间的血糖不计入目标进度和连续达标天数
+        _ => {
+            let targets = load_targets(conn, user_id)?;
+            let sick = load_sick_periods(conn, user_id)?;
+            MetricRecords::Glucose(
+                load_readings(conn, user_id, from, to)?
+                    .into_iter()
+                    .filter(|(t, _)| !sick.contains(*t))
+                    .map(|(t, reading)| {
+                        let in_range = targets.at(t).in_tir(reading.value);
+                        (t, reading.measurement_time, reading.value, in_range)
+                    })
+                    .collect(),
+            )
+        }
+    };
+    Ok(records)
+}
+
+/// 计算 [from, to) 内的指标值，没有可用记录时返回 None
+fn evaluate(goal: &Goal, records: &MetricRecords, from: DateTime<Utc>, to: DateTime<Utc>) -> Option<f64> {
+    let within = |t: &DateTime<Utc>| *t >= from && *t < to;
+    match records {
+        MetricRecords::Glucose(readings) => {
+            let values: Vec<(f64, bool)> = readings
+                .iter()
+                .filter(|(t, time, ..)| {
+                    within(t)
+                        && match goal.metric.as_str() {
+                            METRIC_FASTING_GLUCOSE => *time == MEASUREMENT_FASTING,
+                            METRIC_POSTPRANDIAL_GLUCOSE => *time == MEASUREMENT_AFTER_MEAL_2H,
+                            _ => true,
+                        }
+                })
+                .map(|(_, _, value, in_range)| (*value, *in_range))
+                .collect();
+            match goal.metric.as_str() {
+                METRIC_GLUCOSE_CHECKS => Some(values.len() as f64),
+                METRIC_TIME_IN_RANGE => percent(values.iter().filter(|(_, in_range)| *in_range).count(), values.len()),
+                _ => mean(&values.iter().map(|(v, _)| *v).collect::<Vec<f64>>()),
+            }
+        }
+        MetricRecords::Food(entries) => {
+            let entries: Vec<&(DateTime<Utc>, FoodEntry)> = entries.iter().filter(|(t, _)| within(t)).collect();
+            if goal.metric == METRIC_MEAL_CARBOHYDRATES {
+                // 同一天同一餐次的食物合计为一餐，取碳水最高的一餐
+                let mut meals: BTreeMap<(NaiveDate, i32), f64> = BTreeMap::new();
+                for (t, entry) in entries {
+                    if goal.meal_type.map(|meal| meal == entry.meal_type).unwrap_or(true) {
+                        *meals.entry((t.with_timezone(&Local).date_naive(), entry.meal_type)).or_default() +=
+                            entry.carbohydrates;
+                    }
+                }
+                meals.into_values().reduce(f64::max)
+            } else {
+                // 按记录了饮食的天数求日均，与周期总结一致
+                let days: HashSet<NaiveDate> = entries.iter().map(|(t, _)| t.with_timezone(&Local).date_naive()).collect();
+                if days.is_empty() {
+                    return None;
+                }
+                let total: f64 = entries
+                    .iter()
+                    .map(|(_, e)| if goal.metric == METRIC_CALORIES { e.calories } else { e.carbohydrates })
+                    .sum();
+                Some(total / days.len() as f64)
+            }
+        }
+        MetricRecords::Weight(weights) => weights.iter().rev().find(|(t, _)| *t < to).map(|(_, w)| *w),
+        MetricRecords::Exercise(sessions) => {
+            let sessions: Vec<i32> = sessions.iter().filter(|(t, _)| within(t)).map(|(_, m)| *m).collect();
+            if goal.metric == METRIC_EXERCISE_SESSIONS {
+                Some(sessions.len() as f64)
+            } else {
+                Some(sessions.iter().sum::<i32>() as f64)
+            }
+        }
+        MetricRecords::Doses(doses) => {
+            let doses: Vec<bool> = doses.iter().filter(|(t, _)| within(t)).map(|(_, taken)| *taken).collect();
+            percent(doses.iter().filter(|taken| **taken).count(), doses.len())
+        }
+    }
+}
+
+/// 评估目标在包含 date 的周期内的完成情况，并统计连续达标
+pub(crate) fn goal_progress(
+    conn: &Connection,
+    goal: &Goal,
+    date: NaiveDate,
+    today: NaiveDate,
+) -> Result<GoalProgress, String> {
+    let (period_start, period_end) = period_bounds(&goal.period, date);
+    let goal_start = NaiveDate::parse_from_str(&goal.start_date, "%Y-%m-%d").unwrap_or(period_start);
+    let first = period_bounds(&goal.period, goal_start.max(date - Duration::days(MAX_LOOKBACK_DAYS))).0;
+    // 按周统计时首个周期可能早于目标开始日期，开始日期之前的记录不计入
+    let from = first.max(goal_start);
+    let records = load_records(conn, &goal.user_id, &goal.metric, local_midnight(from), local_midnight(period_end))?;
+
+    let step = if goal.period == PERIOD_WEEKLY { 7 } else { 1 };
+    let mut outcomes = Vec::new();
+    let mut start = first;
+    while start < period_end {
+        let end = start + Duration::days(step);
+        let value = evaluate(goal, &records, local_midnight(start.max(from)), local_midnight(end));
+        outcomes.push((value, value.map(|v| goal.is_met(v)).unwrap_or(false)));
+        start = end;
+    }
+
+    let (current_value, met) = outcomes.last().copied().unwrap_or((None, false));
+    let in_progress = period_end > today;
+    let met_by_period: Vec<bool> = outcomes.iter().map(|(_, met)| *met).collect();
+    let (current_streak, best_streak) = streaks(&met_by_period, in_progress);
+
+    let (_, _, unit, _) = metric_info(&goal.metric).unwrap_or(("", "", "", ""));
+    Ok(GoalProgress {
+        goal: goal.clone(),
+        label: goal.display_label(),
+        unit: unit.to_string(),
+        period_start: period_start.format("%Y-%m-%d").to_string(),
+        period_end: period_end.format("%Y-%m-%d").to_string(),
+        current_value: current_value.map(round1),
+        met,
+        in_progress,
+        current_streak,
+        best_streak,
+    })
+}
+
+pub(crate) fn load_goals(conn: &Connection, user_id: &str) -> Result<Vec<Goal>, String> {
+    let mut stmt = conn
+        .prepare("SELECT * FROM Goals WHERE user_id = ?1 ORDER BY created_at ASC")
+        .map_err(|e| e.to_string())?;
+    let goals = stmt
+        .query_map(params![user_id], Goal::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(goals)
+}
+
+/// 周期总结中的自定义目标：只纳入统计周期与总结周期 [start, end) 完全一致的目标
+pub(crate) fn attainment_for_period(
+    conn: &Connection,
+    user_id: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<GoalAttainment>, String> {
+    let mut attainments = Vec::new();
+    for goal in load_goals(conn, user_id)? {
+        if !goal.applies_on(start) || period_bounds(&goal.period, start) != (start, end) {
+            continue;
+        }
+        let records = load_records(conn, user_id, &goal.metric, local_midnight(start), local_midnight(end))?;
+        let Some(actual) = evaluate(&goal, &records, local_midnight(start), local_midnight(end)) else {
+            continue;
+        };
+        let (_, _, _, category) = metric_info(&goal.metric).unwrap_or(("", "", "", CATEGORY_PROFILE));
+        attainments.push(GoalAttainment {
+            goal: goal.id.clone(),
+            label: goal.display_label(),
+            category: category.to_string(),
+            target: goal.target,
+            actual: round1(actual),
+            met: goal.is_met(actual),
+        });
+    }
+    Ok(attainments)
+}
+
+// ============ Goal Commands ============
+
+#[tauri::command]
+pub async fn goal_create(goal: Goal) -> Result<ApiResponse<Goal>, String> {
+    goal.validate()?;
+    let conn = open_conn()?;
+
+    conn.execute(
+        r#"INSERT INTO Goals (id, user_id, created_at, metric, operator, target, period, meal_type, label,
+            start_date, end_date, is_active)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)"#,
+        params![
+            goal.id, goal.user_id, goal.created_at, goal.metric, goal.operator, goal.target, goal.period,
+            goal.meal_type, goal.label, goal.start_date, goal.end_date,
+            if goal.is_active { 1i32 } else { 0i32 }
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(goal),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn goals_get(user_id: String) -> Result<ApiResponse<Vec<Goal>>, String> {
+    let conn = open_conn()?;
+    let goals = load_goals(&conn, &user_id)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(goals),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn goal_update(goal: Goal) -> Result<ApiResponse<Goal>, String> {
+    goal.validate()?;
+    let conn = open_conn()?;
+
+    conn.execute(
+        r#"UPDATE Goals SET
+            metric = ?2, operator = ?3, target = ?4, period = ?5, meal_type = ?6, label = ?7,
+            start_date = ?8, end_date = ?9, is_active = ?10
+        WHERE id = ?1"#,
+        params![
+            goal.id, goal.metric, goal.operator, goal.target, goal.period, goal.meal_type, goal.label,
+            goal.start_date, goal.end_date,
+            if goal.is_active { 1i32 } else { 0i32 }
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(goal),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn goal_delete(id: String) -> Result<ApiResponse<String>, String> {
+    let conn = open_conn()?;
+
+    conn.execute("DELETE FROM Goals WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some("Deleted".to_string()),
+        message: None,
+    })
+}
+
+/// date 为本地日期，只评估当天生效的目标
+#[tauri::command]
+pub async fn goals_progress(user_id: String, date: String) -> Result<ApiResponse<Vec<GoalProgress>>, String> {
+    let date = parse_date_arg("date", &date)?;
+    let conn = open_conn()?;
+    let today = Local::now().date_naive();
+
+    let mut progress = Vec::new();
+    for goal in load_goals(&conn, &user_id)?.iter().filter(|g| g.applies_on(date)) {
+        progress.push(goal_progress(&conn, goal, date, today)?);
+    }
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(progress),
+        message: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn goal(operator: &str) -> Goal {
+        Goal {
+            id: "goal1".to_string(),
+            user_id: "u1".to_string(),
+            created_at: String::new(),
+            metric: METRIC_GLUCOSE_MEAN.to_string(),
+            operator: operator.to_string(),
+            target: 7.0,
+            period: PERIOD_DAILY.to_string(),
+            meal_type: None,
+            label: None,
+            start_date: "2026-01-05".to_string(),
+            end_date: Some("2026-01-31".to_string()),
+            is_active: true,
+        }
+    }
+
+    #[test]
+    fn weekly_periods_start_on_monday() {
+        assert_eq!(period_bounds(PERIOD_WEEKLY, d("2026-01-07")), (d("2026-01-05"), d("2026-01-12")));
+        assert_eq!(period_bounds(PERIOD_WEEKLY, d("2026-01-05")), (d("2026-01-05"), d("2026-01-12")));
+        assert_eq!(period_bounds(PERIOD_WEEKLY, d("2026-01-11")), (d("2026-01-05"), d("2026-01-12")));
+        assert_eq!(period_bounds(PERIOD_DAILY, d("2026-01-07")), (d("2026-01-07"), d("2026-01-08")));
+    }
+
+    #[test]
+    fn streaks_count_consecutive_periods() {
+        assert_eq!(streaks(&[], false), (0, 0));
+        assert_eq!(streaks(&[true, true, false, true], false), (1, 2));
+        assert_eq!(streaks(&[true, true, true, false], false), (0, 3));
+    }
+
+    #[test]
+    fn unfinished_period_does_not_break_streak() {
+        assert_eq!(streaks(&[true, true, false], true), (2, 2));
+        assert_eq!(streaks(&[true, true, true], true), (3, 3));
+        // 已结束的周期未达成时连续中断
+        assert_eq!(streaks(&[true, false, false], true), (0, 1));
+    }
+
+    #[test]
+    fn operators_compare_against_target() {
+        assert!(goal(OP_LT).is_met(6.9) && !goal(OP_LT).is_met(7.0));
+        assert!(goal(OP_LTE).is_met(7.0) && !goal(OP_LTE).is_met(7.1));
+        assert!(goal(OP_GT).is_met(7.1) && !goal(OP_GT).is_met(7.0));
+        assert!(goal(OP_GTE).is_met(7.0) && !goal(OP_GTE).is_met(6.9));
+    }
+
+    #[test]
+    fn applies_only_between_start_and_end() {
+        let mut goal = goal(OP_LT);
+        assert!(!goal.applies_on(d("2026-01-04")));
+        assert!(goal.applies_on(d("2026-01-05")));
+        assert!(goal.applies_on(d("2026-01-31")));
+        assert!(!goal.applies_on(d("2026-02-01")));
+        goal.is_active = false;
+        assert!(!goal.applies_on(d("2026-01-10")));
+    }
+}
@@ -0,0 +1,563 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::body::latest_weight;
+use crate::chat::load_default_provider;
+use crate::database::{open_conn, ApiResponse, FoodEntry, Medication, User};
+use crate::datetime::parse_timestamp;
+use crate::enums::{diabetes_type_label, measurement_time_label, treatment_plan_label, GENDER_FEMALE, GENDER_MALE};
+use crate::exercise::ExerciseEntry;
+use crate::gestational::{week_label, Pregnancy};
+use crate::llm::LlmProviderConfig;
+use crate::meal_pairing::load_readings;
+use crate::sick_days::{load_sick_periods, SickPeriods};
+use crate::targets::load_targets;
+
+pub const CATEGORY_PROFILE: &str = "profile";
+pub const CATEGORY_GLUCOSE: &str = "glucose";
+pub const CATEGORY_MEDICATIONS: &str = "medications";
+pub const CATEGORY_NUTRITION: &str = "nutrition";
+pub const CATEGORY_BODY: &str = "body";
+pub const CATEGORY_ACTIVITY: &str = "activity";
+
+const MMOL_TO_MG_DL: f64 = 18.0;
+
+const MIN_LOOKBACK_DAYS: i32 = 1;
+const MAX_LOOKBACK_DAYS: i32 = 90;
+const MIN_TOKEN_BUDGET: i32 = 100;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HealthContextSettings {
+    pub user_id: String,
+    pub enabled: bool,
+    // 以下开关控制发送给远程模型的数据类别
+    pub share_profile: bool,
+    pub share_glucose: bool,
+    pub share_medications: bool,
+    pub share_nutrition: bool,
+    #[serde(default)]
+    pub share_body: bool,
+    #[serde(default)]
+    pub share_activity: bool,
+    // 本机模型（localhost）是否不受上述开关限制
+    pub local_share_all: bool,
+    pub lookback_days: i32,
+    pub token_budget: i32,
+}
+
+impl HealthContextSettings {
+    pub fn defaults(user_id: &str) -> HealthContextSettings {
+        HealthContextSettings {
+            user_id: user_id.to_string(),
+            enabled: true,
+            share_profile: true,
+            share_glucose: true,
+            share_medications: true,
+            share_nutrition: true,
+            share_body: true,
+            share_activity: true,
+            local_share_all: true,
+            lookback_days: 7,
+            token_budget: 800,
+        }
+    }
+
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<HealthContextSettings> {
+        Ok(HealthContextSettings {
+            user_id: row.get(0)?,
+            enabled: row.get::<_, i32>(1)? == 1,
+            share_profile: row.get::<_, i32>(2)? == 1,
+            share_glucose: row.get::<_, i32>(3)? == 1,
+            share_medications: row.get::<_, i32>(4)? == 1,
+            share_nutrition: row.get::<_, i32>(5)? == 1,
+            local_share_all: row.get::<_, i32>(6)? == 1,
+            lookback_days: row.get(7)?,
+            token_budget: row.get(8)?,
+            share_body: row.get::<_, i32>(9)? == 1,
+            share_activity: row.get::<_, i32>(10)? == 1,
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !(MIN_LOOKBACK_DAYS..=MAX_LOOKBACK_DAYS).contains(&self.lookback_days) {
+            return Err(format!(
+                "lookback_days must be between {} and {}",
+                MIN_LOOKBACK_DAYS, MAX_LOOKBACK_DAYS
+            ));
+        }
+        if self.token_budget < MIN_TOKEN_BUDGET {
+            return Err(format!("token_budget must be at least {}", MIN_TOKEN_BUDGET));
+        }
+        Ok(())
+    }
+
+    /// 本次请求允许发送的数据类别，按重要程度排序
+    pub fn allowed_categories(&self, local_model: bool) -> Vec<&'static str> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        let unrestricted = local_model && self.local_share_all;
+        [
+            (CATEGORY_PROFILE, self.share_profile),
+            (CATEGORY_GLUCOSE, self.share_glucose),
+            (CATEGORY_MEDICATIONS, self.share_medications),
+            (CATEGORY_NUTRITION, self.share_nutrition),
+            (CATEGORY_BODY, self.share_body),
+            (CATEGORY_ACTIVITY, self.share_activity),
+        ]
+        .into_iter()
+        .filter(|(_, shared)| unrestricted || *shared)
+        .map(|(category, _)| category)
+        .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HealthContextPreview {
+    pub local_model: bool,
+    pub categories: Vec<String>,
+    pub text: Option<String>,
+    pub estimated_tokens: i32,
+}
+
+pub(crate) fn load_settings(conn: &Connection, user_id: &str) -> Result<HealthContextSettings, String> {
+    let settings = conn
+        .query_row(
+            "SELECT * FROM HealthContextSettings WHERE user_id = ?1",
+            params![user_id],
+            HealthContextSettings::from_row,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    Ok(settings.unwrap_or_else(|| HealthContextSettings::defaults(user_id)))
+}
+
+/// 模型服务地址指向本机时数据不会离开设备
+pub(crate) fn is_local_provider(config: &LlmProviderConfig) -> bool {
+    reqwest::Url::parse(&config.base_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .map(|host| matches!(host.as_str(), "localhost" | "127.0.0.1" | "[::1]" | "::1"))
+        .unwrap_or(false)
+}
+
+/// 粗略估算 token 数：中日韩字符约 1 token/字，其余约 4 字符/token
+pub(crate) fn estimate_tokens(text: &str) -> i32 {
+    let (cjk, other) = text.chars().fold((0i32, 0i32), |(cjk, other), c| {
+        if c as u32 >= 0x2E80 {
+            (cjk + 1, other)
+        } else {
+            (cjk, other + 1)
+        }
+    });
+    cjk + (other + 3) / 4
+}
+
+pub(crate) fn whole_years(from: NaiveDate, to: NaiveDate) -> i32 {
+    let mut years = to.year() - from.year();
+    if (to.month(), to.day()) < (from.month(), from.day()) {
+        years -= 1;
+    }
+    years.max(0)
+}
+
+// 只发送与病情相关的概况，不包含姓名、邮箱、电话、生日等身份信息
+fn profile_section(user: &User, now: DateTime<Utc>) -> Vec<String> {
+    let today = now.with_timezone(&Local).date_naive();
+
+    let mut summary = vec![diabetes_type_label(user.diabetes_type).to_string()];
+    summary.push(treatment_plan_label(user.treatment_plan).to_string());
+    if let Some(diagnosed) = user.diagnosis_date.as_deref().and_then(parse_timestamp) {
+        summary.push(format!("确诊 {} 年", whole_years(diagnosed.date_naive(), today)));
+    }
+    if let Some(birthday) = user.birthday.as_deref().and_then(parse_timestamp) {
+        summary.push(format!("{} 岁", whole_years(birthday.date_naive(), today)));
+    }
+    match user.gender {
+        GENDER_MALE => summary.push("男".to_string()),
+        GENDER_FEMALE => summary.push("女".to_string()),
+        _ => {}
+    }
+    if user.height > 0.0 {
+        summary.push(format!("身高 {:.0} cm", user.height));
+    }
+    if let Some(pregnancy) = Pregnancy::from_user(user).filter(|p| p.contains(today)) {
+        let (week, day) = pregnancy.week_of(today);
+        summary.push(week_label(week, day));
+    }
+
+    let mut lines = vec![format!("【基本信息】{}", summary.join("，"))];
+
+    let mut targets = Vec::new();
+    if let Some(a1c) = user.target_hb_a1c {
+        targets.push(format!("HbA1c < {:.1}%", a1c));
+    }
+    if let Some(calories) = user.target_calories {
+        targets.push(format!("每日热量 {:.0} kcal", calories));
+    }
+    if let Some(carbs) = user.target_carbohydrates {
+        targets.push(format!("每日碳水 {:.0} g", carbs));
+    }
+    if let Some(weight) = user.target_weight {
+        targets.push(format!("体重 {:.1} kg", weight));
+    }
+    if !targets.is_empty() {
+        lines.push(format!("目标：{}", targets.join("，")));
+    }
+    lines
+}
+
+fn sick_day_note(sick: &SickPeriods, now: DateTime<Utc>) -> Option<String> {
+    sick.episode_start(now).map(|started| {
+        format!(
+            "用户自 {} 起处于病假日（生病期间），血糖波动可能较大，回答时请优先提醒补液、监测酮体和及时就医",
+            started.with_timezone(&Local).format("%m-%d %H:%M")
+        )
+    })
+}
+
+fn glucose_section(
+    conn: &Connection,
+    user_id: &str,
+    from: DateTime<Utc>,
+    now: DateTime<Utc>,
+    days: i32,
+) -> Result<Vec<String>, String> {
+    // 病假日期间的读数不计入统计，与周期总结一致
+    let sick = load_sick_periods(conn, user_id)?;
+    let (sick_readings, readings): (Vec<_>, Vec<_>) =
+        load_readings(conn, user_id, from, now)?.into_iter().partition(|(t, _)| sick.contains(*t));
+    if readings.is_empty() {
+        let status = if sick_readings.is_empty() { "没有记录" } else { "只有病假日期间的记录" };
+        let mut lines = vec![format!("【血糖（近 {} 天）】{}", days, status)];
+        lines.extend(sick_day_note(&sick, now));
+        return Ok(lines);
+    }
+
+    // 按读数当时适用的个人目标统计，标签取当前目标
+    let targets = load_targets(conn, user_id)?;
+    let current = targets.at(now);
+    let values: Vec<f64> = readings.iter().map(|(_, r)| r.value).collect();
+    let count = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / count;
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let in_range = readings.iter().filter(|(t, r)| targets.at(*t).in_tir(r.value)).count() as f64;
+    let lows = readings.iter().filter(|(t, r)| targets.at(*t).is_hypo(r.value)).count();
+
+    let mut lines = vec![format!(
+        "【血糖（近 {} 天）】共 {} 次，平均 {:.1} mmol/L，范围 {:.1}–{:.1}；{} {:.0}%，低血糖 {} 次",
+        days,
+        values.len(),
+        mean,
+        min,
+        max,
+        current.tir_label(),
+        in_range / count * 100.0,
+        lows
+    )];
+
+    let mut by_time: BTreeMap<i32, Vec<f64>> = BTreeMap::new();
+    for (_, reading) in &readings {
+        by_time.entry(reading.measurement_time).or_default().push(reading.value);
+    }
+    let per_time: Vec<String> = by_time
+        .iter()
+        .map(|(time, values)| {
+            let (low, high) = current.range_for(*time);
+            format!(
+                "{}平均 {:.1}（{} 次，目标 {:.1}–{:.1}）",
+                measurement_time_label(*time),
+                values.iter().sum::<f64>() / values.len() as f64,
+                values.len(),
+                low,
+                high
+            )
+        })
+        .collect();
+    lines.push(per_time.join("；"));
+    if !sick_readings.is_empty() {
+        lines.push(format!("另有 {} 次读数在病假日期间，未计入以上统计", sick_readings.len()));
+    }
+    lines.extend(sick_day_note(&sick, now));
+
+    // GMI 仅在读数较多时才有参考意义
+    if values.len() >= 14 {
+        let gmi = 3.31 + 0.02392 * mean * MMOL_TO_MG_DL;
+        lines.push(format!("估算 GMI {:.1}%", gmi));
+    }
+    Ok(lines)
+}
+
+fn medications_section(
+    conn: &Connection,
+    user_id: &str,
+    from: DateTime<Utc>,
+    now: DateTime<Utc>,
+    days: i32,
+) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM Medications
+            WHERE user_id = ?1 AND scheduled_time >= ?2 AND scheduled_time <= ?3"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let doses: Vec<Medication> = stmt
+        .query_map(
+            params![
+                user_id,
+                from.format("%Y-%m-%d").to_string(),
+                (now + Duration::days(1)).format("%Y-%m-%d").to_string()
+            ],
+            Medication::from_row,
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter(|m| matches!(parse_timestamp(&m.scheduled_time), Some(t) if t >= from && t <= now))
+        .collect();
+
+    if doses.is_empty() {
+        return Ok(vec![format!("【用药（近 {} 天）】没有记录", days)]);
+    }
+
+    // 同一药品按剂量分别统计服用次数
+    let mut adherence: BTreeMap<(String, String), (i32, i32)> = BTreeMap::new();
+    for dose in &doses {
+        let key = (dose.drug_name.clone(), format!("{} {}", dose.dose, dose.unit));
+        let entry = adherence.entry(key).or_insert((0, 0));
+        entry.1 += 1;
+        if dose.is_taken {
+            entry.0 += 1;
+        }
+    }
+
+    let mut lines = vec![format!("【用药（近 {} 天）】", days)];
+    lines.extend(
+        adherence
+            .into_iter()
+            .map(|((drug, dose), (taken, total))| format!("{} {}：已服 {}/{} 次", drug, dose, taken, total)),
+    );
+    Ok(lines)
+}
+
+fn nutrition_section(
+    conn: &Connection,
+    user_id: &str,
+    from: DateTime<Utc>,
+    now: DateTime<Utc>,
+    days: i32,
+) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM FoodEntries
+            WHERE user_id = ?1 AND meal_time >= ?2 AND meal_time <= ?3"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let entries: Vec<(DateTime<Utc>, FoodEntry)> = stmt
+        .query_map(
+            params![
+                user_id,
+                from.format("%Y-%m-%d").to_string(),
+                (now + Duration::days(1)).format("%Y-%m-%d").to_string()
+            ],
+            FoodEntry::from_row,
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|entry| Some((parse_timestamp(&entry.meal_time)?, entry)))
+        .filter(|(t, _)| *t >= from && *t <= now)
+        .collect();
+
+    if entries.is_empty() {
+        return Ok(vec![format!("【饮食（近 {} 天）】没有记录", days)]);
+    }
+
+    // 按记录了饮食的天数求日均，避免漏记的日子拉低平均值
+    let logged_days: HashSet<NaiveDate> =
+        entries.iter().map(|(t, _)| t.with_timezone(&Local).date_naive()).collect();
+    let day_count = logged_days.len() as f64;
+    let total = |f: fn(&FoodEntry) -> f64| entries.iter().map(|(_, e)| f(e)).sum::<f64>() / day_count;
+
+    Ok(vec![format!(
+        "【饮食（近 {} 天，记录 {} 天）】日均 {:.0} kcal，碳水 {:.0} g，蛋白质 {:.0} g，脂肪 {:.0} g",
+        days,
+        logged_days.len(),
+        total(|e| e.calories),
+        total(|e| e.carbohydrates),
+        total(|e| e.protein),
+        total(|e| e.fat)
+    )])
+}
+
+fn body_section(conn: &Connection, user_id: &str) -> Result<Vec<String>, String> {
+    Ok(match latest_weight(conn, user_id)? {
+        Some(weight) => vec![format!("【体重】最近一次 {:.1} kg", weight)],
+        None => Vec::new(),
+    })
+}
+
+fn activity_section(
+    conn: &Connection,
+    user_id: &str,
+    from: DateTime<Utc>,
+    now: DateTime<Utc>,
+    days: i32,
+) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM ExerciseEntries
+            WHERE user_id = ?1 AND start_time >= ?2 AND start_time <= ?3"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let entries: Vec<ExerciseEntry> = stmt
+        .query_map(
+            params![
+                user_id,
+                from.format("%Y-%m-%d").to_string(),
+                (now + Duration::days(1)).format("%Y-%m-%d").to_string()
+            ],
+            ExerciseEntry::from_row,
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter(|e| matches!(parse_timestamp(&e.start_time), Some(t) if t >= from && t <= now))
+        .collect();
+
+    if entries.is_empty() {
+        return Ok(vec![format!("【运动（近 {} 天）】没有记录", days)]);
+    }
+    let minutes: i32 = entries.iter().map(|e| e.duration_minutes).sum();
+    Ok(vec![format!("【运动（近 {} 天）】共 {} 次，累计 {} 分钟", days, entries.len(), minutes)])
+}
+
+/// 汇总用户的健康数据作为系统提示词的一部分；按类别重要程度依次加入，超出 token 预算的内容直接省略
+pub(crate) fn build_health_context(
+    conn: &Connection,
+    user_id: &str,
+    settings: &HealthContextSettings,
+    local_model: bool,
+    now: DateTime<Utc>,
+) -> Result<Option<String>, String> {
+    let categories = settings.allowed_categories(local_model);
+    if categories.is_empty() {
+        return Ok(None);
+    }
+
+    let user = conn
+        .query_row("SELECT * FROM Users WHERE id = ?1", params![user_id], User::from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("User not found: {}", user_id))?;
+
+    let days = settings.lookback_days.clamp(MIN_LOOKBACK_DAYS, MAX_LOOKBACK_DAYS);
+    let from = now - Duration::days(days as i64);
+
+    let header = "以下是用户最近的健康记录摘要，回答时请结合这些数据，数据不足时如实说明：".to_string();
+    let mut used = estimate_tokens(&header);
+    let mut lines = vec![header];
+
+    for category in categories {
+        let section = match category {
+            CATEGORY_PROFILE => profile_section(&user, now),
+            CATEGORY_GLUCOSE => glucose_section(conn, user_id, from, now, days)?,
+            CATEGORY_MEDICATIONS => medications_section(conn, user_id, from, now, days)?,
+            CATEGORY_NUTRITION => nutrition_section(conn, user_id, from, now, days)?,
+            CATEGORY_BODY => body_section(conn, user_id)?,
+            _ => activity_section(conn, user_id, from, now, days)?,
+        };
+        for line in section {
+            let cost = estimate_tokens(&line) + 1;
+            if used + cost > settings.token_budget {
+                break;
+            }
+            used += cost;
+            lines.push(line);
+        }
+    }
+
+    if lines.len() == 1 {
+        return Ok(None);
+    }
+    Ok(Some(lines.join("\n")))
+}
+
+// ============ Health Context Commands ============
+
+#[tauri::command]
+pub async fn health_context_settings_get(user_id: String) -> Result<ApiResponse<HealthContextSettings>, String> {
+    let conn = open_conn()?;
+    let settings = load_settings(&conn, &user_id)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(settings),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn health_context_settings_update(
+    settings: HealthContextSettings,
+) -> Result<ApiResponse<HealthContextSettings>, String> {
+    settings.validate()?;
+    let conn = open_conn()?;
+
+    conn.execute(
+        r#"INSERT OR REPLACE INTO HealthContextSettings (user_id, enabled, share_profile, share_glucose,
+            share_medications, share_nutrition, local_share_all, lookback_days, token_budget, share_body,
+            share_activity)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"#,
+        params![
+            settings.user_id,
+            if settings.enabled { 1i32 } else { 0i32 },
+            if settings.share_profile { 1i32 } else { 0i32 },
+            if settings.share_glucose { 1i32 } else { 0i32 },
+            if settings.share_medications { 1i32 } else { 0i32 },
+            if settings.share_nutrition { 1i32 } else { 0i32 },
+            if settings.local_share_all { 1i32 } else { 0i32 },
+            settings.lookback_days,
+            settings.token_budget,
+            if settings.share_body { 1i32 } else { 0i32 },
+            if settings.share_activity { 1i32 } else { 0i32 }
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(settings),
+        message: None,
+    })
+}
+
+/// 预览下一次对话会附带的健康数据，便于用户确认发送了哪些内容
+#[tauri::command]
+pub async fn health_context_preview(user_id: String) -> Result<ApiResponse<HealthContextPreview>, String> {
+    let conn = open_conn()?;
+    let settings = load_settings(&conn, &user_id)?;
+
+    // 尚未配置模型时按远程模型预览
+    let local_model = load_default_provider(&conn)
+        .map(|config| is_local_provider(&config))
+        .unwrap_or(false);
+    let categories = settings
+        .allowed_categories(local_model)
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    let text = build_health_context(&conn, &user_id, &settings, local_model, Utc::now())?;
+    let estimated_tokens = text.as_deref().map(estimate_tokens).unwrap_or(0);
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(HealthContextPreview {
+            local_model,
+            categories,
+            text,
+            estimated_tokens,
+        }),
+        message: None,
+    })
+}
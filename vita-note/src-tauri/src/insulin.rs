@@ -0,0 +1,329 @@
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::database::{open_conn, ApiResponse, Medication};
+use crate::datetime::{format_timestamp, parse_timestamp, parse_timestamp_arg};
+use crate::enums::{
+    INSULIN_INTERMEDIATE, INSULIN_LONG_ACTING, INSULIN_PREMIXED, INSULIN_RAPID_ACTING, INSULIN_SHORT_ACTING,
+};
+
+const MAX_TIMELINE_POINTS: i64 = 5000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum InsulinCurve {
+    Bilinear,
+    Exponential,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct InsulinActionProfile {
+    pub curve: InsulinCurve,
+    pub duration_minutes: f64,
+    pub peak_minutes: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InsulinActivityPoint {
+    pub time: String,
+    pub iob: f64,
+    pub activity: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InsulinDoseContribution {
+    pub medication_id: String,
+    pub drug_name: String,
+    pub insulin_type: i32,
+    pub dose: f64,
+    pub dosed_at: String,
+    pub profile: InsulinActionProfile,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InsulinActivityTimeline {
+    pub points: Vec<InsulinActivityPoint>,
+    pub doses: Vec<InsulinDoseContribution>,
+}
+
+// 各类胰岛素的默认作用曲线（起效峰值与作用时长取说明书常用值）
+pub fn default_profile(insulin_type: i32) -> InsulinActionProfile {
+    let (curve, duration_hours, peak_minutes) = match insulin_type {
+        INSULIN_RAPID_ACTING => (InsulinCurve::Exponential, 5.0, 75.0),
+        INSULIN_SHORT_ACTING => (InsulinCurve::Bilinear, 7.0, 150.0),
+        INSULIN_INTERMEDIATE => (InsulinCurve::Bilinear, 16.0, 360.0),
+        INSULIN_LONG_ACTING => (InsulinCurve::Bilinear, 24.0, 720.0),
+        INSULIN_PREMIXED => (InsulinCurve::Bilinear, 16.0, 180.0),
+        _ => (InsulinCurve::Exponential, 5.0, 75.0),
+    };
+
+    InsulinActionProfile {
+        curve,
+        duration_minutes: duration_hours * 60.0,
+        peak_minutes,
+    }
+}
+
+// insulin_duration 以小时记录，填写时覆盖默认作用时长，峰值时间按比例缩放
+pub fn profile_for(insulin_type: i32, insulin_duration: Option<i32>) -> InsulinActionProfile {
+    let mut profile = default_profile(insulin_type);
+
+    if let Some(hours) = insulin_duration.filter(|h| *h > 0) {
+        let duration_minutes = hours as f64 * 60.0;
+        profile.peak_minutes *= duration_minutes / profile.duration_minutes;
+        profile.duration_minutes = duration_minutes;
+    }
+
+    profile
+}
+
+impl InsulinActionProfile {
+    /// 注射后 `minutes` 分钟时剩余的胰岛素比例（0~1）
+    pub fn iob_fraction(&self, minutes: f64) -> f64 {
+        let td = self.duration_minutes;
+        if minutes <= 0.0 {
+            return 1.0;
+        }
+        if minutes >= td {
+            return 0.0;
+        }
+
+        match self.curve {
+            InsulinCurve::Bilinear => {
+                let tp = self.peak_minutes.clamp(1.0, td - 1.0);
+                let height = 2.0 / td;
+                let absorbed = if minutes <= tp {
+                    height * minutes * minutes / (2.0 * tp)
+                } else {
+                    1.0 - height * (td - minutes) * (td - minutes) / (2.0 * (td - tp))
+                };
+                (1.0 - absorbed).clamp(0.0, 1.0)
+            }
+            InsulinCurve::Exponential => {
+                let (tau, a, s) = self.exponential_params();
+                let t = minutes;
+                let iob = 1.0
+                    - s * (1.0 - a)
+                        * ((t * t / (tau * td * (1.0 - a)) - t / tau - 1.0) * (-t / tau).exp()
+                            + 1.0);
+                iob.clamp(0.0, 1.0)
+            }
+        }
+    }
+
+    /// 注射后 `minutes` 分钟时单位时间作用的胰岛素比例（每分钟）
+    pub fn activity_fraction(&self, minutes: f64) -> f64 {
+        let td = self.duration_minutes;
+        if minutes <= 0.0 || minutes >= td {
+            return 0.0;
+        }
+
+        match self.curve {
+            InsulinCurve::Bilinear => {
+                let tp = self.peak_minutes.clamp(1.0, td - 1.0);
+                let height = 2.0 / td;
+                if minutes <= tp {
+                    height * minutes / tp
+                } else {
+                    height * (td - minutes) / (td - tp)
+                }
+            }
+            InsulinCurve::Exponential => {
+                let (tau, _, s) = self.exponential_params();
+                let t = minutes;
+                (s / (tau * tau) * t * (1.0 - t / td) * (-t / tau).exp()).max(0.0)
+            }
+        }
+    }
+
+    // 指数模型要求峰值早于作用时长的一半
+    fn exponential_params(&self) -> (f64, f64, f64) {
+        let td = self.duration_minutes;
+        let tp = self.peak_minutes.clamp(1.0, td * 0.45);
+        let tau = tp * (1.0 - tp / td) / (1.0 - 2.0 * tp / td);
+        let a = 2.0 * tau / td;
+        let s = 1.0 / (1.0 - a + (1.0 + a) * (-td / tau).exp());
+        (tau, a, s)
+    }
+}
+
+fn load_insulin_doses(
+    conn: &Connection,
+    user_id: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<(Medication, DateTime<Utc>, InsulinActionProfile)>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM Medications
+            WHERE user_id = ?1 AND insulin_type IS NOT NULL AND is_taken = 1"#,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let doses = stmt
+        .query_map(params![user_id], Medication::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|med| {
+            let dosed_at = parse_timestamp(med.actual_time.as_deref().unwrap_or(&med.scheduled_time))?;
+            let profile = profile_for(med.insulin_type?, med.insulin_duration);
+            let active_until = dosed_at + Duration::minutes(profile.duration_minutes as i64);
+            if dosed_at > end || active_until < start {
+                return None;
+            }
+            Some((med, dosed_at, profile))
+        })
+        .collect();
+
+    Ok(doses)
+}
+
+pub(crate) fn build_timeline(
+    conn: &Connection,
+    user_id: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    step_minutes: i64,
+) -> Result<InsulinActivityTimeline, String> {
+    if step_minutes <= 0 {
+        return Err("step_minutes must be positive".to_string());
+    }
+    if end < start {
+        return Err("end must not be earlier than start".to_string());
+    }
+    if (end - start).num_minutes() / step_minutes > MAX_TIMELINE_POINTS {
+        return Err(format!("Timeline exceeds {} points, increase step_minutes", MAX_TIMELINE_POINTS));
+    }
+
+    let doses = load_insulin_doses(conn, user_id, start, end)?;
+
+    let mut points = Vec::new();
+    let mut time = start;
+    while time <= end {
+        let mut iob = 0.0;
+        let mut activity = 0.0;
+        for (med, dosed_at, profile) in &doses {
+            let minutes = (time - *dosed_at).num_seconds() as f64 / 60.0;
+            if minutes < 0.0 {
+                continue;
+            }
+            iob += med.dose * profile.iob_fraction(minutes);
+            // 以 U/h 返回，便于与血糖曲线叠加显示
+            activity += med.dose * profile.activity_fraction(minutes) * 60.0;
+        }
+        points.push(InsulinActivityPoint {
+            time: format_timestamp(&time),
+            iob,
+            activity,
+        });
+        time += Duration::minutes(step_minutes);
+    }
+
+    let doses = doses
+        .into_iter()
+        .map(|(med, dosed_at, profile)| InsulinDoseContribution {
+            medication_id: med.id,
+            drug_name: med.drug_name,
+            insulin_type: med.insulin_type.unwrap_or_default(),
+            dose: med.dose,
+            dosed_at: format_timestamp(&dosed_at),
+            profile,
+        })
+        .collect();
+
+    Ok(InsulinActivityTimeline { points, doses })
+}
+
+// ============ Insulin Activity Commands ============
+
+#[tauri::command]
+pub async fn insulin_activity_timeline(
+    user_id: String,
+    start: String,
+    end: String,
+    step_minutes: i64,
+) -> Result<ApiResponse<InsulinActivityTimeline>, String> {
+    let conn = open_conn()?;
+    let start = parse_timestamp_arg("start", &start)?;
+    let end = parse_timestamp_arg("end", &end)?;
+
+    let timeline = build_timeline(&conn, &user_id, start, end, step_minutes)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(timeline),
+        message: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 按分钟累加作用比例，整个作用时长内应接近 1
+    fn total_activity(profile: &InsulinActionProfile) -> f64 {
+        (0..profile.duration_minutes as i64).map(|m| profile.activity_fraction(m as f64 + 0.5)).sum()
+    }
+
+    #[test]
+    fn iob_starts_full_and_ends_empty() {
+        for insulin_type in INSULIN_RAPID_ACTING..=INSULIN_PREMIXED {
+            let profile = default_profile(insulin_type);
+            assert_eq!(profile.iob_fraction(-10.0), 1.0);
+            assert_eq!(profile.iob_fraction(0.0), 1.0);
+            assert_eq!(profile.iob_fraction(profile.duration_minutes), 0.0);
+            assert_eq!(profile.activity_fraction(profile.duration_minutes + 1.0), 0.0);
+        }
+    }
+
+    #[test]
+    fn iob_decreases_monotonically() {
+        for insulin_type in [INSULIN_RAPID_ACTING, INSULIN_LONG_ACTING] {
+            let profile = default_profile(insulin_type);
+            let mut previous = 1.0;
+            for minute in (0..profile.duration_minutes as i64).step_by(5) {
+                let iob = profile.iob_fraction(minute as f64);
+                assert!(iob <= previous + 1e-9, "type {} minute {}", insulin_type, minute);
+                previous = iob;
+            }
+        }
+    }
+
+    #[test]
+    fn activity_integrates_to_one() {
+        for insulin_type in INSULIN_RAPID_ACTING..=INSULIN_PREMIXED {
+            let total = total_activity(&default_profile(insulin_type));
+            assert!((total - 1.0).abs() < 0.01, "type {} total {}", insulin_type, total);
+        }
+    }
+
+    #[test]
+    fn bilinear_activity_peaks_at_peak_time() {
+        let profile = default_profile(INSULIN_SHORT_ACTING);
+        assert_eq!(profile.curve, InsulinCurve::Bilinear);
+        let peak = profile.activity_fraction(profile.peak_minutes);
+        assert!((peak - 2.0 / profile.duration_minutes).abs() < 1e-12);
+        assert!(profile.activity_fraction(profile.peak_minutes - 30.0) < peak);
+        assert!(profile.activity_fraction(profile.peak_minutes + 30.0) < peak);
+    }
+
+    #[test]
+    fn exponential_activity_peaks_near_peak_time() {
+        let profile = default_profile(INSULIN_RAPID_ACTING);
+        let peak_minute = (1..profile.duration_minutes as i64)
+            .max_by(|a, b| profile.activity_fraction(*a as f64).total_cmp(&profile.activity_fraction(*b as f64)))
+            .unwrap();
+        assert!((peak_minute as f64 - profile.peak_minutes).abs() <= 1.0);
+    }
+
+    #[test]
+    fn custom_duration_scales_peak() {
+        let profile = profile_for(INSULIN_SHORT_ACTING, Some(14));
+        assert_eq!(profile.duration_minutes, 14.0 * 60.0);
+        assert_eq!(profile.peak_minutes, 300.0);
+        // 未填写或无效的作用时长使用默认值
+        assert_eq!(profile_for(INSULIN_SHORT_ACTING, Some(0)).duration_minutes, 7.0 * 60.0);
+        assert_eq!(profile_for(INSULIN_SHORT_ACTING, None).peak_minutes, 150.0);
+    }
+}
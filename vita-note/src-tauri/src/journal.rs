@@ -0,0 +1,332 @@
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::database::{open_conn, ApiResponse, BloodGlucose};
+use crate::datetime::{parse_timestamp, parse_timestamp_arg};
+use crate::meal_pairing::load_readings;
+use crate::targets::load_targets;
+
+pub const SYMPTOM_SHAKY: &str = "shaky";
+pub const SYMPTOM_SWEATING: &str = "sweating";
+pub const SYMPTOM_PALPITATIONS: &str = "palpitations";
+pub const SYMPTOM_HUNGER: &str = "hunger";
+pub const SYMPTOM_DIZZINESS: &str = "dizziness";
+pub const SYMPTOM_CONFUSION: &str = "confusion";
+pub const SYMPTOM_IRRITABILITY: &str = "irritability";
+pub const SYMPTOM_BLURRED_VISION: &str = "blurred_vision";
+pub const SYMPTOM_HEADACHE: &str = "headache";
+pub const SYMPTOM_FATIGUE: &str = "fatigue";
+pub const SYMPTOM_THIRST: &str = "thirst";
+pub const SYMPTOM_FREQUENT_URINATION: &str = "frequent_urination";
+pub const SYMPTOM_NAUSEA: &str = "nausea";
+pub const SYMPTOM_VOMITING: &str = "vomiting";
+pub const SYMPTOM_ABDOMINAL_PAIN: &str = "abdominal_pain";
+pub const SYMPTOM_NUMBNESS: &str = "numbness";
+
+/// 症状词表：(代码, 名称, 是否为典型低血糖症状)
+pub const SYMPTOMS: [(&str, &str, bool); 16] = [
+    (SYMPTOM_SHAKY, "手抖", true),
+    (SYMPTOM_SWEATING, "出冷汗", true),
+    (SYMPTOM_PALPITATIONS, "心慌", true),
+    (SYMPTOM_HUNGER, "饥饿感", true),
+    (SYMPTOM_DIZZINESS, "头晕", true),
+    (SYMPTOM_CONFUSION, "注意力不集中/意识模糊", true),
+    (SYMPTOM_IRRITABILITY, "烦躁", true),
+    (SYMPTOM_BLURRED_VISION, "视物模糊", false),
+    (SYMPTOM_HEADACHE, "头痛", false),
+    (SYMPTOM_FATIGUE, "乏力", false),
+    (SYMPTOM_THIRST, "口渴", false),
+    (SYMPTOM_FREQUENT_URINATION, "尿频", false),
+    (SYMPTOM_NAUSEA, "恶心", false),
+    (SYMPTOM_VOMITING, "呕吐", false),
+    (SYMPTOM_ABDOMINAL_PAIN, "腹痛", false),
+    (SYMPTOM_NUMBNESS, "手脚麻木", false),
+];
+
+const DEFAULT_WINDOW_MINUTES: i64 = 60;
+const MAX_WINDOW_MINUTES: i64 = 240;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JournalEntry {
+    pub id: String,
+    pub user_id: String,
+    pub created_at: String,
+    pub recorded_at: String,
+    /// SYMPTOMS 中的代码
+    pub symptoms: Vec<String>,
+    /// 1（很差）到 5（很好）
+    pub mood: Option<i32>,
+    /// 1（很低）到 5（很高）
+    pub stress: Option<i32>,
+    pub sleep_minutes: Option<i32>,
+    /// 1（很差）到 5（很好）
+    pub sleep_quality: Option<i32>,
+    pub is_ill: bool,
+    pub notes: Option<String>,
+}
+
+impl JournalEntry {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<JournalEntry> {
+        Ok(JournalEntry {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            created_at: row.get(2)?,
+            recorded_at: row.get(3)?,
+            symptoms: row
+                .get::<_, Option<String>>(4)?
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            mood: row.get(5)?,
+            stress: row.get(6)?,
+            sleep_minutes: row.get(7)?,
+            sleep_quality: row.get(8)?,
+            is_ill: row.get::<_, i32>(9)? == 1,
+            notes: row.get(10)?,
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        parse_timestamp_arg("recorded_at", &self.recorded_at)?;
+        if let Some(unknown) = self.symptoms.iter().find(|s| !SYMPTOMS.iter().any(|(code, ..)| code == s)) {
+            return Err(format!("Unknown symptom: {}", unknown));
+        }
+        for (name, score) in [("mood", self.mood), ("stress", self.stress), ("sleep_quality", self.sleep_quality)] {
+            if matches!(score, Some(s) if !(1..=5).contains(&s)) {
+                return Err(format!("{} must be between 1 and 5", name));
+            }
+        }
+        if matches!(self.sleep_minutes, Some(m) if !(0..=24 * 60).contains(&m)) {
+            return Err("Invalid sleep_minutes".to_string());
+        }
+        Ok(())
+    }
+
+    fn symptoms_json(&self) -> Result<Option<String>, String> {
+        if self.symptoms.is_empty() {
+            return Ok(None);
+        }
+        serde_json::to_string(&self.symptoms).map(Some).map_err(|e| e.to_string())
+    }
+
+    pub fn has_hypo_symptoms(&self) -> bool {
+        self.symptoms
+            .iter()
+            .any(|s| SYMPTOMS.iter().any(|(code, _, hypo)| *hypo && code == s))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SymptomInfo {
+    pub code: String,
+    pub label: String,
+    pub hypo_related: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JournalWithGlucose {
+    pub entry: JournalEntry,
+    /// 记录时间前后窗口内的血糖，按时间正序
+    pub readings: Vec<BloodGlucose>,
+    pub nearest: Option<BloodGlucose>,
+    /// 最近一次读数相对记录时间的分钟数，负数表示在记录之前
+    pub nearest_minutes: Option<i64>,
+    pub lowest: Option<f64>,
+    pub hypo_symptoms: bool,
+    /// 有低血糖症状且窗口内有低于当时目标下限（通常为 3.9）的读数
+    pub confirmed_hypo: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JournalCorrelation {
+    pub window_minutes: i64,
+    pub entries: Vec<JournalWithGlucose>,
+    pub hypo_symptom_entries: usize,
+    pub confirmed_hypo_entries: usize,
+    /// 有低血糖症状但窗口内没有测血糖
+    pub unchecked_hypo_entries: usize,
+}
+
+fn load_entries(conn: &Connection, user_id: &str, start_date: &str, end_date: &str) -> Result<Vec<JournalEntry>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM JournalEntries
+            WHERE user_id = ?1 AND recorded_at >= ?2 AND recorded_at < ?3
+            ORDER BY recorded_at DESC"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let entries = stmt
+        .query_map(params![user_id, start_date, end_date], JournalEntry::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(entries)
+}
+
+/// 把每条日记与前后 window_minutes 内的血糖读数放在一起，便于核对低血糖症状
+pub(crate) fn correlate_with_glucose(
+    conn: &Connection,
+    user_id: &str,
+    entries: Vec<JournalEntry>,
+    window_minutes: i64,
+) -> Result<JournalCorrelation, String> {
+    let window = Duration::minutes(window_minutes);
+    let times: Vec<DateTime<Utc>> = entries.iter().filter_map(|e| parse_timestamp(&e.recorded_at)).collect();
+    let readings = match (times.iter().min(), times.iter().max()) {
+        (Some(first), Some(last)) => load_readings(conn, user_id, *first - window, *last + window)?,
+        _ => Vec::new(),
+    };
+    let targets = load_targets(conn, user_id)?;
+
+    let mut results = Vec::new();
+    for entry in entries {
+        let Some(at) = parse_timestamp(&entry.recorded_at) else { continue };
+        let nearby: Vec<(i64, &BloodGlucose)> = readings
+            .iter()
+            .map(|(t, r)| ((*t - at).num_minutes(), r))
+            .filter(|(minutes, _)| minutes.abs() <= window_minutes)
+            .collect();
+        let nearest = nearby.iter().min_by_key(|(minutes, _)| minutes.abs());
+        let lowest = nearby.iter().map(|(_, r)| r.value).min_by(|a, b| a.total_cmp(b));
+        let hypo_below = targets.at(at).tir_low;
+        let hypo_symptoms = entry.has_hypo_symptoms();
+
+        results.push(JournalWithGlucose {
+            readings: nearby.iter().map(|(_, r)| (*r).clone()).collect(),
+            nearest: nearest.map(|(_, r)| (*r).clone()),
+            nearest_minutes: nearest.map(|(minutes, _)| *minutes),
+            lowest,
+            hypo_symptoms,
+            confirmed_hypo: hypo_symptoms && matches!(lowest, Some(v) if v < hypo_below),
+            entry,
+        });
+    }
+
+    Ok(JournalCorrelation {
+        window_minutes,
+        hypo_symptom_entries: results.iter().filter(|r| r.hypo_symptoms).count(),
+        confirmed_hypo_entries: results.iter().filter(|r| r.confirmed_hypo).count(),
+        unchecked_hypo_entries: results.iter().filter(|r| r.hypo_symptoms && r.readings.is_empty()).count(),
+        entries: results,
+    })
+}
+
+// ============ Journal Commands ============
+
+#[tauri::command]
+pub async fn journal_symptoms_get() -> Result<ApiResponse<Vec<SymptomInfo>>, String> {
+    let symptoms = SYMPTOMS
+        .iter()
+        .map(|(code, label, hypo)| SymptomInfo {
+            code: code.to_string(),
+            label: label.to_string(),
+            hypo_related: *hypo,
+        })
+        .collect();
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(symptoms),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn journal_entry_create(entry: JournalEntry) -> Result<ApiResponse<JournalEntry>, String> {
+    entry.validate()?;
+    let conn = open_conn()?;
+
+    conn.execute(
+        r#"INSERT INTO JournalEntries (id, user_id, created_at, recorded_at, symptoms, mood, stress,
+            sleep_minutes, sleep_quality, is_ill, notes)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"#,
+        params![
+            entry.id, entry.user_id, entry.created_at, entry.recorded_at, entry.symptoms_json()?, entry.mood,
+            entry.stress, entry.sleep_minutes, entry.sleep_quality, if entry.is_ill { 1i32 } else { 0i32 },
+            entry.notes
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(entry),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn journal_entries_get(
+    user_id: String,
+    start_date: String,
+    end_date: String,
+) -> Result<ApiResponse<Vec<JournalEntry>>, String> {
+    let conn = open_conn()?;
+    let items = load_entries(&conn, &user_id, &start_date, &end_date)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(items),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn journal_entry_update(entry: JournalEntry) -> Result<ApiResponse<JournalEntry>, String> {
+    entry.validate()?;
+    let conn = open_conn()?;
+
+    conn.execute(
+        r#"UPDATE JournalEntries SET
+            recorded_at = ?2, symptoms = ?3, mood = ?4, stress = ?5, sleep_minutes = ?6,
+            sleep_quality = ?7, is_ill = ?8, notes = ?9
+        WHERE id = ?1"#,
+        params![
+            entry.id, entry.recorded_at, entry.symptoms_json()?, entry.mood, entry.stress, entry.sleep_minutes,
+            entry.sleep_quality, if entry.is_ill { 1i32 } else { 0i32 }, entry.notes
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(entry),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn journal_entry_delete(id: String) -> Result<ApiResponse<String>, String> {
+    let conn = open_conn()?;
+
+    conn.execute("DELETE FROM JournalEntries WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some("Deleted".to_string()),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn journal_with_glucose(
+    user_id: String,
+    start_date: String,
+    end_date: String,
+    window_minutes: Option<i64>,
+) -> Result<ApiResponse<JournalCorrelation>, String> {
+    let window_minutes = window_minutes.unwrap_or(DEFAULT_WINDOW_MINUTES);
+    if !(1..=MAX_WINDOW_MINUTES).contains(&window_minutes) {
+        return Err(format!("window_minutes must be between 1 and {}", MAX_WINDOW_MINUTES));
+    }
+    let conn = open_conn()?;
+    let entries = load_entries(&conn, &user_id, &start_date, &end_date)?;
+    let correlation = correlate_with_glucose(&conn, &user_id, entries, window_minutes)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(correlation),
+        message: None,
+    })
+}
@@ -0,0 +1,388 @@
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use uuid::Uuid;
+
+use crate::database::{open_conn, try_get_app_handle, ApiResponse};
+use crate::datetime::{format_timestamp, parse_timestamp, parse_timestamp_arg};
+use crate::meal_pairing::load_readings;
+use crate::reminders::notify;
+
+pub const SAMPLE_BLOOD: &str = "blood";
+pub const SAMPLE_URINE: &str = "urine";
+
+// 尿酮试纸：阴性、微量(±)、少量(+)、中量(++)、大量(+++)
+pub const URINE_NEGATIVE: i32 = 0;
+pub const URINE_TRACE: i32 = 1;
+pub const URINE_SMALL: i32 = 2;
+pub const URINE_MODERATE: i32 = 3;
+pub const URINE_LARGE: i32 = 4;
+
+pub const KETONE_NORMAL: &str = "normal";
+pub const KETONE_ELEVATED: &str = "elevated";
+pub const KETONE_HIGH: &str = "high";
+pub const KETONE_DKA_RISK: &str = "dka_risk";
+
+pub const SEVERITY_WARNING: &str = "warning";
+pub const SEVERITY_URGENT: &str = "urgent";
+
+pub const DKA_WARNING_EVENT: &str = "dka-warning";
+
+// 血 β-羟丁酸分级，单位 mmol/L
+const BLOOD_ELEVATED_FROM: f64 = 0.6;
+const BLOOD_HIGH_FROM: f64 = 1.5;
+const BLOOD_DKA_RISK_FROM: f64 = 3.0;
+// 血糖 > 13.9 mmol/L 时应查酮体
+const HIGH_GLUCOSE: f64 = 13.9;
+// 血糖与酮体测量时间相差在该范围内视为同时发生
+const COINCIDENCE_HOURS: i64 = 2;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KetoneReading {
+    pub id: String,
+    pub user_id: String,
+    pub created_at: String,
+    pub measured_at: String,
+    pub sample_type: String,
+    /// 血酮 β-羟丁酸，mmol/L
+    pub value: Option<f64>,
+    /// 尿酮试纸等级 URINE_*
+    pub urine_level: Option<i32>,
+    pub notes: Option<String>,
+}
+
+impl KetoneReading {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<KetoneReading> {
+        Ok(KetoneReading {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            created_at: row.get(2)?,
+            measured_at: row.get(3)?,
+            sample_type: row.get(4)?,
+            value: row.get(5)?,
+            urine_level: row.get(6)?,
+            notes: row.get(7)?,
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        parse_timestamp_arg("measured_at", &self.measured_at)?;
+        match self.sample_type.as_str() {
+            SAMPLE_BLOOD if matches!(self.value, Some(v) if (0.0..=10.0).contains(&v)) => Ok(()),
+            SAMPLE_BLOOD => Err("Blood ketone value must be between 0 and 10 mmol/L".to_string()),
+            SAMPLE_URINE if matches!(self.urine_level, Some(l) if (URINE_NEGATIVE..=URINE_LARGE).contains(&l)) => Ok(()),
+            SAMPLE_URINE => Err("Invalid urine_level".to_string()),
+            other => Err(format!("Unknown sample_type: {}", other)),
+        }
+    }
+
+    pub fn level(&self) -> &'static str {
+        match (self.sample_type.as_str(), self.value, self.urine_level) {
+            (SAMPLE_BLOOD, Some(v), _) if v >= BLOOD_DKA_RISK_FROM => KETONE_DKA_RISK,
+            (SAMPLE_BLOOD, Some(v), _) if v >= BLOOD_HIGH_FROM => KETONE_HIGH,
+            (SAMPLE_BLOOD, Some(v), _) if v >= BLOOD_ELEVATED_FROM => KETONE_ELEVATED,
+            (SAMPLE_URINE, _, Some(URINE_LARGE)) => KETONE_DKA_RISK,
+            (SAMPLE_URINE, _, Some(URINE_MODERATE)) => KETONE_HIGH,
+            (SAMPLE_URINE, _, Some(URINE_TRACE)) | (SAMPLE_URINE, _, Some(URINE_SMALL)) => KETONE_ELEVATED,
+            _ => KETONE_NORMAL,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match (self.sample_type.as_str(), self.value, self.urine_level) {
+            (SAMPLE_BLOOD, Some(v), _) => format!("血酮 {:.1} mmol/L", v),
+            (_, _, Some(level)) => format!("尿酮 {}", urine_level_label(level)),
+            _ => "酮体".to_string(),
+        }
+    }
+}
+
+// 等级的严重程度，修改记录后据此判断是否需要再次提醒
+fn level_rank(level: &str) -> i32 {
+    match level {
+        KETONE_ELEVATED => 1,
+        KETONE_HIGH => 2,
+        KETONE_DKA_RISK => 3,
+        _ => 0,
+    }
+}
+
+pub fn urine_level_label(level: i32) -> &'static str {
+    match level {
+        URINE_NEGATIVE => "阴性(-)",
+        URINE_TRACE => "微量(±)",
+        URINE_SMALL => "少量(+)",
+        URINE_MODERATE => "中量(++)",
+        URINE_LARGE => "大量(+++)",
+        _ => "未知",
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DkaWarning {
+    pub id: String,
+    pub user_id: String,
+    pub created_at: String,
+    pub ketone_id: String,
+    pub glucose_id: Option<String>,
+    pub glucose_value: Option<f64>,
+    pub ketone_level: String,
+    pub severity: String,
+    pub message: String,
+}
+
+impl DkaWarning {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<DkaWarning> {
+        Ok(DkaWarning {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            created_at: row.get(2)?,
+            ketone_id: row.get(3)?,
+            glucose_id: row.get(4)?,
+            glucose_value: row.get(5)?,
+            ketone_level: row.get(6)?,
+            severity: row.get(7)?,
+            message: row.get(8)?,
+        })
+    }
+}
+
+pub(crate) fn load_ketones(
+    conn: &Connection,
+    user_id: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<(DateTime<Utc>, KetoneReading)>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM KetoneReadings
+            WHERE user_id = ?1 AND measured_at >= ?2 AND measured_at <= ?3
+            ORDER BY measured_at ASC"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let readings = stmt
+        .query_map(
+            params![
+                user_id,
+                from.format("%Y-%m-%d").to_string(),
+                (to + Duration::days(1)).format("%Y-%m-%d").to_string()
+            ],
+            KetoneReading::from_row,
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|k| Some((parse_timestamp(&k.measured_at)?, k)))
+        .filter(|(t, _)| *t >= from && *t <= to)
+        .collect();
+    Ok(readings)
+}
+
+/// 酮体升高且前后 2 小时内有高血糖时生成提醒；血酮 ≥ 3.0 或尿酮 +++ 时即使血糖不高也提醒
+/// （使用 SGLT2 抑制剂时可能出现血糖正常的酮症酸中毒）。每条酮体记录同一等级只提醒一次：
+/// 修改后等级升高时替换原提醒并再次提醒，等级降低时只更新提醒内容，不再需要提醒时撤销原提醒。
+/// 返回新生成的提醒
+pub(crate) fn check_dka_risk(conn: &Connection, user_id: &str, at: DateTime<Utc>) -> Result<Vec<DkaWarning>, String> {
+    let window = Duration::hours(COINCIDENCE_HOURS);
+    let ketones = load_ketones(conn, user_id, at - window, at + window)?;
+    let glucose = load_readings(conn, user_id, at - window * 2, at + window * 2)?;
+
+    let mut warnings = Vec::new();
+    for (measured_at, ketone) in &ketones {
+        let warned_level: Option<String> = conn
+            .query_row(
+                "SELECT ketone_level FROM DkaWarnings WHERE ketone_id = ?1",
+                params![ketone.id],
+                |row| row.get(0),
+            )
+            .ok();
+        let level = ketone.level();
+        let highest = glucose
+            .iter()
+            .filter(|(t, _)| (*t - *measured_at).num_minutes().abs() <= window.num_minutes())
+            .max_by(|(_, a), (_, b)| a.value.total_cmp(&b.value))
+            .map(|(_, g)| g);
+        let high_glucose = highest.filter(|g| g.value > HIGH_GLUCOSE);
+        if level == KETONE_NORMAL || (high_glucose.is_none() && level != KETONE_DKA_RISK) {
+            if warned_level.is_some() {
+                conn.execute("DELETE FROM DkaWarnings WHERE ketone_id = ?1", params![ketone.id])
+                    .map_err(|e| e.to_string())?;
+            }
+            continue;
+        }
+        if warned_level.as_deref() == Some(level) {
+            continue;
+        }
+
+        let severity = if level == KETONE_ELEVATED { SEVERITY_WARNING } else { SEVERITY_URGENT };
+        let glucose_text = match high_glucose {
+            Some(g) => format!("血糖 {:.1} mmol/L，", g.value),
+            None => String::new(),
+        };
+        let advice = if severity == SEVERITY_URGENT {
+            "存在糖尿病酮症酸中毒风险，请按医嘱补充胰岛素并多饮水；如出现恶心、呕吐、腹痛、呼吸深快或意识改变，请立即就医。"
+        } else {
+            "酮体已升高，请多饮水、按医嘱处理高血糖，并在 2 小时内复测血糖和酮体。"
+        };
+        let warning = DkaWarning {
+            id: Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            created_at: format_timestamp(&Utc::now()),
+            ketone_id: ketone.id.clone(),
+            glucose_id: high_glucose.map(|g| g.id.clone()),
+            glucose_value: high_glucose.map(|g| g.value),
+            ketone_level: level.to_string(),
+            severity: severity.to_string(),
+            message: format!("{}{}。{}", glucose_text, ketone.describe(), advice),
+        };
+
+        conn.execute(
+            r#"INSERT OR REPLACE INTO DkaWarnings (id, user_id, created_at, ketone_id, glucose_id,
+                glucose_value, ketone_level, severity, message)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"#,
+            params![
+                warning.id, warning.user_id, warning.created_at, warning.ketone_id, warning.glucose_id,
+                warning.glucose_value, warning.ketone_level, warning.severity, warning.message
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        let downgraded = warned_level.is_some_and(|warned| level_rank(&warned) > level_rank(level));
+        if !downgraded {
+            warnings.push(warning);
+        }
+    }
+    Ok(warnings)
+}
+
+/// 新增血糖或酮体记录后检查，通过事件和系统通知提醒前端
+pub(crate) fn raise_dka_warnings(conn: &Connection, user_id: &str, time: &str) -> Result<Vec<DkaWarning>, String> {
+    let Some(at) = parse_timestamp(time) else {
+        return Ok(Vec::new());
+    };
+    let warnings = check_dka_risk(conn, user_id, at)?;
+    if let Some(app) = try_get_app_handle() {
+        for warning in &warnings {
+            let _ = app.emit(DKA_WARNING_EVENT, warning.clone());
+            notify(app, "酮症酸中毒风险提醒", Some(&warning.message));
+        }
+    }
+    Ok(warnings)
+}
+
+fn response_with_warnings(entry: KetoneReading, warnings: Vec<DkaWarning>) -> ApiResponse<KetoneReading> {
+    ApiResponse {
+        success: true,
+        data: Some(entry),
+        message: warnings.into_iter().map(|w| w.message).reduce(|a, b| format!("{}\n{}", a, b)),
+    }
+}
+
+// ============ Ketone Commands ============
+
+#[tauri::command]
+pub async fn ketone_create(entry: KetoneReading) -> Result<ApiResponse<KetoneReading>, String> {
+    entry.validate()?;
+    let conn = open_conn()?;
+
+    conn.execute(
+        r#"INSERT INTO KetoneReadings (id, user_id, created_at, measured_at, sample_type, value,
+            urine_level, notes)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"#,
+        params![
+            entry.id, entry.user_id, entry.created_at, entry.measured_at, entry.sample_type, entry.value,
+            entry.urine_level, entry.notes
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let warnings = raise_dka_warnings(&conn, &entry.user_id, &entry.measured_at).unwrap_or_else(|e| {
+        eprintln!("Failed to check ketone warnings: {}", e);
+        Vec::new()
+    });
+    Ok(response_with_warnings(entry, warnings))
+}
+
+#[tauri::command]
+pub async fn ketones_get(
+    user_id: String,
+    start_date: String,
+    end_date: String,
+) -> Result<ApiResponse<Vec<KetoneReading>>, String> {
+    let conn = open_conn()?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM KetoneReadings
+            WHERE user_id = ?1 AND measured_at >= ?2 AND measured_at < ?3
+            ORDER BY measured_at DESC"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let items: Vec<KetoneReading> = stmt
+        .query_map(params![user_id, start_date, end_date], KetoneReading::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(items),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn ketone_update(entry: KetoneReading) -> Result<ApiResponse<KetoneReading>, String> {
+    entry.validate()?;
+    let conn = open_conn()?;
+
+    conn.execute(
+        r#"UPDATE KetoneReadings SET
+            measured_at = ?2, sample_type = ?3, value = ?4, urine_level = ?5, notes = ?6
+        WHERE id = ?1"#,
+        params![entry.id, entry.measured_at, entry.sample_type, entry.value, entry.urine_level, entry.notes],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let warnings = raise_dka_warnings(&conn, &entry.user_id, &entry.measured_at).unwrap_or_else(|e| {
+        eprintln!("Failed to check ketone warnings: {}", e);
+        Vec::new()
+    });
+    Ok(response_with_warnings(entry, warnings))
+}
+
+#[tauri::command]
+pub async fn ketone_delete(id: String) -> Result<ApiResponse<String>, String> {
+    let conn = open_conn()?;
+
+    // 连接未开启外键约束，提醒记录需要手动删除
+    conn.execute("DELETE FROM DkaWarnings WHERE ketone_id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM KetoneReadings WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some("Deleted".to_string()),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn dka_warnings_get(user_id: String, limit: i64) -> Result<ApiResponse<Vec<DkaWarning>>, String> {
+    let conn = open_conn()?;
+
+    let mut stmt = conn
+        .prepare("SELECT * FROM DkaWarnings WHERE user_id = ?1 ORDER BY created_at DESC LIMIT ?2")
+        .map_err(|e| e.to_string())?;
+    let items: Vec<DkaWarning> = stmt
+        .query_map(params![user_id, limit], DkaWarning::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(items),
+        message: None,
+    })
+}
@@ -0,0 +1,508 @@
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::database::{open_conn, ApiResponse};
+use crate::datetime::format_timestamp;
+
+// 分块时单块的最大字符数，按段落合并，超长段落再硬切
+const MAX_CHUNK_CHARS: usize = 500;
+// 每次提问附带的段落数，以及附带时每段截取的字符数
+const TOP_PASSAGES: usize = 3;
+const PASSAGE_CHARS: usize = 400;
+// 与最相关段落相比得分过低的段落只是碰巧命中个别字词，不附带
+const MIN_RELATIVE_SCORE: f64 = 0.4;
+const MAX_QUERY_TERMS: usize = 48;
+const MAX_SEARCH_LIMIT: i64 = 20;
+
+// trigram 分词下几乎每个问题都会命中的问句用语，不参与检索
+const STOP_TERMS: [&str; 12] = [
+    "什么是", "是什么", "怎么办", "为什么", "怎么样", "怎么做", "可以吗", "能不能", "应该怎", "该怎么",
+    "the", "and",
+];
+
+const KNOWLEDGE_PROMPT: &str = "以下是知识库中与问题相关的资料。回答时优先依据这些资料，\
+并在引用的句子后用 [编号] 标注出处；资料没有涉及的内容请说明属于一般性建议。";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KnowledgeDocument {
+    pub id: String,
+    pub title: String,
+    pub source_path: String,
+    pub imported_at: String,
+    pub updated_at: String,
+    pub chunk_count: i64,
+}
+
+impl KnowledgeDocument {
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<KnowledgeDocument> {
+        Ok(KnowledgeDocument {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            source_path: row.get(2)?,
+            imported_at: row.get(3)?,
+            updated_at: row.get(4)?,
+            chunk_count: row.get(5)?,
+        })
+    }
+}
+
+/// 检索到的知识库段落
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KnowledgePassage {
+    pub document_id: String,
+    pub document_title: String,
+    pub heading: Option<String>,
+    pub body: String,
+    pub score: f64,
+}
+
+/// 回答中 [index] 标注对应的出处
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct KnowledgeCitation {
+    pub index: usize,
+    pub document_id: String,
+    pub document_title: String,
+    pub heading: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KnowledgeIndexStats {
+    pub documents: i64,
+    pub chunks: i64,
+    // 源文件已不存在、沿用上次导入内容的文档
+    pub missing: Vec<String>,
+}
+
+struct Chunk {
+    heading: Option<String>,
+    body: String,
+}
+
+/// 文档标题取第一个一级标题，没有时用文件名
+fn document_title(content: &str, path: &Path) -> String {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+}
+
+fn heading_level(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let text = line[level..].strip_prefix(' ')?;
+    Some((level, text.trim()))
+}
+
+// 把一节的段落合并成不超过 MAX_CHUNK_CHARS 的块
+fn split_section(heading: Option<String>, paragraphs: &[String], chunks: &mut Vec<Chunk>) {
+    let mut body = String::new();
+    let flush = |body: &mut String, chunks: &mut Vec<Chunk>| {
+        if !body.is_empty() {
+            chunks.push(Chunk { heading: heading.clone(), body: std::mem::take(body) });
+        }
+    };
+
+    for paragraph in paragraphs {
+        let chars: Vec<char> = paragraph.chars().collect();
+        if chars.len() > MAX_CHUNK_CHARS {
+            flush(&mut body, chunks);
+            for piece in chars.chunks(MAX_CHUNK_CHARS) {
+                body = piece.iter().collect();
+                flush(&mut body, chunks);
+            }
+            continue;
+        }
+        if body.chars().count() + chars.len() + 2 > MAX_CHUNK_CHARS {
+            flush(&mut body, chunks);
+        }
+        if !body.is_empty() {
+            body.push_str("\n\n");
+        }
+        body.push_str(paragraph);
+    }
+    flush(&mut body, chunks);
+}
+
+/// 按标题把 Markdown 切成小节，小节标题为二级及以下标题的路径
+fn chunk_markdown(content: &str) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut path: Vec<(usize, String)> = Vec::new();
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut paragraph = String::new();
+    let mut in_fence = false;
+
+    let section_heading = |path: &[(usize, String)]| {
+        let parts: Vec<&str> = path.iter().filter(|(level, _)| *level > 1).map(|(_, text)| text.as_str()).collect();
+        (!parts.is_empty()).then(|| parts.join(" › "))
+    };
+
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        let heading = if in_fence { None } else { heading_level(line) };
+
+        if let Some((level, text)) = heading {
+            if !paragraph.is_empty() {
+                paragraphs.push(std::mem::take(&mut paragraph));
+            }
+            split_section(section_heading(&path), &paragraphs, &mut chunks);
+            paragraphs.clear();
+            path.retain(|(l, _)| *l < level);
+            path.push((level, text.to_string()));
+        } else if line.trim().is_empty() && !in_fence {
+            if !paragraph.is_empty() {
+                paragraphs.push(std::mem::take(&mut paragraph));
+            }
+        } else {
+            if !paragraph.is_empty() {
+                paragraph.push('\n');
+            }
+            paragraph.push_str(line.trim_end());
+        }
+    }
+    if !paragraph.is_empty() {
+        paragraphs.push(paragraph);
+    }
+    split_section(section_heading(&path), &paragraphs, &mut chunks);
+    chunks
+}
+
+fn index_document(conn: &Connection, document_id: &str, content: &str) -> Result<i64, String> {
+    conn.execute("DELETE FROM KnowledgeChunks WHERE document_id = ?1", params![document_id])
+        .map_err(|e| e.to_string())?;
+
+    let chunks = chunk_markdown(content);
+    for (index, chunk) in chunks.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO KnowledgeChunks (document_id, chunk_index, heading, body) VALUES (?1, ?2, ?3, ?4)",
+            params![document_id, index as i64, chunk.heading, chunk.body],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(chunks.len() as i64)
+}
+
+fn collect_markdown_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    if path.is_dir() {
+        let mut entries: Vec<PathBuf> = fs::read_dir(path)
+            .map_err(|e| e.to_string())?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .collect();
+        entries.sort();
+        for entry in entries {
+            collect_markdown_files(&entry, files)?;
+        }
+    } else if path
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown"))
+        .unwrap_or(false)
+    {
+        files.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+/// 导入或更新一篇文档；按源文件路径去重，内容未变化时不重建索引
+pub(crate) fn import_document(conn: &Connection, path: &Path) -> Result<String, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let source_path = path.to_string_lossy().to_string();
+    let title = document_title(&content, path);
+    let now = format_timestamp(&Utc::now());
+
+    let existing: Option<(String, String)> = conn
+        .query_row(
+            "SELECT id, content FROM KnowledgeDocuments WHERE source_path = ?1",
+            params![source_path],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    let id = match existing {
+        Some((id, old_content)) if old_content == content => return Ok(id),
+        Some((id, _)) => {
+            conn.execute(
+                "UPDATE KnowledgeDocuments SET title = ?1, content = ?2, updated_at = ?3 WHERE id = ?4",
+                params![title, content, now, id],
+            )
+            .map_err(|e| e.to_string())?;
+            id
+        }
+        None => {
+            let id = Uuid::new_v4().to_string();
+            conn.execute(
+                r#"INSERT INTO KnowledgeDocuments (id, title, source_path, content, imported_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?5)"#,
+                params![id, title, source_path, content, now],
+            )
+            .map_err(|e| e.to_string())?;
+            id
+        }
+    };
+    index_document(conn, &id, &content)?;
+    Ok(id)
+}
+
+fn is_cjk(c: char) -> bool {
+    !c.is_ascii() && c.is_alphanumeric()
+}
+
+/// 把问题拆成 FTS5 查询：英文按词，中文按连续三字切片，用 OR 连接交给 bm25 排序
+fn match_query(text: &str) -> Option<String> {
+    let mut terms: Vec<String> = Vec::new();
+    let mut push = |term: String| {
+        if !STOP_TERMS.contains(&term.as_str()) && !terms.contains(&term) {
+            terms.push(term);
+        }
+    };
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut pos = 0;
+    while pos < chars.len() {
+        let c = chars[pos];
+        if is_cjk(c) {
+            let run: Vec<char> = chars[pos..].iter().take_while(|c| is_cjk(**c)).copied().collect();
+            for window in run.windows(3) {
+                push(window.iter().collect());
+            }
+            pos += run.len();
+        } else if c.is_ascii_alphanumeric() {
+            let word: String = chars[pos..].iter().take_while(|c| c.is_ascii_alphanumeric()).collect();
+            pos += word.len();
+            if word.len() >= 3 {
+                push(word.to_ascii_lowercase());
+            }
+        } else {
+            pos += 1;
+        }
+    }
+
+    terms.truncate(MAX_QUERY_TERMS);
+    (!terms.is_empty()).then(|| {
+        terms
+            .iter()
+            .map(|term| format!("\"{}\"", term))
+            .collect::<Vec<_>>()
+            .join(" OR ")
+    })
+}
+
+/// 按 BM25 检索最相关的段落，分数越大越相关
+pub(crate) fn search_passages(conn: &Connection, text: &str, limit: usize) -> Result<Vec<KnowledgePassage>, String> {
+    let Some(query) = match_query(text) else {
+        return Ok(Vec::new());
+    };
+
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT c.document_id, d.title, c.heading, c.body, bm25(KnowledgeChunks)
+            FROM KnowledgeChunks c
+            JOIN KnowledgeDocuments d ON d.id = c.document_id
+            WHERE KnowledgeChunks MATCH ?1
+            ORDER BY bm25(KnowledgeChunks)
+            LIMIT ?2"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let passages = stmt
+        .query_map(params![query, limit as i64], |row| {
+            Ok(KnowledgePassage {
+                document_id: row.get(0)?,
+                document_title: row.get(1)?,
+                heading: row.get(2)?,
+                body: row.get(3)?,
+                score: -row.get::<_, f64>(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(passages)
+}
+
+/// 为一次提问检索资料，返回追加到系统提示词的文本和对应的出处
+pub(crate) fn build_knowledge_context(
+    conn: &Connection,
+    question: &str,
+) -> Result<Option<(String, Vec<KnowledgeCitation>)>, String> {
+    let passages = search_passages(conn, question, TOP_PASSAGES)?;
+    let Some(best) = passages.first().map(|p| p.score) else {
+        return Ok(None);
+    };
+
+    let mut text = KNOWLEDGE_PROMPT.to_string();
+    let mut citations = Vec::new();
+    for passage in passages.into_iter().filter(|p| p.score >= best * MIN_RELATIVE_SCORE) {
+        let index = citations.len() + 1;
+        let source = match &passage.heading {
+            Some(heading) => format!("《{}》› {}", passage.document_title, heading),
+            None => format!("《{}》", passage.document_title),
+        };
+        let body: String = passage.body.chars().take(PASSAGE_CHARS).collect();
+        text.push_str(&format!("\n[{}] {}\n{}", index, source, body));
+
+        citations.push(KnowledgeCitation {
+            index,
+            document_id: passage.document_id,
+            document_title: passage.document_title,
+            heading: passage.heading,
+        });
+    }
+    Ok(Some((text, citations)))
+}
+
+// ============ Knowledge Base Commands ============
+
+#[tauri::command]
+pub async fn knowledge_import(path: String) -> Result<ApiResponse<Vec<KnowledgeDocument>>, String> {
+    let mut files = Vec::new();
+    collect_markdown_files(Path::new(&path), &mut files)?;
+    if files.is_empty() {
+        return Err(format!("No Markdown files found: {}", path));
+    }
+
+    let mut conn = open_conn()?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut ids = Vec::new();
+    for file in &files {
+        ids.push(import_document(&tx, file)?);
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT id, title, source_path, imported_at, updated_at,
+                (SELECT COUNT(*) FROM KnowledgeChunks WHERE document_id = d.id)
+            FROM KnowledgeDocuments d WHERE id = ?1"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let documents: Vec<KnowledgeDocument> = ids
+        .iter()
+        .filter_map(|id| stmt.query_row(params![id], KnowledgeDocument::from_row).ok())
+        .collect();
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(documents),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn knowledge_reindex() -> Result<ApiResponse<KnowledgeIndexStats>, String> {
+    let mut conn = open_conn()?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let documents: Vec<(String, String, String)> = {
+        let mut stmt = tx
+            .prepare("SELECT id, source_path, content FROM KnowledgeDocuments")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        rows
+    };
+
+    // 源文件仍在时重新读取，不在时用上次导入的内容重建
+    tx.execute("DELETE FROM KnowledgeChunks", params![]).map_err(|e| e.to_string())?;
+    let mut stats = KnowledgeIndexStats {
+        documents: documents.len() as i64,
+        chunks: 0,
+        missing: Vec::new(),
+    };
+    for (id, source_path, content) in documents {
+        let path = Path::new(&source_path);
+        let content = match fs::read_to_string(path) {
+            Ok(latest) if latest != content => {
+                tx.execute(
+                    "UPDATE KnowledgeDocuments SET title = ?1, content = ?2, updated_at = ?3 WHERE id = ?4",
+                    params![document_title(&latest, path), latest, format_timestamp(&Utc::now()), id],
+                )
+                .map_err(|e| e.to_string())?;
+                latest
+            }
+            Ok(_) => content,
+            Err(_) => {
+                stats.missing.push(source_path.clone());
+                content
+            }
+        };
+        stats.chunks += index_document(&tx, &id, &content)?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(stats),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn knowledge_documents_get() -> Result<ApiResponse<Vec<KnowledgeDocument>>, String> {
+    let conn = open_conn()?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT id, title, source_path, imported_at, updated_at,
+                (SELECT COUNT(*) FROM KnowledgeChunks WHERE document_id = d.id)
+            FROM KnowledgeDocuments d
+            ORDER BY title ASC"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let documents: Vec<KnowledgeDocument> = stmt
+        .query_map(params![], KnowledgeDocument::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(documents),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn knowledge_document_delete(id: String) -> Result<ApiResponse<String>, String> {
+    let mut conn = open_conn()?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM KnowledgeChunks WHERE document_id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM KnowledgeDocuments WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some("Deleted".to_string()),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn knowledge_search(query: String, limit: i64) -> Result<ApiResponse<Vec<KnowledgePassage>>, String> {
+    let conn = open_conn()?;
+    let passages = search_passages(&conn, &query, limit.clamp(1, MAX_SEARCH_LIMIT) as usize)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(passages),
+        message: None,
+    })
+}
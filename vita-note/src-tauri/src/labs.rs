@@ -0,0 +1,509 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::database::{open_conn, ApiResponse};
+use crate::datetime::{parse_timestamp, parse_timestamp_arg};
+use crate::enums::GENDER_FEMALE;
+
+pub const FLAG_LOW: &str = "low";
+pub const FLAG_NORMAL: &str = "normal";
+pub const FLAG_HIGH: &str = "high";
+
+const DEFAULT_TREND_LIMIT: i64 = 20;
+
+/// 检验项目；value 以 unit 为标准单位保存，其他单位按 canonical = value × factor + offset 换算
+#[derive(Debug, Serialize, Clone)]
+pub struct LabAnalyte {
+    pub code: &'static str,
+    pub name: &'static str,
+    pub unit: &'static str,
+    pub low: Option<f64>,
+    pub high: Option<f64>,
+    /// 参考范围有性别差异时女性使用的范围
+    pub female_low: Option<f64>,
+    pub female_high: Option<f64>,
+    /// 序列化为可选单位列表，供前端选择
+    #[serde(rename = "other_units", serialize_with = "serialize_units")]
+    pub conversions: &'static [(&'static str, f64, f64)],
+}
+
+fn serialize_units<S: serde::Serializer>(
+    conversions: &&'static [(&'static str, f64, f64)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(conversions.iter().map(|(unit, ..)| unit))
+}
+
+const MG_DL_CHOLESTEROL: f64 = 0.02586;
+
+// 参考范围取国内检验科常用值，成人
+pub const ANALYTES: [LabAnalyte; 12] = [
+    LabAnalyte {
+        code: "HBA1C",
+        name: "糖化血红蛋白",
+        unit: "%",
+        low: Some(4.0),
+        high: Some(6.0),
+        female_low: None,
+        female_high: None,
+        // IFCC 单位换算为 NGSP 百分比
+        conversions: &[("mmol/mol", 0.09148, 2.152)],
+    },
+    LabAnalyte {
+        code: "FPG",
+        name: "空腹血浆葡萄糖",
+        unit: "mmol/L",
+        low: Some(3.9),
+        high: Some(6.1),
+        female_low: None,
+        female_high: None,
+        conversions: &[("mg/dL", 1.0 / 18.0, 0.0)],
+    },
+    LabAnalyte {
+        code: "TC",
+        name: "总胆固醇",
+        unit: "mmol/L",
+        low: None,
+        high: Some(5.2),
+        female_low: None,
+        female_high: None,
+        conversions: &[("mg/dL", MG_DL_CHOLESTEROL, 0.0)],
+    },
+    LabAnalyte {
+        code: "TG",
+        name: "甘油三酯",
+        unit: "mmol/L",
+        low: None,
+        high: Some(1.7),
+        female_low: None,
+        female_high: None,
+        conversions: &[("mg/dL", 0.01129, 0.0)],
+    },
+    LabAnalyte {
+        code: "LDL_C",
+        name: "低密度脂蛋白胆固醇",
+        unit: "mmol/L",
+        low: None,
+        high: Some(3.4),
+        female_low: None,
+        female_high: None,
+        conversions: &[("mg/dL", MG_DL_CHOLESTEROL, 0.0)],
+    },
+    LabAnalyte {
+        code: "HDL_C",
+        name: "高密度脂蛋白胆固醇",
+        unit: "mmol/L",
+        low: Some(1.0),
+        high: None,
+        female_low: Some(1.3),
+        female_high: None,
+        conversions: &[("mg/dL", MG_DL_CHOLESTEROL, 0.0)],
+    },
+    LabAnalyte {
+        code: "CREA",
+        name: "血肌酐",
+        unit: "μmol/L",
+        low: Some(57.0),
+        high: Some(111.0),
+        female_low: Some(41.0),
+        female_high: Some(81.0),
+        conversions: &[("umol/L", 1.0, 0.0), ("mg/dL", 88.42, 0.0)],
+    },
+    LabAnalyte {
+        code: "EGFR",
+        name: "估算肾小球滤过率",
+        unit: "mL/min/1.73m²",
+        low: Some(90.0),
+        high: None,
+        female_low: None,
+        female_high: None,
+        // 只接受按体表面积校正的结果；未校正的肌酐清除率 (mL/min) 不能直接换算
+        conversions: &[("mL/min/1.73m2", 1.0, 0.0)],
+    },
+    LabAnalyte {
+        code: "UACR",
+        name: "尿白蛋白/肌酐比值",
+        unit: "mg/g",
+        low: None,
+        high: Some(30.0),
+        female_low: None,
+        female_high: None,
+        conversions: &[("mg/mmol", 8.84, 0.0), ("μg/mg", 1.0, 0.0), ("ug/mg", 1.0, 0.0)],
+    },
+    LabAnalyte {
+        code: "ALT",
+        name: "丙氨酸氨基转移酶",
+        unit: "U/L",
+        low: Some(9.0),
+        high: Some(50.0),
+        female_low: Some(7.0),
+        female_high: Some(40.0),
+        conversions: &[("IU/L", 1.0, 0.0)],
+    },
+    LabAnalyte {
+        code: "AST",
+        name: "天门冬氨酸氨基转移酶",
+        unit: "U/L",
+        low: Some(15.0),
+        high: Some(40.0),
+        female_low: Some(13.0),
+        female_high: Some(35.0),
+        conversions: &[("IU/L", 1.0, 0.0)],
+    },
+    LabAnalyte {
+        code: "UA",
+        name: "血尿酸",
+        unit: "μmol/L",
+        low: Some(208.0),
+        high: Some(428.0),
+        female_low: Some(155.0),
+        female_high: Some(357.0),
+        conversions: &[("umol/L", 1.0, 0.0), ("mg/dL", 59.48, 0.0)],
+    },
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LabResult {
+    pub id: String,
+    pub user_id: String,
+    pub created_at: String,
+    pub analyte_code: String,
+    pub collected_at: String,
+    /// 创建时可使用任一支持的单位，保存后为标准单位
+    pub value: f64,
+    pub unit: String,
+    #[serde(default)]
+    pub original_value: Option<f64>,
+    #[serde(default)]
+    pub original_unit: Option<String>,
+    #[serde(default)]
+    pub flag: Option<String>,
+    pub lab_name: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl LabResult {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<LabResult> {
+        Ok(LabResult {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            created_at: row.get(2)?,
+            analyte_code: row.get(3)?,
+            collected_at: row.get(4)?,
+            value: row.get(5)?,
+            unit: row.get(6)?,
+            original_value: row.get(7)?,
+            original_unit: row.get(8)?,
+            flag: row.get(9)?,
+            lab_name: row.get(10)?,
+            notes: row.get(11)?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LabTrendPoint {
+    pub collected_at: String,
+    pub value: f64,
+    pub flag: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LabTrend {
+    pub analyte: LabAnalyte,
+    pub low: Option<f64>,
+    pub high: Option<f64>,
+    /// 按采样时间正序
+    pub points: Vec<LabTrendPoint>,
+    pub change_from_previous: Option<f64>,
+    pub percent_change_from_previous: Option<f64>,
+}
+
+pub fn find_analyte(code: &str) -> Option<&'static LabAnalyte> {
+    ANALYTES.iter().find(|a| a.code.eq_ignore_ascii_case(code))
+}
+
+/// 换算到项目的标准单位，单位比较忽略大小写
+pub fn to_canonical(analyte: &LabAnalyte, value: f64, unit: &str) -> Result<f64, String> {
+    if unit.eq_ignore_ascii_case(analyte.unit) {
+        return Ok(value);
+    }
+    analyte
+        .conversions
+        .iter()
+        .find(|(u, ..)| u.eq_ignore_ascii_case(unit))
+        .map(|(_, factor, offset)| value * factor + offset)
+        .ok_or_else(|| format!("Unsupported unit for {}: {}", analyte.code, unit))
+}
+
+pub fn reference_range(analyte: &LabAnalyte, gender: i32) -> (Option<f64>, Option<f64>) {
+    if gender == GENDER_FEMALE && (analyte.female_low.is_some() || analyte.female_high.is_some()) {
+        (analyte.female_low, analyte.female_high)
+    } else {
+        (analyte.low, analyte.high)
+    }
+}
+
+pub fn flag_for(value: f64, (low, high): (Option<f64>, Option<f64>)) -> &'static str {
+    if matches!(low, Some(low) if value < low) {
+        FLAG_LOW
+    } else if matches!(high, Some(high) if value > high) {
+        FLAG_HIGH
+    } else {
+        FLAG_NORMAL
+    }
+}
+
+/// 按当前参考范围重新标记；参考范围随性别或版本更新变化时，已保存的标记可能过期
+fn refresh_flag(mut entry: LabResult, gender: i32) -> LabResult {
+    if let Some(analyte) = find_analyte(&entry.analyte_code) {
+        entry.flag = Some(flag_for(entry.value, reference_range(analyte, gender)).to_string());
+    }
+    entry
+}
+
+fn user_gender(conn: &Connection, user_id: &str) -> Result<i32, String> {
+    conn.query_row("SELECT gender FROM Users WHERE id = ?1", params![user_id], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("User not found: {}", user_id))
+}
+
+/// 校验并换算为标准单位，保留原始数值与单位，按用户性别标记是否超出参考范围
+pub(crate) fn normalize_result(conn: &Connection, mut entry: LabResult) -> Result<LabResult, String> {
+    parse_timestamp_arg("collected_at", &entry.collected_at)?;
+    let analyte = find_analyte(&entry.analyte_code)
+        .ok_or_else(|| format!("Unknown analyte: {}", entry.analyte_code))?;
+    if !entry.value.is_finite() || entry.value < 0.0 {
+        return Err(format!("Invalid value: {}", entry.value));
+    }
+
+    let canonical = to_canonical(analyte, entry.value, &entry.unit)?;
+    if !entry.unit.eq_ignore_ascii_case(analyte.unit) {
+        entry.original_value = Some(entry.value);
+        entry.original_unit = Some(entry.unit.clone());
+    }
+    entry.analyte_code = analyte.code.to_string();
+    entry.value = canonical;
+    entry.unit = analyte.unit.to_string();
+
+    let range = reference_range(analyte, user_gender(conn, &entry.user_id)?);
+    entry.flag = Some(flag_for(canonical, range).to_string());
+    Ok(entry)
+}
+
+pub(crate) fn lab_trend(conn: &Connection, user_id: &str, analyte_code: &str, limit: i64) -> Result<LabTrend, String> {
+    let analyte = find_analyte(analyte_code).ok_or_else(|| format!("Unknown analyte: {}", analyte_code))?;
+    let gender = user_gender(conn, user_id)?;
+    let (low, high) = reference_range(analyte, gender);
+
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM LabResults
+            WHERE user_id = ?1 AND analyte_code = ?2
+            ORDER BY collected_at DESC
+            LIMIT ?3"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let mut points: Vec<LabTrendPoint> = stmt
+        .query_map(params![user_id, analyte.code, limit], LabResult::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter(|r| parse_timestamp(&r.collected_at).is_some())
+        .map(|r| refresh_flag(r, gender))
+        .map(|r| LabTrendPoint {
+            collected_at: r.collected_at,
+            value: r.value,
+            flag: r.flag,
+        })
+        .collect();
+    points.reverse();
+
+    let (change, percent) = match points.as_slice() {
+        [.., previous, latest] => {
+            let change = latest.value - previous.value;
+            let percent = if previous.value != 0.0 { Some(change / previous.value * 100.0) } else { None };
+            (Some(change), percent)
+        }
+        _ => (None, None),
+    };
+
+    Ok(LabTrend {
+        analyte: analyte.clone(),
+        low,
+        high,
+        points,
+        change_from_previous: change,
+        percent_change_from_previous: percent,
+    })
+}
+
+// ============ Lab Result Commands ============
+
+#[tauri::command]
+pub async fn lab_analytes_get() -> Result<ApiResponse<Vec<LabAnalyte>>, String> {
+    Ok(ApiResponse {
+        success: true,
+        data: Some(ANALYTES.to_vec()),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn lab_result_create(entry: LabResult) -> Result<ApiResponse<LabResult>, String> {
+    let conn = open_conn()?;
+    let entry = normalize_result(&conn, entry)?;
+
+    conn.execute(
+        r#"INSERT INTO LabResults (id, user_id, created_at, analyte_code, collected_at, value, unit,
+            original_value, original_unit, flag, lab_name, notes)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)"#,
+        params![
+            entry.id, entry.user_id, entry.created_at, entry.analyte_code, entry.collected_at, entry.value,
+            entry.unit, entry.original_value, entry.original_unit, entry.flag, entry.lab_name, entry.notes
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(entry),
+        message: None,
+    })
+}
+
+/// analyte_code 为空时返回全部项目
+#[tauri::command]
+pub async fn lab_results_get(
+    user_id: String,
+    start_date: String,
+    end_date: String,
+    analyte_code: Option<String>,
+) -> Result<ApiResponse<Vec<LabResult>>, String> {
+    let conn = open_conn()?;
+    let gender = user_gender(&conn, &user_id)?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM LabResults
+            WHERE user_id = ?1 AND collected_at >= ?2 AND collected_at < ?3
+                AND (?4 IS NULL OR analyte_code = ?4)
+            ORDER BY collected_at DESC, analyte_code ASC"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let code = analyte_code.map(|c| c.to_ascii_uppercase());
+    let items: Vec<LabResult> = stmt
+        .query_map(params![user_id, start_date, end_date, code], LabResult::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .map(|r| refresh_flag(r, gender))
+        .collect();
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(items),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn lab_result_delete(id: String) -> Result<ApiResponse<String>, String> {
+    let conn = open_conn()?;
+
+    conn.execute("DELETE FROM LabResults WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some("Deleted".to_string()),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn lab_result_trend(
+    user_id: String,
+    analyte_code: String,
+    limit: Option<i64>,
+) -> Result<ApiResponse<LabTrend>, String> {
+    let conn = open_conn()?;
+    let trend = lab_trend(&conn, &user_id, &analyte_code, limit.unwrap_or(DEFAULT_TREND_LIMIT))?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(trend),
+        message: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::GENDER_MALE;
+
+    fn analyte(code: &str) -> &'static LabAnalyte {
+        find_analyte(code).unwrap()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 0.01, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn converts_to_canonical_units() {
+        assert_close(to_canonical(analyte("HBA1C"), 53.0, "mmol/mol").unwrap(), 7.0);
+        assert_close(to_canonical(analyte("FPG"), 126.0, "mg/dL").unwrap(), 7.0);
+        assert_close(to_canonical(analyte("LDL_C"), 100.0, "mg/dL").unwrap(), 2.586);
+        assert_close(to_canonical(analyte("CREA"), 1.0, "mg/dL").unwrap(), 88.42);
+        assert_close(to_canonical(analyte("UACR"), 3.0, "mg/mmol").unwrap(), 26.52);
+        // 单位比较忽略大小写，标准单位原样返回
+        assert_close(to_canonical(analyte("FPG"), 126.0, "MG/DL").unwrap(), 7.0);
+        assert_eq!(to_canonical(analyte("FPG"), 6.0, "mmol/L").unwrap(), 6.0);
+    }
+
+    #[test]
+    fn rejects_unsupported_units() {
+        assert!(to_canonical(analyte("FPG"), 6.0, "g/L").is_err());
+        // 未按体表面积校正的肌酐清除率不能当作 eGFR
+        assert!(to_canonical(analyte("EGFR"), 85.0, "mL/min").is_err());
+        assert_eq!(to_canonical(analyte("EGFR"), 85.0, "mL/min/1.73m2").unwrap(), 85.0);
+    }
+
+    #[test]
+    fn female_reference_ranges() {
+        assert_eq!(reference_range(analyte("HDL_C"), GENDER_MALE), (Some(1.0), None));
+        assert_eq!(reference_range(analyte("HDL_C"), GENDER_FEMALE), (Some(1.3), None));
+        // 没有性别差异的项目女性也使用通用范围
+        assert_eq!(reference_range(analyte("FPG"), GENDER_FEMALE), (Some(3.9), Some(6.1)));
+    }
+
+    #[test]
+    fn flags_against_range() {
+        let range = reference_range(analyte("FPG"), GENDER_MALE);
+        assert_eq!(flag_for(3.8, range), FLAG_LOW);
+        assert_eq!(flag_for(3.9, range), FLAG_NORMAL);
+        assert_eq!(flag_for(6.1, range), FLAG_NORMAL);
+        assert_eq!(flag_for(6.2, range), FLAG_HIGH);
+        assert_eq!(flag_for(100.0, (None, None)), FLAG_NORMAL);
+    }
+
+    #[test]
+    fn refreshes_stale_flags() {
+        let entry = LabResult {
+            id: "l1".to_string(),
+            user_id: "u1".to_string(),
+            created_at: String::new(),
+            analyte_code: "HDL_C".to_string(),
+            collected_at: "2026-01-02T08:00:00.000Z".to_string(),
+            value: 1.2,
+            unit: "mmol/L".to_string(),
+            original_value: None,
+            original_unit: None,
+            flag: Some(FLAG_NORMAL.to_string()),
+            lab_name: None,
+            notes: None,
+        };
+        assert_eq!(refresh_flag(entry.clone(), GENDER_MALE).flag.as_deref(), Some(FLAG_NORMAL));
+        assert_eq!(refresh_flag(entry, GENDER_FEMALE).flag.as_deref(), Some(FLAG_LOW));
+    }
+}
@@ -1,5 +1,46 @@
+mod blood_pressure;
+mod body;
+mod chat;
+mod chat_tools;
+mod conversations;
 mod database;
+mod datetime;
+mod enums;
+mod exercise;
+mod food_response;
+mod gestational;
+mod glucose_checks;
+mod goals;
+mod health_context;
+mod html;
+mod insulin;
+mod journal;
+mod ketones;
+mod knowledge;
+mod labs;
+mod llm;
+mod logbook;
+mod meal_pairing;
+mod reminders;
+mod report;
+mod search;
+mod sick_days;
+mod summaries;
+mod targets;
 
+use blood_pressure::{
+    blood_pressure_create, blood_pressure_get, blood_pressure_update, blood_pressure_delete, blood_pressure_stats,
+};
+use body::{
+    body_measurement_create, body_measurements_get, body_measurement_update, body_measurement_delete,
+    body_weight_trend,
+};
+use chat::{chat_cancel, chat_send, llm_provider_save, llm_providers_get, llm_provider_delete};
+use chat_tools::chat_tool_confirm;
+use conversations::{
+    conversation_archive, conversation_create, conversation_delete, conversation_messages_get, conversation_pin,
+    conversation_rename, conversations_get,
+};
 use database::{
     db_init, 
     user_create, user_get_by_email, user_get_by_id, user_update,
@@ -9,14 +50,55 @@ use database::{
     chat_message_create, chat_message_get_history,
     set_app_handle,
 };
+use exercise::{
+    exercise_entry_create, exercise_entries_get, exercise_entry_update, exercise_entry_delete,
+    exercise_settings_get, exercise_settings_update, exercise_activity_summary,
+};
+use food_response::food_response_ranking;
+use gestational::{
+    gestational_status, gestational_weekly_report, gestational_weekly_report_export, user_pregnancy_update,
+};
+use glucose_checks::{glucose_check_preferences_get, glucose_check_preferences_update, glucose_checks_report};
+use goals::{goal_create, goals_get, goal_update, goal_delete, goals_progress};
+use health_context::{health_context_preview, health_context_settings_get, health_context_settings_update};
+use insulin::insulin_activity_timeline;
+use journal::{
+    journal_symptoms_get, journal_entry_create, journal_entries_get, journal_entry_update, journal_entry_delete,
+    journal_with_glucose,
+};
+use ketones::{ketone_create, ketones_get, ketone_update, ketone_delete, dka_warnings_get};
+use knowledge::{
+    knowledge_import, knowledge_reindex, knowledge_documents_get, knowledge_document_delete, knowledge_search,
+};
+use labs::{lab_analytes_get, lab_result_create, lab_results_get, lab_result_delete, lab_result_trend};
+use logbook::{glucose_logbook, glucose_logbook_export};
+use meal_pairing::{
+    meal_pairing_settings_get, meal_pairing_settings_update, meal_pairing_rebuild, meal_glucose_response,
+};
+use reminders::{
+    start_scheduler,
+    reminder_rule_create, reminder_rules_get, reminder_rule_update, reminder_rule_delete,
+    reminder_deliveries_get, reminder_acknowledge,
+};
+use report::report_generate_pdf;
+use search::search_all;
+use sick_days::{sick_day_start, sick_day_end, sick_day_status, sick_days_get, sick_day_delete};
+use summaries::{summaries_get, summary_generate};
+use targets::{
+    glucose_target_presets_get, glucose_targets_get, glucose_targets_set, glucose_targets_history,
+    glucose_targets_delete,
+};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
             // 设置全局 app handle，用于获取应用数据目录
             set_app_handle(app.handle().clone());
+            // 后台提醒调度器（用药、测血糖等）
+            start_scheduler(app.handle().clone());
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -25,7 +107,36 @@ pub fn run() {
             food_entry_create, food_entry_get_by_user, food_entry_delete,
             blood_glucose_create, blood_glucose_get_by_user, blood_glucose_delete,
             medication_create, medication_get_by_user, medication_mark_taken, medication_delete,
-            chat_message_create, chat_message_get_history
+            chat_message_create, chat_message_get_history,
+            insulin_activity_timeline,
+            reminder_rule_create, reminder_rules_get, reminder_rule_update, reminder_rule_delete,
+            reminder_deliveries_get, reminder_acknowledge,
+            glucose_check_preferences_get, glucose_check_preferences_update, glucose_checks_report,
+            meal_pairing_settings_get, meal_pairing_settings_update, meal_pairing_rebuild, meal_glucose_response,
+            food_response_ranking,
+            llm_provider_save, llm_providers_get, llm_provider_delete, chat_send, chat_cancel, chat_tool_confirm,
+            conversation_create, conversations_get, conversation_rename, conversation_archive,
+            conversation_pin, conversation_delete, conversation_messages_get,
+            health_context_settings_get, health_context_settings_update, health_context_preview,
+            search_all,
+            knowledge_import, knowledge_reindex, knowledge_documents_get, knowledge_document_delete, knowledge_search,
+            summary_generate, summaries_get,
+            report_generate_pdf,
+            glucose_logbook, glucose_logbook_export,
+            body_measurement_create, body_measurements_get, body_measurement_update, body_measurement_delete,
+            body_weight_trend,
+            exercise_entry_create, exercise_entries_get, exercise_entry_update, exercise_entry_delete,
+            exercise_settings_get, exercise_settings_update, exercise_activity_summary,
+            blood_pressure_create, blood_pressure_get, blood_pressure_update, blood_pressure_delete, blood_pressure_stats,
+            lab_analytes_get, lab_result_create, lab_results_get, lab_result_delete, lab_result_trend,
+            ketone_create, ketones_get, ketone_update, ketone_delete, dka_warnings_get,
+            journal_symptoms_get, journal_entry_create, journal_entries_get, journal_entry_update, journal_entry_delete,
+            journal_with_glucose,
+            goal_create, goals_get, goal_update, goal_delete, goals_progress,
+            glucose_target_presets_get, glucose_targets_get, glucose_targets_set, glucose_targets_history,
+            glucose_targets_delete,
+            gestational_status, gestational_weekly_report, gestational_weekly_report_export, user_pregnancy_update,
+            sick_day_start, sick_day_end, sick_day_status, sick_days_get, sick_day_delete
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
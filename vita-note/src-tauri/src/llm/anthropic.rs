@@ -0,0 +1,153 @@
+use reqwest::{Client, RequestBuilder};
+use serde_json::{json, Value};
+
+use super::{
+    endpoint, ChatCompletion, ChatProvider, ChatRequest, LlmMessage, LlmProviderConfig, StreamEvent, ROLE_TOOL,
+};
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Anthropic Messages API
+pub struct AnthropicProvider {
+    config: LlmProviderConfig,
+}
+
+impl AnthropicProvider {
+    pub fn new(config: LlmProviderConfig) -> AnthropicProvider {
+        AnthropicProvider { config }
+    }
+
+    fn body(&self, request: &ChatRequest, stream: bool) -> Value {
+        // system 提示词单独传递，messages 中只保留 user / assistant
+        let mut messages: Vec<Value> = Vec::new();
+        for message in request.messages.iter().filter(|m| m.role != "system") {
+            if message.role == ROLE_TOOL {
+                // 工具结果作为 user 消息的 tool_result 块，连续的结果合并到同一条消息
+                let block = json!({
+                    "type": "tool_result",
+                    "tool_use_id": message.tool_call_id,
+                    "content": message.content,
+                });
+                match messages.last_mut() {
+                    Some(last) if last["role"] == "user" && last["content"].is_array() => {
+                        if let Some(blocks) = last["content"].as_array_mut() {
+                            blocks.push(block);
+                        }
+                    }
+                    _ => messages.push(json!({ "role": "user", "content": [block] })),
+                }
+                continue;
+            }
+            messages.push(message_json(message));
+        }
+
+        let mut body = json!({
+            "model": self.config.model,
+            "max_tokens": self.config.max_tokens,
+            "messages": messages,
+            "stream": stream,
+        });
+        if let Some(system) = &request.system {
+            body["system"] = json!(system);
+        }
+        if let Some(temperature) = self.config.temperature {
+            body["temperature"] = json!(temperature);
+        }
+        if !request.tools.is_empty() {
+            let tools: Vec<Value> = request
+                .tools
+                .iter()
+                .map(|tool| {
+                    json!({
+                        "name": tool.name,
+                        "description": tool.description,
+                        "input_schema": tool.parameters,
+                    })
+                })
+                .collect();
+            body["tools"] = json!(tools);
+            if request.disable_tool_calls {
+                body["tool_choice"] = json!({ "type": "none" });
+            }
+        }
+        body
+    }
+}
+
+fn message_json(message: &LlmMessage) -> Value {
+    if message.tool_calls.is_empty() {
+        return json!({ "role": message.role, "content": message.content });
+    }
+
+    let mut blocks = Vec::new();
+    if !message.content.is_empty() {
+        blocks.push(json!({ "type": "text", "text": message.content }));
+    }
+    for call in &message.tool_calls {
+        blocks.push(json!({ "type": "tool_use", "id": call.id, "name": call.name, "input": call.arguments }));
+    }
+    json!({ "role": message.role, "content": blocks })
+}
+
+impl ChatProvider for AnthropicProvider {
+    fn build_request(&self, client: &Client, request: &ChatRequest, stream: bool) -> RequestBuilder {
+        let mut builder = client
+            .post(endpoint(&self.config.base_url, "v1/messages"))
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&self.body(request, stream));
+        if let Some(key) = &self.config.api_key {
+            builder = builder.header("x-api-key", key);
+        }
+        builder
+    }
+
+    fn parse_response(&self, body: &Value) -> Result<ChatCompletion, String> {
+        let blocks = body["content"].as_array().ok_or("Missing content in LLM response")?;
+        let content: String = blocks
+            .iter()
+            .filter(|block| block["type"] == "text")
+            .filter_map(|block| block["text"].as_str())
+            .collect();
+
+        Ok(ChatCompletion {
+            content,
+            model: body["model"].as_str().unwrap_or(&self.config.model).to_string(),
+        })
+    }
+
+    fn parse_stream_event(&self, data: &str) -> Result<Vec<StreamEvent>, String> {
+        let event: Value = serde_json::from_str(data).map_err(|e| e.to_string())?;
+
+        match event["type"].as_str().unwrap_or_default() {
+            "message_start" => Ok(event
+                .pointer("/message/model")
+                .and_then(Value::as_str)
+                .map(|model| vec![StreamEvent::Model(model.to_string())])
+                .unwrap_or_default()),
+            "content_block_start" if event.pointer("/content_block/type") == Some(&json!("tool_use")) => {
+                Ok(vec![StreamEvent::ToolCallStart {
+                    index: event["index"].as_u64().unwrap_or(0) as usize,
+                    id: event.pointer("/content_block/id").and_then(Value::as_str).unwrap_or_default().to_string(),
+                    name: event.pointer("/content_block/name").and_then(Value::as_str).unwrap_or_default().to_string(),
+                }])
+            }
+            "content_block_delta" => {
+                let index = event["index"].as_u64().unwrap_or(0) as usize;
+                match event.pointer("/delta/type").and_then(Value::as_str) {
+                    Some("input_json_delta") => Ok(vec![StreamEvent::ToolCallDelta {
+                        index,
+                        arguments: event.pointer("/delta/partial_json").and_then(Value::as_str).unwrap_or_default().to_string(),
+                    }]),
+                    _ => Ok(event
+                        .pointer("/delta/text")
+                        .and_then(Value::as_str)
+                        .map(|text| vec![StreamEvent::Delta(text.to_string())])
+                        .unwrap_or_default()),
+                }
+            }
+            "message_stop" => Ok(vec![StreamEvent::Done]),
+            "error" => Err(format!("LLM stream error: {}", event["error"])),
+            _ => Ok(Vec::new()),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use reqwest::{Client, RequestBuilder};
+use serde_json::{json, Value};
+
+use super::{ChatCompletion, ChatProvider, ChatRequest, LlmProviderConfig, StreamEvent};
+
+// 未配置 response_path 时从响应的 content 字段读取回复
+const DEFAULT_RESPONSE_PATH: &str = "/content";
+
+/// 通用 HTTP 接口：直接向 base_url 发送 JSON，按 response_path（JSON Pointer）读取回复；
+/// 流式模式下每条 SSE data 可以是同样结构的 JSON，也可以是纯文本片段。不支持工具调用
+pub struct GenericHttpProvider {
+    config: LlmProviderConfig,
+}
+
+impl GenericHttpProvider {
+    pub fn new(config: LlmProviderConfig) -> GenericHttpProvider {
+        GenericHttpProvider { config }
+    }
+
+    fn response_path(&self) -> &str {
+        self.config.response_path.as_deref().unwrap_or(DEFAULT_RESPONSE_PATH)
+    }
+}
+
+impl ChatProvider for GenericHttpProvider {
+    fn build_request(&self, client: &Client, request: &ChatRequest, stream: bool) -> RequestBuilder {
+        let body = json!({
+            "model": self.config.model,
+            "system": request.system,
+            "messages": request.messages,
+            "max_tokens": self.config.max_tokens,
+            "temperature": self.config.temperature,
+            "stream": stream,
+        });
+
+        let mut builder = client.post(&self.config.base_url).json(&body);
+        if let Some(key) = &self.config.api_key {
+            builder = builder.bearer_auth(key);
+        }
+        builder
+    }
+
+    fn parse_response(&self, body: &Value) -> Result<ChatCompletion, String> {
+        let path = self.response_path();
+        let content = body
+            .pointer(path)
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("Missing {} in LLM response", path))?;
+
+        Ok(ChatCompletion {
+            content: content.to_string(),
+            model: body["model"].as_str().unwrap_or(&self.config.model).to_string(),
+        })
+    }
+
+    fn parse_stream_event(&self, data: &str) -> Result<Vec<StreamEvent>, String> {
+        if data == "[DONE]" {
+            return Ok(vec![StreamEvent::Done]);
+        }
+
+        match serde_json::from_str::<Value>(data) {
+            Ok(chunk) => {
+                let mut events = Vec::new();
+                if let Some(model) = chunk["model"].as_str() {
+                    events.push(StreamEvent::Model(model.to_string()));
+                }
+                if let Some(delta) = chunk.pointer(self.response_path()).and_then(Value::as_str) {
+                    events.push(StreamEvent::Delta(delta.to_string()));
+                }
+                Ok(events)
+            }
+            Err(_) => Ok(vec![StreamEvent::Delta(data.to_string())]),
+        }
+    }
+}
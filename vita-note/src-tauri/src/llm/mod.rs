@@ -0,0 +1,410 @@
+mod anthropic;
+mod generic;
+mod openai;
+mod stream;
+
+pub use stream::stream_complete;
+pub(crate) use stream::wait_for_cancel;
+
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+pub const PROVIDER_OPENAI: &str = "openai";
+pub const PROVIDER_ANTHROPIC: &str = "anthropic";
+pub const PROVIDER_HTTP: &str = "http";
+
+const RETRY_BASE_DELAY_MS: u64 = 500;
+const MASK_MIN_KEY_LEN: usize = 8;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LlmProviderConfig {
+    pub id: String,
+    pub name: String,
+    pub kind: String,
+    pub base_url: String,
+    pub api_key: Option<String>,
+    pub model: String,
+    pub timeout_secs: i64,
+    pub max_retries: i64,
+    pub max_tokens: i64,
+    pub temperature: Option<f64>,
+    pub response_path: Option<String>,
+    pub is_default: bool,
+    pub created_at: String,
+}
+
+impl LlmProviderConfig {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<LlmProviderConfig> {
+        Ok(LlmProviderConfig {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            kind: row.get(2)?,
+            base_url: row.get(3)?,
+            api_key: row.get(4)?,
+            model: row.get(5)?,
+            timeout_secs: row.get(6)?,
+            max_retries: row.get(7)?,
+            max_tokens: row.get(8)?,
+            temperature: row.get(9)?,
+            response_path: row.get(10)?,
+            is_default: row.get::<_, i32>(11)? == 1,
+            created_at: row.get(12)?,
+        })
+    }
+
+    /// 返回给前端的配置不包含完整密钥
+    pub fn masked(mut self) -> LlmProviderConfig {
+        self.api_key = self.api_key.map(|key| mask_key(&key));
+        self
+    }
+}
+
+/// 最多显示末尾 4 位；过短的密钥全部遮盖，避免遮盖后仍能看出完整密钥
+pub(crate) fn mask_key(key: &str) -> String {
+    let count = key.chars().count();
+    let visible = if count > MASK_MIN_KEY_LEN { 4 } else { 0 };
+    let tail: String = key.chars().skip(count - visible).collect();
+    format!("****{}", tail)
+}
+
+pub(crate) fn is_masked_key(key: &str) -> bool {
+    key.starts_with("****")
+}
+
+pub const ROLE_TOOL: &str = "tool";
+
+/// 提供给模型调用的工具，parameters 为 JSON Schema
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ToolSpec {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: Value,
+}
+
+/// 对话消息；assistant 消息可以带工具调用，role 为 tool 的消息是对应调用的结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LlmMessage {
+    pub role: String,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl LlmMessage {
+    pub fn text(role: &str, content: String) -> LlmMessage {
+        LlmMessage {
+            role: role.to_string(),
+            content,
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChatRequest {
+    pub system: Option<String>,
+    pub messages: Vec<LlmMessage>,
+    pub tools: Vec<ToolSpec>,
+    /// 仍附带工具定义但要求模型直接回答；历史消息含工具调用时不能去掉工具定义
+    pub disable_tool_calls: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChatCompletion {
+    pub content: String,
+    pub model: String,
+}
+
+/// 流式响应中一条 SSE data 解析出的事件；工具调用的参数按 index 分段到达
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    Delta(String),
+    Model(String),
+    ToolCallStart { index: usize, id: String, name: String },
+    ToolCallDelta { index: usize, arguments: String },
+    Done,
+}
+
+/// 工具调用参数是模型生成的 JSON 字符串，为空时视为没有参数
+pub(crate) fn parse_tool_arguments(arguments: &str) -> Value {
+    if arguments.trim().is_empty() {
+        return Value::Object(Default::default());
+    }
+    serde_json::from_str(arguments).unwrap_or_else(|_| Value::String(arguments.to_string()))
+}
+
+/// 各家模型服务的协议差异只体现在请求构造与响应解析上，超时与重试由 `send_with_retry` 统一处理
+pub trait ChatProvider: Send + Sync {
+    fn build_request(&self, client: &Client, request: &ChatRequest, stream: bool) -> RequestBuilder;
+    fn parse_response(&self, body: &Value) -> Result<ChatCompletion, String>;
+    fn parse_stream_event(&self, data: &str) -> Result<Vec<StreamEvent>, String>;
+}
+
+pub fn provider_for(config: &LlmProviderConfig) -> Result<Box<dyn ChatProvider>, String> {
+    match config.kind.as_str() {
+        PROVIDER_OPENAI => Ok(Box::new(openai::OpenAiProvider::new(config.clone()))),
+        PROVIDER_ANTHROPIC => Ok(Box::new(anthropic::AnthropicProvider::new(config.clone()))),
+        PROVIDER_HTTP => Ok(Box::new(generic::GenericHttpProvider::new(config.clone()))),
+        other => Err(format!("Unknown LLM provider kind: {}", other)),
+    }
+}
+
+/// 通用 HTTP 接口没有统一的工具调用格式
+pub fn supports_tools(config: &LlmProviderConfig) -> bool {
+    config.kind != PROVIDER_HTTP
+}
+
+pub(crate) fn build_client(config: &LlmProviderConfig) -> Result<Client, String> {
+    Client::builder()
+        .timeout(Duration::from_secs(config.timeout_secs.max(1) as u64))
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+// 限流与服务端错误可以重试，其余 4xx 直接返回
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::REQUEST_TIMEOUT || status.is_server_error()
+}
+
+// 超时和连接失败可以重试；请求本身构造有误时重试也不会成功
+fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_connect()
+}
+
+pub(crate) async fn backoff(attempt: i64) {
+    let delay = RETRY_BASE_DELAY_MS * 2u64.pow(attempt.clamp(0, 6) as u32);
+    tokio::time::sleep(Duration::from_millis(delay)).await;
+}
+
+/// 发送一次请求，失败时按指数退避重试，返回成功的 HTTP 响应
+pub(crate) async fn send_with_retry<F>(config: &LlmProviderConfig, build: F) -> Result<reqwest::Response, String>
+where
+    F: Fn() -> RequestBuilder,
+{
+    let mut attempt = 0;
+    loop {
+        let retryable_failure = match build().send().await {
+            Ok(response) if response.status().is_success() => return Ok(response),
+            Ok(response) => {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                let message = format!("LLM request failed ({}): {}", status, body);
+                if !is_retryable_status(status) {
+                    return Err(message);
+                }
+                message
+            }
+            Err(e) => {
+                if !is_retryable_error(&e) {
+                    return Err(e.to_string());
+                }
+                e.to_string()
+            }
+        };
+
+        if attempt >= config.max_retries {
+            return Err(retryable_failure);
+        }
+        backoff(attempt).await;
+        attempt += 1;
+    }
+}
+
+pub async fn complete(config: &LlmProviderConfig, request: &ChatRequest) -> Result<ChatCompletion, String> {
+    let client = build_client(config)?;
+    let provider = provider_for(config)?;
+
+    let response = send_with_retry(config, || provider.build_request(&client, request, false)).await?;
+    let body: Value = response.json().await.map_err(|e| e.to_string())?;
+    provider.parse_response(&body)
+}
+
+pub(crate) fn endpoint(base_url: &str, path: &str) -> String {
+    format!("{}/{}", base_url.trim_end_matches('/'), path.trim_start_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+
+    enum Reply {
+        Json(&'static str, &'static str),
+        /// 收下请求后一直不响应，直到客户端超时断开
+        Hang,
+    }
+
+    /// 本地模拟服务：按顺序处理每个连接，返回地址和收到的请求行
+    fn mock_server(replies: Vec<Reply>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+        std::thread::spawn(move || {
+            for reply in replies {
+                let Ok((mut socket, _)) = listener.accept() else { return };
+                let request = read_request(&mut socket);
+                log.lock().unwrap().push(request.lines().next().unwrap_or_default().to_string());
+                match reply {
+                    Reply::Json(status, body) => {
+                        let response = format!(
+                            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            status,
+                            body.len(),
+                            body
+                        );
+                        let _ = socket.write_all(response.as_bytes());
+                    }
+                    // 挂起的连接交给单独线程，后续连接可以立即被接受，结果不依赖两边计时的先后
+                    Reply::Hang => {
+                        std::thread::spawn(move || {
+                            let _ = socket.read(&mut [0u8; 1]);
+                        });
+                    }
+                }
+            }
+        });
+        (base_url, received)
+    }
+
+    fn read_request(socket: &mut TcpStream) -> String {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = socket.read(&mut buf).unwrap_or(0);
+            if n == 0 {
+                break;
+            }
+            data.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&data);
+            if let Some(end) = text.find("\r\n\r\n") {
+                let length = text[..end]
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length").then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                if data.len() >= end + 4 + length {
+                    break;
+                }
+            }
+        }
+        String::from_utf8_lossy(&data).to_string()
+    }
+
+    fn config(kind: &str, base_url: String) -> LlmProviderConfig {
+        LlmProviderConfig {
+            id: "p1".to_string(),
+            name: "test".to_string(),
+            kind: kind.to_string(),
+            base_url,
+            api_key: Some("sk-test-key".to_string()),
+            model: "test-model".to_string(),
+            timeout_secs: 1,
+            max_retries: 2,
+            max_tokens: 64,
+            temperature: None,
+            response_path: None,
+            is_default: true,
+            created_at: "2026-01-01T00:00:00.000Z".to_string(),
+        }
+    }
+
+    fn request() -> ChatRequest {
+        ChatRequest {
+            system: Some("system".to_string()),
+            messages: vec![LlmMessage::text("user", "hello".to_string())],
+            tools: Vec::new(),
+            disable_tool_calls: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn parses_openai_response() {
+        let (base_url, received) = mock_server(vec![Reply::Json(
+            "200 OK",
+            r#"{"model":"gpt-test","choices":[{"message":{"role":"assistant","content":"hi"}}]}"#,
+        )]);
+        let completion = complete(&config(PROVIDER_OPENAI, base_url), &request()).await.unwrap();
+        assert_eq!(completion.content, "hi");
+        assert_eq!(completion.model, "gpt-test");
+        assert_eq!(received.lock().unwrap()[0], "POST /chat/completions HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn parses_anthropic_response() {
+        let (base_url, received) = mock_server(vec![Reply::Json(
+            "200 OK",
+            r#"{"model":"claude-test","content":[{"type":"text","text":"a"},{"type":"text","text":"b"}]}"#,
+        )]);
+        let completion = complete(&config(PROVIDER_ANTHROPIC, base_url), &request()).await.unwrap();
+        assert_eq!(completion.content, "ab");
+        assert_eq!(completion.model, "claude-test");
+        assert_eq!(received.lock().unwrap()[0], "POST /v1/messages HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn retries_rate_limit_and_server_errors() {
+        let (base_url, received) = mock_server(vec![
+            Reply::Json("429 Too Many Requests", "{}"),
+            Reply::Json("503 Service Unavailable", "{}"),
+            Reply::Json("200 OK", r#"{"choices":[{"message":{"content":"ok"}}]}"#),
+        ]);
+        let completion = complete(&config(PROVIDER_OPENAI, base_url), &request()).await.unwrap();
+        assert_eq!(completion.content, "ok");
+        assert_eq!(received.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_client_errors() {
+        let (base_url, received) = mock_server(vec![
+            Reply::Json("400 Bad Request", r#"{"error":"bad"}"#),
+            Reply::Json("200 OK", r#"{"choices":[{"message":{"content":"ok"}}]}"#),
+        ]);
+        let error = complete(&config(PROVIDER_OPENAI, base_url), &request()).await.unwrap_err();
+        assert!(error.contains("400"));
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn retries_after_timeout() {
+        let (base_url, received) = mock_server(vec![
+            Reply::Hang,
+            Reply::Json("200 OK", r#"{"choices":[{"message":{"content":"late"}}]}"#),
+        ]);
+        let completion = complete(&config(PROVIDER_OPENAI, base_url), &request()).await.unwrap();
+        assert_eq!(completion.content, "late");
+        assert_eq!(received.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_retries() {
+        let (base_url, _) = mock_server(vec![Reply::Hang]);
+        let mut config = config(PROVIDER_OPENAI, base_url);
+        config.max_retries = 0;
+        assert!(complete(&config, &request()).await.is_err());
+    }
+
+    #[test]
+    fn masks_keys() {
+        assert_eq!(mask_key("sk-abcdef123456"), "****3456");
+        assert_eq!(mask_key("abcd"), "****");
+        assert_eq!(mask_key("sk-abc12"), "****");
+        assert!(is_masked_key(&mask_key("x")));
+    }
+}
@@ -0,0 +1,145 @@
+use reqwest::{Client, RequestBuilder};
+use serde_json::{json, Value};
+
+use super::{
+    endpoint, ChatCompletion, ChatProvider, ChatRequest, LlmMessage, LlmProviderConfig, StreamEvent, ROLE_TOOL,
+};
+
+/// OpenAI 兼容接口（OpenAI、DeepSeek、通义千问兼容模式、本地 Ollama 等）
+pub struct OpenAiProvider {
+    config: LlmProviderConfig,
+}
+
+impl OpenAiProvider {
+    pub fn new(config: LlmProviderConfig) -> OpenAiProvider {
+        OpenAiProvider { config }
+    }
+
+    fn body(&self, request: &ChatRequest, stream: bool) -> Value {
+        let mut messages = Vec::new();
+        if let Some(system) = &request.system {
+            messages.push(json!({ "role": "system", "content": system }));
+        }
+        messages.extend(request.messages.iter().map(message_json));
+
+        let mut body = json!({
+            "model": self.config.model,
+            "messages": messages,
+            "max_tokens": self.config.max_tokens,
+            "stream": stream,
+        });
+        if let Some(temperature) = self.config.temperature {
+            body["temperature"] = json!(temperature);
+        }
+        if !request.tools.is_empty() {
+            let tools: Vec<Value> = request
+                .tools
+                .iter()
+                .map(|tool| {
+                    json!({
+                        "type": "function",
+                        "function": {
+                            "name": tool.name,
+                            "description": tool.description,
+                            "parameters": tool.parameters,
+                        },
+                    })
+                })
+                .collect();
+            body["tools"] = json!(tools);
+            if request.disable_tool_calls {
+                body["tool_choice"] = json!("none");
+            }
+        }
+        body
+    }
+}
+
+fn message_json(message: &LlmMessage) -> Value {
+    if message.role == ROLE_TOOL {
+        return json!({
+            "role": "tool",
+            "tool_call_id": message.tool_call_id,
+            "content": message.content,
+        });
+    }
+    if message.tool_calls.is_empty() {
+        return json!({ "role": message.role, "content": message.content });
+    }
+
+    // 参数以 JSON 字符串形式回传
+    let tool_calls: Vec<Value> = message
+        .tool_calls
+        .iter()
+        .map(|call| {
+            json!({
+                "id": call.id,
+                "type": "function",
+                "function": { "name": call.name, "arguments": call.arguments.to_string() },
+            })
+        })
+        .collect();
+    json!({ "role": message.role, "content": message.content, "tool_calls": tool_calls })
+}
+
+impl ChatProvider for OpenAiProvider {
+    fn build_request(&self, client: &Client, request: &ChatRequest, stream: bool) -> RequestBuilder {
+        let mut builder = client
+            .post(endpoint(&self.config.base_url, "chat/completions"))
+            .json(&self.body(request, stream));
+        if let Some(key) = &self.config.api_key {
+            builder = builder.bearer_auth(key);
+        }
+        builder
+    }
+
+    fn parse_response(&self, body: &Value) -> Result<ChatCompletion, String> {
+        let message = body.pointer("/choices/0/message").ok_or("Missing choices[0].message in LLM response")?;
+        // 只返回工具调用时 content 为 null
+        let content = message["content"].as_str().unwrap_or_default();
+
+        Ok(ChatCompletion {
+            content: content.to_string(),
+            model: body["model"].as_str().unwrap_or(&self.config.model).to_string(),
+        })
+    }
+
+    fn parse_stream_event(&self, data: &str) -> Result<Vec<StreamEvent>, String> {
+        if data == "[DONE]" {
+            return Ok(vec![StreamEvent::Done]);
+        }
+        let chunk: Value = serde_json::from_str(data).map_err(|e| e.to_string())?;
+        if let Some(error) = chunk.get("error") {
+            return Err(format!("LLM stream error: {}", error));
+        }
+
+        let mut events = Vec::new();
+        if let Some(model) = chunk["model"].as_str() {
+            events.push(StreamEvent::Model(model.to_string()));
+        }
+        if let Some(delta) = chunk.pointer("/choices/0/delta/content").and_then(Value::as_str) {
+            events.push(StreamEvent::Delta(delta.to_string()));
+        }
+        // 工具调用的首个分片带 id 和名称，之后的分片只带参数片段
+        let calls = chunk.pointer("/choices/0/delta/tool_calls").and_then(Value::as_array);
+        for call in calls.into_iter().flatten() {
+            let index = call["index"].as_u64().unwrap_or(0) as usize;
+            if let (Some(id), Some(name)) = (call["id"].as_str(), call.pointer("/function/name").and_then(Value::as_str)) {
+                events.push(StreamEvent::ToolCallStart {
+                    index,
+                    id: id.to_string(),
+                    name: name.to_string(),
+                });
+            }
+            if let Some(arguments) = call.pointer("/function/arguments").and_then(Value::as_str) {
+                if !arguments.is_empty() {
+                    events.push(StreamEvent::ToolCallDelta {
+                        index,
+                        arguments: arguments.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(events)
+    }
+}
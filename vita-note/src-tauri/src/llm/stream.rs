@@ -0,0 +1,161 @@
+use reqwest::Client;
+use std::time::Duration;
+use tokio::sync::watch;
+
+use super::{parse_tool_arguments, provider_for, send_with_retry, ChatRequest, LlmProviderConfig, StreamEvent, ToolCall};
+
+/// 流式请求的结果；被取消或连接中断时 content 为已经收到的部分
+#[derive(Debug, Clone)]
+pub struct StreamOutcome {
+    pub content: String,
+    pub model: String,
+    pub tool_calls: Vec<ToolCall>,
+    pub cancelled: bool,
+    pub error: Option<String>,
+}
+
+/// 按行切分响应字节并取出 SSE 的 data 字段；按字节缓冲，避免多字节字符被分块截断
+#[derive(Default)]
+struct SseDecoder {
+    buffer: Vec<u8>,
+}
+
+impl SseDecoder {
+    fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut payloads = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            payloads.extend(data_payload(&line));
+        }
+        payloads
+    }
+
+    fn finish(&mut self) -> Vec<String> {
+        let line = std::mem::take(&mut self.buffer);
+        data_payload(&line).into_iter().collect()
+    }
+}
+
+fn data_payload(line: &[u8]) -> Option<String> {
+    let line = String::from_utf8_lossy(line);
+    let payload = line.trim_end_matches(['\r', '\n']).strip_prefix("data:")?;
+    let payload = payload.strip_prefix(' ').unwrap_or(payload);
+    (!payload.is_empty()).then(|| payload.to_string())
+}
+
+// 流式响应可能持续很久，只限制连接与两次数据之间的间隔，不限制总时长
+fn build_stream_client(config: &LlmProviderConfig) -> Result<Client, String> {
+    let timeout = Duration::from_secs(config.timeout_secs.max(1) as u64);
+    Client::builder()
+        .connect_timeout(timeout)
+        .read_timeout(timeout)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// 等待取消信号；发送端被丢弃时视为不会再取消
+pub(crate) async fn wait_for_cancel(cancel: &mut watch::Receiver<bool>) {
+    if cancel.wait_for(|c| *c).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// 以流式方式请求模型，每收到一段文本调用一次 `on_delta`；
+/// 建立连接前的失败返回 Err，之后的中断记录在 `StreamOutcome::error` 中并保留已收到的内容
+pub async fn stream_complete<F>(
+    config: &LlmProviderConfig,
+    request: &ChatRequest,
+    mut cancel: watch::Receiver<bool>,
+    mut on_delta: F,
+) -> Result<StreamOutcome, String>
+where
+    F: FnMut(&str),
+{
+    let client = build_stream_client(config)?;
+    let provider = provider_for(config)?;
+
+    let mut outcome = StreamOutcome {
+        content: String::new(),
+        model: config.model.clone(),
+        tool_calls: Vec::new(),
+        cancelled: false,
+        error: None,
+    };
+    // 按 index 拼接工具调用：(index, id, name, 参数片段)
+    let mut pending_calls: Vec<(usize, String, String, String)> = Vec::new();
+
+    let mut response = tokio::select! {
+        result = send_with_retry(config, || provider.build_request(&client, request, true)) => result?,
+        _ = wait_for_cancel(&mut cancel) => {
+            outcome.cancelled = true;
+            return Ok(outcome);
+        }
+    };
+
+    let mut decoder = SseDecoder::default();
+    'read: loop {
+        let chunk = tokio::select! {
+            chunk = response.chunk() => chunk,
+            _ = wait_for_cancel(&mut cancel) => {
+                outcome.cancelled = true;
+                break;
+            }
+        };
+
+        let (payloads, mut finished) = match chunk {
+            Ok(Some(bytes)) => (decoder.push(&bytes), false),
+            Ok(None) => (decoder.finish(), true),
+            Err(e) => {
+                outcome.error = Some(e.to_string());
+                break;
+            }
+        };
+
+        for payload in payloads {
+            let events = match provider.parse_stream_event(&payload) {
+                Ok(events) => events,
+                Err(e) => {
+                    outcome.error = Some(e);
+                    break 'read;
+                }
+            };
+            for event in events {
+                match event {
+                    StreamEvent::Delta(text) => {
+                        on_delta(&text);
+                        outcome.content.push_str(&text);
+                    }
+                    StreamEvent::Model(model) => outcome.model = model,
+                    StreamEvent::ToolCallStart { index, id, name } => {
+                        pending_calls.push((index, id, name, String::new()));
+                    }
+                    StreamEvent::ToolCallDelta { index, arguments } => {
+                        if let Some(call) = pending_calls.iter_mut().rev().find(|call| call.0 == index) {
+                            call.3.push_str(&arguments);
+                        }
+                    }
+                    StreamEvent::Done => finished = true,
+                }
+            }
+        }
+
+        if finished {
+            break;
+        }
+    }
+
+    // 被取消或中断时参数可能不完整，不执行工具调用
+    if !outcome.cancelled && outcome.error.is_none() {
+        outcome.tool_calls = pending_calls
+            .into_iter()
+            .map(|(_, id, name, arguments)| ToolCall {
+                id,
+                name,
+                arguments: parse_tool_arguments(&arguments),
+            })
+            .collect();
+    }
+    Ok(outcome)
+}
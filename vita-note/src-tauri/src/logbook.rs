@@ -0,0 +1,325 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, Timelike, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::database::{open_conn, ApiResponse, BloodGlucose};
+use crate::datetime::{format_timestamp, local_midnight, parse_date_arg, parse_timestamp};
+use crate::enums::{
+    measurement_time_label, MEAL_BREAKFAST, MEAL_DINNER, MEAL_LUNCH, MEASUREMENT_AFTER_MEAL_1H,
+    MEASUREMENT_AFTER_MEAL_2H, MEASUREMENT_BEFORE_BED, MEASUREMENT_BEFORE_MEAL, MEASUREMENT_FASTING,
+};
+use crate::html::escape_html;
+use crate::meal_pairing::{load_meals, load_readings, load_settings, match_meal, ROLE_PRE_MEAL};
+
+pub const COLUMN_FASTING: &str = "fasting";
+pub const COLUMN_POST_BREAKFAST: &str = "post_breakfast";
+pub const COLUMN_PRE_LUNCH: &str = "pre_lunch";
+pub const COLUMN_POST_LUNCH: &str = "post_lunch";
+pub const COLUMN_PRE_DINNER: &str = "pre_dinner";
+pub const COLUMN_POST_DINNER: &str = "post_dinner";
+pub const COLUMN_BEDTIME: &str = "bedtime";
+
+/// 门诊常用的七点血糖表
+pub const LOGBOOK_COLUMNS: [(&str, &str); 7] = [
+    (COLUMN_FASTING, "空腹"),
+    (COLUMN_POST_BREAKFAST, "早餐后"),
+    (COLUMN_PRE_LUNCH, "午餐前"),
+    (COLUMN_POST_LUNCH, "午餐后"),
+    (COLUMN_PRE_DINNER, "晚餐前"),
+    (COLUMN_POST_DINNER, "晚餐后"),
+    (COLUMN_BEDTIME, "睡前"),
+];
+
+pub const FORMAT_CSV: &str = "csv";
+pub const FORMAT_HTML: &str = "html";
+
+const MAX_LOGBOOK_DAYS: i64 = 366;
+
+// 没有配对到餐次时按当地时间的钟点推断属于哪一餐
+const BREAKFAST_BEFORE_HOUR: u32 = 10;
+const LUNCH_BEFORE_HOUR: u32 = 15;
+const POST_BREAKFAST_BEFORE_HOUR: u32 = 11;
+const POST_LUNCH_BEFORE_HOUR: u32 = 17;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LogbookReading {
+    pub glucose_id: String,
+    pub measured_at: String,
+    pub value: f64,
+    pub measurement_time: i32,
+    pub minutes_from_meal: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LogbookDay {
+    pub date: String,
+    /// 与 columns 一一对应，每格可能有多次读数
+    pub cells: Vec<Vec<LogbookReading>>,
+    /// 夜间、随机等不属于七个时段的读数
+    pub other: Vec<LogbookReading>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GlucoseLogbook {
+    pub user_id: String,
+    pub start: String,
+    pub end: String,
+    pub columns: Vec<String>,
+    pub column_labels: Vec<String>,
+    pub days: Vec<LogbookDay>,
+}
+
+// 空腹和早餐前是同一格
+fn pre_meal_column(meal_type: i32) -> Option<&'static str> {
+    match meal_type {
+        MEAL_BREAKFAST => Some(COLUMN_FASTING),
+        MEAL_LUNCH => Some(COLUMN_PRE_LUNCH),
+        MEAL_DINNER => Some(COLUMN_PRE_DINNER),
+        _ => None,
+    }
+}
+
+fn post_meal_column(meal_type: i32) -> Option<&'static str> {
+    match meal_type {
+        MEAL_BREAKFAST => Some(COLUMN_POST_BREAKFAST),
+        MEAL_LUNCH => Some(COLUMN_POST_LUNCH),
+        MEAL_DINNER => Some(COLUMN_POST_DINNER),
+        _ => None,
+    }
+}
+
+/// 优先使用餐次配对结果，加餐或未配对的餐前、餐后读数按钟点归入最近的正餐
+fn column_for(reading: &BloodGlucose, local_hour: u32, paired: Option<(&str, i32)>) -> Option<&'static str> {
+    match reading.measurement_time {
+        MEASUREMENT_FASTING => return Some(COLUMN_FASTING),
+        MEASUREMENT_BEFORE_BED => return Some(COLUMN_BEDTIME),
+        _ => {}
+    }
+    let by_pair = paired.and_then(|(role, meal_type)| {
+        if role == ROLE_PRE_MEAL {
+            pre_meal_column(meal_type)
+        } else {
+            post_meal_column(meal_type)
+        }
+    });
+    if by_pair.is_some() {
+        return by_pair;
+    }
+
+    match reading.measurement_time {
+        MEASUREMENT_BEFORE_MEAL if local_hour < BREAKFAST_BEFORE_HOUR => Some(COLUMN_FASTING),
+        MEASUREMENT_BEFORE_MEAL if local_hour < LUNCH_BEFORE_HOUR => Some(COLUMN_PRE_LUNCH),
+        MEASUREMENT_BEFORE_MEAL => Some(COLUMN_PRE_DINNER),
+        MEASUREMENT_AFTER_MEAL_1H | MEASUREMENT_AFTER_MEAL_2H if local_hour < POST_BREAKFAST_BEFORE_HOUR => {
+            Some(COLUMN_POST_BREAKFAST)
+        }
+        MEASUREMENT_AFTER_MEAL_1H | MEASUREMENT_AFTER_MEAL_2H if local_hour < POST_LUNCH_BEFORE_HOUR => {
+            Some(COLUMN_POST_LUNCH)
+        }
+        MEASUREMENT_AFTER_MEAL_1H | MEASUREMENT_AFTER_MEAL_2H => Some(COLUMN_POST_DINNER),
+        _ => None,
+    }
+}
+
+/// 按本地日期把 start..=end 内的读数排进七点血糖表
+pub(crate) fn build_logbook(
+    conn: &Connection,
+    user_id: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<GlucoseLogbook, String> {
+    let from = local_midnight(start);
+    let to = local_midnight(end + Duration::days(1));
+    let settings = load_settings(conn, user_id)?;
+    let margin = Duration::minutes(settings.pre_meal_window_minutes.max(settings.post_2h_end_minutes).max(0) as i64);
+    let meals = load_meals(conn, user_id, from - margin, to + margin)?;
+    let readings: Vec<(DateTime<Utc>, BloodGlucose)> = load_readings(conn, user_id, from, to)?
+        .into_iter()
+        .filter(|(t, _)| *t < to)
+        .collect();
+
+    let mut days: Vec<LogbookDay> = Vec::new();
+    let mut day = start;
+    while day <= end {
+        days.push(LogbookDay {
+            date: day.format("%Y-%m-%d").to_string(),
+            cells: vec![Vec::new(); LOGBOOK_COLUMNS.len()],
+            other: Vec::new(),
+        });
+        day += Duration::days(1);
+    }
+
+    for (measured_at, reading) in &readings {
+        let local = measured_at.with_timezone(&Local);
+        let index = (local.date_naive() - start).num_days();
+        let Some(row) = usize::try_from(index).ok().and_then(|i| days.get_mut(i)) else {
+            continue;
+        };
+        let paired = match_meal(&settings, reading, *measured_at, &meals);
+        let entry = LogbookReading {
+            glucose_id: reading.id.clone(),
+            measured_at: format_timestamp(measured_at),
+            value: reading.value,
+            measurement_time: reading.measurement_time,
+            minutes_from_meal: paired.map(|(_, minutes, _)| minutes),
+        };
+        let column = column_for(reading, local.hour(), paired.map(|(role, _, meal)| (role, meal.meal_type)))
+            .and_then(|key| LOGBOOK_COLUMNS.iter().position(|(k, _)| *k == key));
+        match column {
+            Some(i) => row.cells[i].push(entry),
+            None => row.other.push(entry),
+        }
+    }
+
+    Ok(GlucoseLogbook {
+        user_id: user_id.to_string(),
+        start: start.format("%Y-%m-%d").to_string(),
+        end: end.format("%Y-%m-%d").to_string(),
+        columns: LOGBOOK_COLUMNS.iter().map(|(key, _)| key.to_string()).collect(),
+        column_labels: LOGBOOK_COLUMNS.iter().map(|(_, label)| label.to_string()).collect(),
+        days,
+    })
+}
+
+fn local_time(reading: &LogbookReading) -> String {
+    parse_timestamp(&reading.measured_at)
+        .map(|t| t.with_timezone(&Local).format("%H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// 一格只有一次读数时只写数值，多次读数时附上测量时间
+pub(crate) fn cell_text(readings: &[LogbookReading]) -> String {
+    match readings {
+        [] => String::new(),
+        [only] => format!("{:.1}", only.value),
+        _ => readings
+            .iter()
+            .map(|r| format!("{:.1} ({})", r.value, local_time(r)))
+            .collect::<Vec<_>>()
+            .join(" / "),
+    }
+}
+
+fn other_text(readings: &[LogbookReading]) -> String {
+    readings
+        .iter()
+        .map(|r| format!("{} {} {:.1}", local_time(r), measurement_time_label(r.measurement_time), r.value))
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+pub(crate) fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// 带 BOM 的 UTF-8，Excel 打开时中文不乱码
+pub(crate) fn logbook_csv(logbook: &GlucoseLogbook) -> String {
+    let mut csv = String::from("\u{FEFF}");
+    let mut header = vec!["日期".to_string()];
+    header.extend(logbook.column_labels.iter().cloned());
+    header.push("其他".to_string());
+    csv.push_str(&header.iter().map(|h| csv_field(h)).collect::<Vec<_>>().join(","));
+    csv.push_str("\r\n");
+
+    for day in &logbook.days {
+        let mut fields = vec![day.date.clone()];
+        fields.extend(day.cells.iter().map(|cell| cell_text(cell)));
+        fields.push(other_text(&day.other));
+        csv.push_str(&fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// 独立的 HTML 页面，可直接在浏览器中打印
+pub(crate) fn logbook_html(logbook: &GlucoseLogbook) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>血糖记录 {} 至 {}</title>\n", logbook.start, logbook.end));
+    html.push_str(
+        "<style>\n\
+        body { font-family: sans-serif; font-size: 12px; }\n\
+        table { border-collapse: collapse; width: 100%; }\n\
+        th, td { border: 1px solid #999; padding: 4px 6px; text-align: center; }\n\
+        th { background: #eee; }\n\
+        td.date, td.other { text-align: left; white-space: nowrap; }\n\
+        @media print { @page { size: A4 landscape; } }\n\
+        </style>\n</head>\n<body>\n",
+    );
+    html.push_str(&format!(
+        "<h2>血糖记录（mmol/L）</h2>\n<p>{} 至 {}</p>\n<table>\n<tr><th>日期</th>",
+        logbook.start, logbook.end
+    ));
+    for label in &logbook.column_labels {
+        html.push_str(&format!("<th>{}</th>", escape_html(label)));
+    }
+    html.push_str("<th>其他</th></tr>\n");
+
+    for day in &logbook.days {
+        html.push_str(&format!("<tr><td class=\"date\">{}</td>", day.date));
+        for cell in &day.cells {
+            html.push_str(&format!("<td>{}</td>", escape_html(&cell_text(cell))));
+        }
+        html.push_str(&format!("<td class=\"other\">{}</td></tr>\n", escape_html(&other_text(&day.other))));
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+fn parse_range(start: &str, end: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let start_date = parse_date_arg("start", start)?;
+    let end_date = parse_date_arg("end", end)?;
+    if end_date < start_date {
+        return Err("end must not be before start".to_string());
+    }
+    if (end_date - start_date).num_days() >= MAX_LOGBOOK_DAYS {
+        return Err(format!("Logbook range must not exceed {} days", MAX_LOGBOOK_DAYS));
+    }
+    Ok((start_date, end_date))
+}
+
+// ============ Logbook Commands ============
+
+#[tauri::command]
+pub async fn glucose_logbook(user_id: String, start: String, end: String) -> Result<ApiResponse<GlucoseLogbook>, String> {
+    let (start_date, end_date) = parse_range(&start, &end)?;
+    let conn = open_conn()?;
+    let logbook = build_logbook(&conn, &user_id, start_date, end_date)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(logbook),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn glucose_logbook_export(
+    user_id: String,
+    start: String,
+    end: String,
+    format: String,
+    dest: String,
+) -> Result<ApiResponse<String>, String> {
+    let (start_date, end_date) = parse_range(&start, &end)?;
+    let conn = open_conn()?;
+    let logbook = build_logbook(&conn, &user_id, start_date, end_date)?;
+
+    let content = match format.as_str() {
+        FORMAT_CSV => logbook_csv(&logbook),
+        FORMAT_HTML => logbook_html(&logbook),
+        _ => return Err(format!("Unsupported export format: {}", format)),
+    };
+    fs::write(&dest, content).map_err(|e| format!("{}: {}", dest, e))?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(dest),
+        message: None,
+    })
+}
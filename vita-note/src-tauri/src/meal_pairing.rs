@@ -0,0 +1,639 @@
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::database::{open_conn, ApiResponse, BloodGlucose, FoodEntry};
+use crate::datetime::{format_timestamp, parse_timestamp, parse_timestamp_arg};
+use crate::enums::{
+    MEAL_BREAKFAST, MEASUREMENT_AFTER_MEAL_1H, MEASUREMENT_AFTER_MEAL_2H, MEASUREMENT_BEFORE_MEAL,
+    MEASUREMENT_FASTING,
+};
+use crate::sick_days::load_sick_periods;
+
+pub const ROLE_PRE_MEAL: &str = "pre";
+pub const ROLE_POST_1H: &str = "post_1h";
+pub const ROLE_POST_2H: &str = "post_2h";
+
+// 新增记录后重新配对的时间范围（覆盖餐前窗口和餐后 2h 窗口）
+const REPAIR_AROUND_HOURS: i64 = 4;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MealPairingSettings {
+    pub user_id: String,
+    pub pre_meal_window_minutes: i32,
+    pub post_1h_start_minutes: i32,
+    pub post_1h_end_minutes: i32,
+    pub post_2h_start_minutes: i32,
+    pub post_2h_end_minutes: i32,
+}
+
+impl MealPairingSettings {
+    pub fn defaults(user_id: &str) -> MealPairingSettings {
+        MealPairingSettings {
+            user_id: user_id.to_string(),
+            pre_meal_window_minutes: 60,
+            post_1h_start_minutes: 40,
+            post_1h_end_minutes: 90,
+            post_2h_start_minutes: 90,
+            post_2h_end_minutes: 150,
+        }
+    }
+
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<MealPairingSettings> {
+        Ok(MealPairingSettings {
+            user_id: row.get(0)?,
+            pre_meal_window_minutes: row.get(1)?,
+            post_1h_start_minutes: row.get(2)?,
+            post_1h_end_minutes: row.get(3)?,
+            post_2h_start_minutes: row.get(4)?,
+            post_2h_end_minutes: row.get(5)?,
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.pre_meal_window_minutes < 0
+            || self.post_1h_start_minutes < 0
+            || self.post_2h_start_minutes < 0
+            || self.post_1h_start_minutes > self.post_1h_end_minutes
+            || self.post_2h_start_minutes > self.post_2h_end_minutes
+        {
+            return Err("Invalid meal pairing windows".to_string());
+        }
+        Ok(())
+    }
+
+    /// 返回 (角色, 窗口起点, 窗口终点, 理想间隔)，单位为相对用餐时间的分钟数
+    fn windows(&self) -> [(&'static str, i64, i64, i64); 3] {
+        [
+            (ROLE_PRE_MEAL, -(self.pre_meal_window_minutes as i64), 0, 0),
+            (ROLE_POST_1H, self.post_1h_start_minutes as i64, self.post_1h_end_minutes as i64, 60),
+            (ROLE_POST_2H, self.post_2h_start_minutes as i64, self.post_2h_end_minutes as i64, 120),
+        ]
+    }
+
+    /// 读数与餐次之间可能相隔的最长时间
+    fn margin(&self) -> Duration {
+        Duration::minutes(self.pre_meal_window_minutes.max(self.post_2h_end_minutes).max(0) as i64)
+    }
+}
+
+/// 同一 meal_time 的多条食物记录合并为一餐
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Meal {
+    pub meal_time: String,
+    pub meal_type: i32,
+    pub food_entry_ids: Vec<String>,
+    pub food_names: Vec<String>,
+    /// 与 food_names 一一对应的每种食物碳水
+    pub food_carbohydrates: Vec<f64>,
+    pub carbohydrates: f64,
+    pub calories: f64,
+    pub gl: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MealGlucosePair {
+    pub glucose_id: String,
+    pub user_id: String,
+    pub meal_time: String,
+    pub meal_type: i32,
+    pub role: String,
+    pub minutes_from_meal: i64,
+    pub paired_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MealGlucoseResponse {
+    pub meal: Meal,
+    pub pre_meal: Option<f64>,
+    pub post_1h: Option<f64>,
+    pub post_2h: Option<f64>,
+    pub peak: Option<f64>,
+    pub peak_minutes: Option<i64>,
+    pub rise: Option<f64>,
+}
+
+pub(crate) fn load_settings(conn: &Connection, user_id: &str) -> Result<MealPairingSettings, String> {
+    let settings = conn
+        .query_row(
+            "SELECT * FROM MealPairingSettings WHERE user_id = ?1",
+            params![user_id],
+            MealPairingSettings::from_row,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    Ok(settings.unwrap_or_else(|| MealPairingSettings::defaults(user_id)))
+}
+
+pub(crate) fn load_meals(
+    conn: &Connection,
+    user_id: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<(DateTime<Utc>, Meal)>, String> {
+    // 先按日期前缀粗筛，再精确比较时间
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM FoodEntries
+            WHERE user_id = ?1 AND meal_time >= ?2 AND meal_time <= ?3
+            ORDER BY meal_time ASC"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let entries: Vec<FoodEntry> = stmt
+        .query_map(
+            params![
+                user_id,
+                from.format("%Y-%m-%d").to_string(),
+                (to + Duration::days(1)).format("%Y-%m-%d").to_string()
+            ],
+            FoodEntry::from_row,
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut meals: Vec<(DateTime<Utc>, Meal)> = Vec::new();
+    for entry in entries {
+        let Some(meal_time) = parse_timestamp(&entry.meal_time) else { continue };
+        if meal_time < from || meal_time > to {
+            continue;
+        }
+        match meals.iter_mut().find(|(t, _)| *t == meal_time) {
+            Some((_, meal)) => {
+                meal.food_entry_ids.push(entry.id);
+                meal.food_names.push(entry.food_name);
+                meal.food_carbohydrates.push(entry.carbohydrates);
+                meal.carbohydrates += entry.carbohydrates;
+                meal.calories += entry.calories;
+                meal.gl += entry.gl.unwrap_or(0.0);
+            }
+            None => meals.push((
+                meal_time,
+                Meal {
+                    meal_time: format_timestamp(&meal_time),
+                    meal_type: entry.meal_type,
+                    food_entry_ids: vec![entry.id],
+                    food_names: vec![entry.food_name],
+                    food_carbohydrates: vec![entry.carbohydrates],
+                    carbohydrates: entry.carbohydrates,
+                    calories: entry.calories,
+                    gl: entry.gl.unwrap_or(0.0),
+                },
+            )),
+        }
+    }
+    meals.sort_by_key(|(t, _)| *t);
+    Ok(meals)
+}
+
+pub(crate) fn load_readings(
+    conn: &Connection,
+    user_id: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<(DateTime<Utc>, BloodGlucose)>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM BloodGlucose
+            WHERE user_id = ?1
+                AND COALESCE(measurement_time_exact, created_at) >= ?2
+                AND COALESCE(measurement_time_exact, created_at) <= ?3"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let mut readings: Vec<(DateTime<Utc>, BloodGlucose)> = stmt
+        .query_map(
+            params![
+                user_id,
+                from.format("%Y-%m-%d").to_string(),
+                (to + Duration::days(1)).format("%Y-%m-%d").to_string()
+            ],
+            BloodGlucose::from_row,
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|reading| Some((reading.measured_at()?, reading)))
+        .filter(|(t, _)| *t >= from && *t <= to)
+        .collect();
+    readings.sort_by_key(|(t, _)| *t);
+    Ok(readings)
+}
+
+fn role_for(reading: &BloodGlucose) -> Option<&'static str> {
+    match reading.measurement_time {
+        MEASUREMENT_BEFORE_MEAL | MEASUREMENT_FASTING => Some(ROLE_PRE_MEAL),
+        MEASUREMENT_AFTER_MEAL_1H => Some(ROLE_POST_1H),
+        MEASUREMENT_AFTER_MEAL_2H => Some(ROLE_POST_2H),
+        _ => None,
+    }
+}
+
+/// 为单条读数寻找时间窗口内最接近的餐次
+pub(crate) fn match_meal<'a>(
+    settings: &MealPairingSettings,
+    reading: &BloodGlucose,
+    measured_at: DateTime<Utc>,
+    meals: &'a [(DateTime<Utc>, Meal)],
+) -> Option<(&'static str, i64, &'a Meal)> {
+    let role = role_for(reading)?;
+    let (_, start, end, ideal) = settings.windows().into_iter().find(|(r, ..)| *r == role)?;
+
+    meals
+        .iter()
+        .filter(|(_, meal)| reading.measurement_time != MEASUREMENT_FASTING || meal.meal_type == MEAL_BREAKFAST)
+        .filter_map(|(meal_time, meal)| {
+            let minutes = (measured_at - *meal_time).num_minutes();
+            if minutes < start || minutes > end {
+                return None;
+            }
+            Some(((minutes - ideal).abs(), minutes, meal))
+        })
+        .min_by_key(|(distance, ..)| *distance)
+        .map(|(_, minutes, meal)| (role, minutes, meal))
+}
+
+/// 重新配对时间范围内的读数，写入配对表并同步 BloodGlucose 上的餐次字段
+pub(crate) fn rebuild_pairs(
+    conn: &Connection,
+    user_id: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<MealGlucosePair>, String> {
+    let settings = load_settings(conn, user_id)?;
+    let margin = settings.margin();
+    let meals = load_meals(conn, user_id, from - margin, to + margin)?;
+    let readings = load_readings(conn, user_id, from, to)?;
+    let now = format_timestamp(&Utc::now());
+
+    let mut pairs = Vec::new();
+    let mut unpaired = Vec::new();
+    for (measured_at, reading) in &readings {
+        let previous: Option<String> = conn
+            .query_row(
+                "SELECT role FROM MealGlucosePairs WHERE glucose_id = ?1",
+                params![reading.id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM MealGlucosePairs WHERE glucose_id = ?1", params![reading.id])
+            .map_err(|e| e.to_string())?;
+        let Some((role, minutes, meal)) = match_meal(&settings, reading, *measured_at, &meals) else {
+            if let Some(role) = previous {
+                unpaired.push((reading.id.clone(), role));
+            }
+            continue;
+        };
+        let pair = MealGlucosePair {
+            glucose_id: reading.id.clone(),
+            user_id: user_id.to_string(),
+            meal_time: meal.meal_time.clone(),
+            meal_type: meal.meal_type,
+            role: role.to_string(),
+            minutes_from_meal: minutes,
+            paired_at: now.clone(),
+        };
+        conn.execute(
+            r#"INSERT INTO MealGlucosePairs (glucose_id, user_id, meal_time, meal_type, role,
+                minutes_from_meal, paired_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"#,
+            params![
+                pair.glucose_id, pair.user_id, pair.meal_time, pair.meal_type, pair.role,
+                pair.minutes_from_meal, pair.paired_at
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        pairs.push(pair);
+    }
+
+    sync_glucose_fields(conn, &readings, &pairs, &unpaired)?;
+    Ok(pairs)
+}
+
+// 让手工维护的 related_meal / before_meal_glucose / after_meal_glucose 与配对结果保持一致；
+// 不再配对的读数清空之前写入的字段
+fn sync_glucose_fields(
+    conn: &Connection,
+    readings: &[(DateTime<Utc>, BloodGlucose)],
+    pairs: &[MealGlucosePair],
+    unpaired: &[(String, String)],
+) -> Result<(), String> {
+    let values: HashMap<&str, f64> = readings.iter().map(|(_, r)| (r.id.as_str(), r.value)).collect();
+    let mut by_meal: HashMap<&str, Vec<&MealGlucosePair>> = HashMap::new();
+    for pair in pairs {
+        by_meal.entry(pair.meal_time.as_str()).or_default().push(pair);
+    }
+
+    for meal_pairs in by_meal.values() {
+        let value_of = |role: &str| {
+            meal_pairs
+                .iter()
+                .find(|p| p.role == role)
+                .and_then(|p| values.get(p.glucose_id.as_str()).copied())
+        };
+        let pre = value_of(ROLE_PRE_MEAL);
+        let post = value_of(ROLE_POST_2H).or_else(|| value_of(ROLE_POST_1H));
+
+        for pair in meal_pairs {
+            let sql = if pair.role == ROLE_PRE_MEAL {
+                "UPDATE BloodGlucose SET related_meal = ?2, after_meal_glucose = ?3 WHERE id = ?1"
+            } else {
+                "UPDATE BloodGlucose SET related_meal = ?2, before_meal_glucose = ?3 WHERE id = ?1"
+            };
+            let counterpart = if pair.role == ROLE_PRE_MEAL { post } else { pre };
+            conn.execute(sql, params![pair.glucose_id, pair.meal_type, counterpart])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    for (glucose_id, role) in unpaired {
+        let sql = if role == ROLE_PRE_MEAL {
+            "UPDATE BloodGlucose SET related_meal = NULL, after_meal_glucose = NULL WHERE id = ?1"
+        } else {
+            "UPDATE BloodGlucose SET related_meal = NULL, before_meal_glucose = NULL WHERE id = ?1"
+        };
+        conn.execute(sql, params![glucose_id]).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 新增或删除饮食、血糖记录后，重新配对该时间点附近的读数
+pub(crate) fn repair_around(conn: &Connection, user_id: &str, time: &str) -> Result<(), String> {
+    let Some(time) = parse_timestamp(time) else {
+        return Ok(());
+    };
+    let span = Duration::hours(REPAIR_AROUND_HOURS);
+    rebuild_pairs(conn, user_id, time - span, time + span)?;
+    Ok(())
+}
+
+/// 按已保存的配对结果汇总每餐的血糖反应，只读不重新配对；病假日期间的进餐和读数不计入
+pub(crate) fn meal_responses(
+    conn: &Connection,
+    user_id: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<MealGlucoseResponse>, String> {
+    let sick = load_sick_periods(conn, user_id)?;
+    let meals = load_meals(conn, user_id, from, to)?;
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT p.meal_time, p.role, p.minutes_from_meal, g.value
+            FROM MealGlucosePairs p JOIN BloodGlucose g ON g.id = p.glucose_id
+            WHERE p.user_id = ?1 AND p.meal_time >= ?2 AND p.meal_time <= ?3
+            ORDER BY p.minutes_from_meal ASC"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows: Vec<(String, String, i64, f64)> = stmt
+        .query_map(params![user_id, format_timestamp(&from), format_timestamp(&to)], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let responses = meals
+        .into_iter()
+        .filter(|(meal_at, _)| !sick.contains(*meal_at))
+        .map(|(meal_at, meal)| {
+            let readings: Vec<&(String, String, i64, f64)> = rows
+                .iter()
+                .filter(|(t, _, minutes, _)| {
+                    *t == meal.meal_time && !sick.contains(meal_at + Duration::minutes(*minutes))
+                })
+                .collect();
+            let value_of = |role: &str| readings.iter().find(|(_, r, ..)| r == role).map(|(.., v)| *v);
+            let pre_meal = value_of(ROLE_PRE_MEAL);
+            let post_1h = value_of(ROLE_POST_1H);
+            let post_2h = value_of(ROLE_POST_2H);
+            let peak_reading = readings
+                .iter()
+                .filter(|(_, r, ..)| r != ROLE_PRE_MEAL)
+                .max_by(|a, b| a.3.total_cmp(&b.3));
+            let peak = peak_reading.map(|(.., v)| *v);
+            let peak_minutes = peak_reading.map(|(_, _, m, _)| *m);
+            let rise = match (pre_meal, peak) {
+                (Some(pre), Some(peak)) => Some(peak - pre),
+                _ => None,
+            };
+            MealGlucoseResponse {
+                meal,
+                pre_meal,
+                post_1h,
+                post_2h,
+                peak,
+                peak_minutes,
+                rise,
+            }
+        })
+        .collect();
+    Ok(responses)
+}
+
+// ============ Meal Pairing Commands ============
+
+#[tauri::command]
+pub async fn meal_pairing_settings_get(user_id: String) -> Result<ApiResponse<MealPairingSettings>, String> {
+    let conn = open_conn()?;
+    let settings = load_settings(&conn, &user_id)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(settings),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn meal_pairing_settings_update(
+    settings: MealPairingSettings,
+) -> Result<ApiResponse<MealPairingSettings>, String> {
+    settings.validate()?;
+    let conn = open_conn()?;
+
+    conn.execute(
+        r#"INSERT OR REPLACE INTO MealPairingSettings (user_id, pre_meal_window_minutes,
+            post_1h_start_minutes, post_1h_end_minutes, post_2h_start_minutes, post_2h_end_minutes)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)"#,
+        params![
+            settings.user_id, settings.pre_meal_window_minutes, settings.post_1h_start_minutes,
+            settings.post_1h_end_minutes, settings.post_2h_start_minutes, settings.post_2h_end_minutes
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // 窗口变化后已有的配对可能失效，按新窗口重新配对全部历史
+    let first: Option<String> = conn
+        .query_row(
+            "SELECT MIN(COALESCE(measurement_time_exact, created_at)) FROM BloodGlucose WHERE user_id = ?1",
+            params![settings.user_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if let Some(first) = first.as_deref().and_then(parse_timestamp) {
+        rebuild_pairs(&conn, &settings.user_id, first, Utc::now() + settings.margin())?;
+    }
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(settings),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn meal_pairing_rebuild(
+    user_id: String,
+    start_date: String,
+    end_date: String,
+) -> Result<ApiResponse<Vec<MealGlucosePair>>, String> {
+    let conn = open_conn()?;
+    let start = parse_timestamp_arg("start_date", &start_date)?;
+    let end = parse_timestamp_arg("end_date", &end_date)?;
+
+    let pairs = rebuild_pairs(&conn, &user_id, start, end)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(pairs),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn meal_glucose_response(
+    user_id: String,
+    start_date: String,
+    end_date: String,
+) -> Result<ApiResponse<Vec<MealGlucoseResponse>>, String> {
+    let conn = open_conn()?;
+    let start = parse_timestamp_arg("start_date", &start_date)?;
+    let end = parse_timestamp_arg("end_date", &end_date)?;
+
+    let responses = meal_responses(&conn, &user_id, start, end)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(responses),
+        message: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::{MEAL_LUNCH, MEASUREMENT_RANDOM};
+
+    fn at(value: &str) -> DateTime<Utc> {
+        parse_timestamp(value).unwrap()
+    }
+
+    fn meal(meal_time: &str, meal_type: i32) -> (DateTime<Utc>, Meal) {
+        (
+            at(meal_time),
+            Meal {
+                meal_time: meal_time.to_string(),
+                meal_type,
+                food_entry_ids: Vec::new(),
+                food_names: Vec::new(),
+                food_carbohydrates: Vec::new(),
+                carbohydrates: 0.0,
+                calories: 0.0,
+                gl: 0.0,
+            },
+        )
+    }
+
+    fn reading(measurement_time: i32) -> BloodGlucose {
+        BloodGlucose {
+            id: "g1".to_string(),
+            user_id: "u1".to_string(),
+            created_at: String::new(),
+            value: 7.0,
+            measurement_time,
+            measurement_time_exact: None,
+            before_meal_glucose: None,
+            after_meal_glucose: None,
+            related_meal: None,
+            notes: None,
+            device_name: None,
+            device_serial: None,
+        }
+    }
+
+    fn matched(
+        measurement_time: i32,
+        measured_at: &str,
+        meals: &[(DateTime<Utc>, Meal)],
+    ) -> Option<(&'static str, i64, String)> {
+        let settings = MealPairingSettings::defaults("u1");
+        match_meal(&settings, &reading(measurement_time), at(measured_at), meals)
+            .map(|(role, minutes, meal)| (role, minutes, meal.meal_time.clone()))
+    }
+
+    #[test]
+    fn pairs_readings_inside_their_windows() {
+        let meals = vec![meal("2026-01-02T12:00:00.000Z", MEAL_LUNCH)];
+        assert_eq!(
+            matched(MEASUREMENT_BEFORE_MEAL, "2026-01-02T11:30:00.000Z", &meals),
+            Some((ROLE_PRE_MEAL, -30, "2026-01-02T12:00:00.000Z".to_string()))
+        );
+        assert_eq!(
+            matched(MEASUREMENT_AFTER_MEAL_1H, "2026-01-02T12:45:00.000Z", &meals).map(|m| (m.0, m.1)),
+            Some((ROLE_POST_1H, 45))
+        );
+        assert_eq!(
+            matched(MEASUREMENT_AFTER_MEAL_2H, "2026-01-02T14:30:00.000Z", &meals).map(|m| (m.0, m.1)),
+            Some((ROLE_POST_2H, 150))
+        );
+    }
+
+    #[test]
+    fn ignores_readings_outside_windows() {
+        let meals = vec![meal("2026-01-02T12:00:00.000Z", MEAL_LUNCH)];
+        assert_eq!(matched(MEASUREMENT_BEFORE_MEAL, "2026-01-02T10:59:00.000Z", &meals), None);
+        assert_eq!(matched(MEASUREMENT_BEFORE_MEAL, "2026-01-02T12:01:00.000Z", &meals), None);
+        assert_eq!(matched(MEASUREMENT_AFTER_MEAL_1H, "2026-01-02T12:39:00.000Z", &meals), None);
+        assert_eq!(matched(MEASUREMENT_AFTER_MEAL_2H, "2026-01-02T14:31:00.000Z", &meals), None);
+        // 睡前、夜间和随机血糖不参与配对
+        assert_eq!(matched(MEASUREMENT_RANDOM, "2026-01-02T13:00:00.000Z", &meals), None);
+    }
+
+    #[test]
+    fn prefers_meal_closest_to_ideal_interval() {
+        let meals = vec![
+            meal("2026-01-02T12:00:00.000Z", MEAL_LUNCH),
+            meal("2026-01-02T12:50:00.000Z", MEAL_LUNCH),
+        ];
+        // 距第一餐 115 分钟、距第二餐 65 分钟，只有第一餐落在 2h 窗口内
+        assert_eq!(
+            matched(MEASUREMENT_AFTER_MEAL_2H, "2026-01-02T13:55:00.000Z", &meals).map(|m| m.2),
+            Some("2026-01-02T12:00:00.000Z".to_string())
+        );
+        // 距两餐分别 150 和 100 分钟，取更接近 120 分钟的第二餐
+        assert_eq!(
+            matched(MEASUREMENT_AFTER_MEAL_2H, "2026-01-02T14:30:00.000Z", &meals).map(|m| m.2),
+            Some("2026-01-02T12:50:00.000Z".to_string())
+        );
+    }
+
+    #[test]
+    fn fasting_readings_only_pair_with_breakfast() {
+        let meals = vec![meal("2026-01-02T07:30:00.000Z", MEAL_LUNCH)];
+        assert_eq!(matched(MEASUREMENT_FASTING, "2026-01-02T07:00:00.000Z", &meals), None);
+        let meals = vec![meal("2026-01-02T07:30:00.000Z", MEAL_BREAKFAST)];
+        assert_eq!(
+            matched(MEASUREMENT_FASTING, "2026-01-02T07:00:00.000Z", &meals).map(|m| (m.0, m.1)),
+            Some((ROLE_PRE_MEAL, -30))
+        );
+    }
+
+    #[test]
+    fn rejects_inverted_windows() {
+        let mut settings = MealPairingSettings::defaults("u1");
+        assert!(settings.validate().is_ok());
+        assert_eq!(settings.margin(), Duration::minutes(150));
+        settings.post_1h_start_minutes = 100;
+        assert!(settings.validate().is_err());
+    }
+}
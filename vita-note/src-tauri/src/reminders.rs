@@ -0,0 +1,667 @@
+use chrono::{DateTime, Duration, Local, NaiveTime, TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::thread;
+use std::time::Duration as StdDuration;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::database::{create_tables, open_conn, ApiPagedResult, ApiResponse, Medication};
+use crate::datetime::{format_timestamp, parse_timestamp};
+use crate::enums::MEASUREMENT_AFTER_MEAL_1H;
+use crate::glucose_checks::{mark_missed_checks, pending_checks_due};
+use crate::sick_days::episodes_between;
+use crate::summaries::generate_due_summaries;
+
+pub const TRIGGER_DAILY: &str = "daily";
+pub const TRIGGER_AFTER_MEAL: &str = "after_meal";
+pub const TRIGGER_INTERVAL: &str = "interval";
+
+pub const SOURCE_MEDICATION: &str = "medication";
+pub const SOURCE_RULE: &str = "rule";
+pub const SOURCE_GLUCOSE_CHECK: &str = "glucose_check";
+pub const SOURCE_SICK_DAY: &str = "sick_day";
+
+pub const STATUS_DELIVERED: &str = "delivered";
+pub const STATUS_ACKNOWLEDGED: &str = "acknowledged";
+
+pub const REMINDER_FIRED_EVENT: &str = "reminder-fired";
+pub const GLUCOSE_CHECK_MISSED_EVENT: &str = "glucose-check-missed";
+
+const TICK_INTERVAL_SECS: u64 = 30;
+// 应用关闭期间错过的提醒最多回溯 24 小时
+const MAX_CATCH_UP_HOURS: i64 = 24;
+// 迟到超过该时长的提醒视为补发
+const CATCH_UP_GRACE_MINUTES: i64 = 5;
+// 补发数量较多时合并成一条系统通知，避免刷屏
+const CATCH_UP_SUMMARY_THRESHOLD: usize = 3;
+const LAST_RUN_KEY: &str = "reminders.last_run_at";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReminderRule {
+    pub id: String,
+    pub user_id: String,
+    pub created_at: String,
+    pub kind: String,
+    pub title: String,
+    pub body: Option<String>,
+    pub trigger_type: String,
+    pub time_of_day: Option<String>,
+    pub offset_minutes: Option<i32>,
+    pub interval_minutes: Option<i32>,
+    pub meal_type: Option<i32>,
+    pub measurement_time: Option<i32>,
+    pub enabled: bool,
+}
+
+impl ReminderRule {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ReminderRule> {
+        Ok(ReminderRule {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            created_at: row.get(2)?,
+            kind: row.get(3)?,
+            title: row.get(4)?,
+            body: row.get(5)?,
+            trigger_type: row.get(6)?,
+            time_of_day: row.get(7)?,
+            offset_minutes: row.get(8)?,
+            interval_minutes: row.get(9)?,
+            meal_type: row.get(10)?,
+            measurement_time: row.get(11)?,
+            enabled: row.get::<_, i32>(12)? == 1,
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self.trigger_type.as_str() {
+            TRIGGER_DAILY => {
+                let time = self.time_of_day.as_deref().ok_or("time_of_day is required for daily reminders")?;
+                NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| format!("Invalid time_of_day: {}", time))?;
+            }
+            TRIGGER_AFTER_MEAL => {
+                if !matches!(self.offset_minutes, Some(m) if m >= 0) {
+                    return Err("offset_minutes is required for after_meal reminders".to_string());
+                }
+            }
+            TRIGGER_INTERVAL => {
+                if !matches!(self.interval_minutes, Some(m) if m > 0) {
+                    return Err("interval_minutes must be positive for interval reminders".to_string());
+                }
+            }
+            other => return Err(format!("Unknown trigger_type: {}", other)),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReminderDelivery {
+    pub id: String,
+    pub user_id: String,
+    pub rule_id: Option<String>,
+    pub source_type: String,
+    pub source_id: String,
+    pub kind: String,
+    pub title: String,
+    pub body: Option<String>,
+    pub due_at: String,
+    pub delivered_at: String,
+    pub acknowledged_at: Option<String>,
+    pub status: String,
+    pub is_catch_up: bool,
+}
+
+impl ReminderDelivery {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ReminderDelivery> {
+        Ok(ReminderDelivery {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            rule_id: row.get(2)?,
+            source_type: row.get(3)?,
+            source_id: row.get(4)?,
+            kind: row.get(5)?,
+            title: row.get(6)?,
+            body: row.get(7)?,
+            due_at: row.get(8)?,
+            delivered_at: row.get(9)?,
+            acknowledged_at: row.get(10)?,
+            status: row.get(11)?,
+            is_catch_up: row.get::<_, i32>(12)? == 1,
+        })
+    }
+}
+
+/// 一次待发送的提醒，由用药计划或提醒规则展开得到
+#[derive(Debug, Clone)]
+pub(crate) struct DueReminder {
+    pub user_id: String,
+    pub rule_id: Option<String>,
+    pub source_type: &'static str,
+    pub source_id: String,
+    pub kind: String,
+    pub title: String,
+    pub body: Option<String>,
+    pub due_at: DateTime<Utc>,
+}
+
+// ============ Due Reminder Expansion ============
+
+fn due_medications(conn: &Connection, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<DueReminder>, String> {
+    let mut stmt = conn
+        .prepare("SELECT * FROM Medications WHERE is_taken = 0 AND scheduled_time >= ?1")
+        .map_err(|e| e.to_string())?;
+
+    // 先按日期前缀粗筛，再精确比较时间
+    let items = stmt
+        .query_map(params![from.format("%Y-%m-%d").to_string()], Medication::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|med| {
+            let due_at = parse_timestamp(&med.scheduled_time)?;
+            if due_at <= from || due_at > to {
+                return None;
+            }
+            Some(DueReminder {
+                user_id: med.user_id,
+                rule_id: None,
+                source_type: SOURCE_MEDICATION,
+                source_id: med.id,
+                kind: "medication".to_string(),
+                title: format!("用药提醒：{}", med.drug_name),
+                body: Some(format!("请按时使用 {} {}{}", med.drug_name, med.dose, med.unit)),
+                due_at,
+            })
+        })
+        .collect();
+
+    Ok(items)
+}
+
+fn rule_reminder(rule: &ReminderRule, due_at: DateTime<Utc>) -> DueReminder {
+    DueReminder {
+        user_id: rule.user_id.clone(),
+        rule_id: Some(rule.id.clone()),
+        source_type: SOURCE_RULE,
+        source_id: rule.id.clone(),
+        kind: rule.kind.clone(),
+        title: rule.title.clone(),
+        body: rule.body.clone(),
+        due_at,
+    }
+}
+
+// 每日提醒的 time_of_day 按设备本地时间解释
+pub(crate) fn daily_occurrences(time_of_day: &str, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    let Ok(time) = NaiveTime::parse_from_str(time_of_day, "%H:%M") else {
+        return Vec::new();
+    };
+
+    let mut occurrences = Vec::new();
+    let mut date = from.with_timezone(&Local).date_naive();
+    let last_date = to.with_timezone(&Local).date_naive();
+    while date <= last_date {
+        if let Some(local) = Local.from_local_datetime(&date.and_time(time)).earliest() {
+            let due_at = local.with_timezone(&Utc);
+            if due_at > from && due_at <= to {
+                occurrences.push(due_at);
+            }
+        }
+        date = match date.succ_opt() {
+            Some(next) => next,
+            None => break,
+        };
+    }
+    occurrences
+}
+
+// 间隔提醒以规则创建时间为起点
+pub(crate) fn interval_occurrences(
+    anchor: DateTime<Utc>,
+    interval_minutes: i64,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<DateTime<Utc>> {
+    if interval_minutes <= 0 || to <= anchor {
+        return Vec::new();
+    }
+
+    let interval = Duration::minutes(interval_minutes);
+    let elapsed = (from - anchor).num_minutes().max(0);
+    let mut due_at = anchor + Duration::minutes(elapsed / interval_minutes * interval_minutes);
+    let mut occurrences = Vec::new();
+    while due_at <= to {
+        if due_at > from {
+            occurrences.push(due_at);
+        }
+        due_at += interval;
+    }
+    occurrences
+}
+
+fn meal_times(
+    conn: &Connection,
+    user_id: &str,
+    meal_type: Option<i32>,
+    from: DateTime<Utc>,
+) -> Result<Vec<DateTime<Utc>>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT DISTINCT meal_time FROM FoodEntries
+            WHERE user_id = ?1 AND meal_time >= ?2 AND (?3 IS NULL OR meal_type = ?3)"#,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut times: Vec<DateTime<Utc>> = stmt
+        .query_map(params![user_id, from.format("%Y-%m-%d").to_string(), meal_type], |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|t| parse_timestamp(&t))
+        .collect();
+    times.sort();
+    times.dedup();
+    Ok(times)
+}
+
+fn due_rules(conn: &Connection, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<DueReminder>, String> {
+    let mut stmt = conn
+        .prepare("SELECT * FROM ReminderRules WHERE enabled = 1")
+        .map_err(|e| e.to_string())?;
+    let rules: Vec<ReminderRule> = stmt
+        .query_map(params![], ReminderRule::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut due = Vec::new();
+    for rule in &rules {
+        match rule.trigger_type.as_str() {
+            TRIGGER_DAILY => {
+                let time_of_day = rule.time_of_day.as_deref().unwrap_or_default();
+                for due_at in daily_occurrences(time_of_day, from, to) {
+                    due.push(rule_reminder(rule, due_at));
+                }
+            }
+            TRIGGER_INTERVAL => {
+                let Some(anchor) = parse_timestamp(&rule.created_at) else { continue };
+                let interval = rule.interval_minutes.unwrap_or_default() as i64;
+                for due_at in interval_occurrences(anchor, interval, from, to) {
+                    due.push(rule_reminder(rule, due_at));
+                }
+            }
+            TRIGGER_AFTER_MEAL => {
+                let offset = Duration::minutes(rule.offset_minutes.unwrap_or_default() as i64);
+                for meal_time in meal_times(conn, &rule.user_id, rule.meal_type, from - offset)? {
+                    let due_at = meal_time + offset;
+                    if due_at > from && due_at <= to {
+                        due.push(rule_reminder(rule, due_at));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(due)
+}
+
+fn due_glucose_checks(conn: &Connection, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<DueReminder>, String> {
+    let items = pending_checks_due(conn, from, to)?
+        .into_iter()
+        .filter_map(|check| {
+            let label = if check.measurement_time == MEASUREMENT_AFTER_MEAL_1H { "1 小时" } else { "2 小时" };
+            Some(DueReminder {
+                user_id: check.user_id,
+                rule_id: None,
+                source_type: SOURCE_GLUCOSE_CHECK,
+                source_id: check.id,
+                kind: "glucose_check".to_string(),
+                title: format!("餐后{}血糖检测", label),
+                body: Some(format!("距离用餐已过{}，请测量餐后血糖", label)),
+                due_at: parse_timestamp(&check.due_at)?,
+            })
+        })
+        .collect();
+    Ok(items)
+}
+
+// 病假日期间按固定间隔提醒测血糖和酮体，两类提醒以 source_id 后缀区分
+fn due_sick_day_checks(conn: &Connection, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<DueReminder>, String> {
+    let mut due = Vec::new();
+    for episode in episodes_between(conn, from, to)? {
+        let Some((start, end)) = episode.bounds() else { continue };
+        let until = end.map(|e| e.min(to)).unwrap_or(to);
+        let checks = [
+            ("glucose", episode.glucose_interval_minutes, "病假日血糖检测", "生病期间血糖波动大，请现在测一次血糖"),
+            (
+                "ketone",
+                episode.ketone_interval_minutes,
+                "病假日酮体检测",
+                "请检测血酮或尿酮，血糖高于 13.9 mmol/L 时尤其重要",
+            ),
+        ];
+        for (suffix, interval, title, body) in checks {
+            for due_at in interval_occurrences(start, interval as i64, from, until) {
+                due.push(DueReminder {
+                    user_id: episode.user_id.clone(),
+                    rule_id: None,
+                    source_type: SOURCE_SICK_DAY,
+                    source_id: format!("{}:{}", episode.id, suffix),
+                    kind: format!("{}_check", suffix),
+                    title: title.to_string(),
+                    body: Some(body.to_string()),
+                    due_at,
+                });
+            }
+        }
+    }
+    Ok(due)
+}
+
+pub(crate) fn collect_due(conn: &Connection, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<DueReminder>, String> {
+    let mut due = due_medications(conn, from, to)?;
+    due.extend(due_rules(conn, from, to)?);
+    due.extend(due_glucose_checks(conn, from, to)?);
+    due.extend(due_sick_day_checks(conn, from, to)?);
+    due.sort_by_key(|d| d.due_at);
+    Ok(due)
+}
+
+// ============ Delivery Recording ============
+
+fn new_delivery_id(due: &DueReminder) -> String {
+    format!("{}:{}:{}", due.source_type, due.source_id, due.due_at.timestamp())
+}
+
+/// 记录一次提醒投递；同一来源同一时刻只记录一次，返回 None 表示已投递过
+pub(crate) fn record_delivery(
+    conn: &Connection,
+    due: &DueReminder,
+    now: DateTime<Utc>,
+) -> Result<Option<ReminderDelivery>, String> {
+    let delivery = ReminderDelivery {
+        id: new_delivery_id(due),
+        user_id: due.user_id.clone(),
+        rule_id: due.rule_id.clone(),
+        source_type: due.source_type.to_string(),
+        source_id: due.source_id.clone(),
+        kind: due.kind.clone(),
+        title: due.title.clone(),
+        body: due.body.clone(),
+        due_at: format_timestamp(&due.due_at),
+        delivered_at: format_timestamp(&now),
+        acknowledged_at: None,
+        status: STATUS_DELIVERED.to_string(),
+        is_catch_up: now - due.due_at > Duration::minutes(CATCH_UP_GRACE_MINUTES),
+    };
+
+    let inserted = conn
+        .execute(
+            r#"INSERT OR IGNORE INTO ReminderDeliveries (id, user_id, rule_id, source_type, source_id,
+                kind, title, body, due_at, delivered_at, acknowledged_at, status, is_catch_up)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)"#,
+            params![
+                delivery.id, delivery.user_id, delivery.rule_id, delivery.source_type, delivery.source_id,
+                delivery.kind, delivery.title, delivery.body, delivery.due_at, delivery.delivered_at,
+                delivery.acknowledged_at, delivery.status, if delivery.is_catch_up { 1i32 } else { 0i32 }
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(if inserted == 1 { Some(delivery) } else { None })
+}
+
+/// 按来源确认提醒（例如服药后自动确认对应的用药提醒）
+pub(crate) fn acknowledge_by_source(
+    conn: &Connection,
+    source_type: &str,
+    source_id: &str,
+    acknowledged_at: &str,
+) -> Result<usize, String> {
+    conn.execute(
+        r#"UPDATE ReminderDeliveries SET status = ?3, acknowledged_at = ?4
+        WHERE source_type = ?1 AND source_id = ?2 AND acknowledged_at IS NULL"#,
+        params![source_type, source_id, STATUS_ACKNOWLEDGED, acknowledged_at],
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn load_last_run(conn: &Connection) -> Result<Option<DateTime<Utc>>, String> {
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM SchedulerState WHERE key = ?1", params![LAST_RUN_KEY], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    Ok(value.as_deref().and_then(parse_timestamp))
+}
+
+fn save_last_run(conn: &Connection, at: DateTime<Utc>) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO SchedulerState (key, value) VALUES (?1, ?2)",
+        params![LAST_RUN_KEY, format_timestamp(&at)],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// ============ Scheduler ============
+
+pub(crate) fn notify(app: &AppHandle, title: &str, body: Option<&str>) {
+    let mut builder = app.notification().builder().title(title);
+    if let Some(body) = body {
+        builder = builder.body(body);
+    }
+    if let Err(e) = builder.show() {
+        eprintln!("Failed to show notification: {}", e);
+    }
+}
+
+fn tick(app: &AppHandle, conn: &Connection) -> Result<(), String> {
+    let now = Utc::now();
+    let earliest = now - Duration::hours(MAX_CATCH_UP_HOURS);
+    let from = load_last_run(conn)?.map_or(now, |t| t.max(earliest));
+
+    let mut delivered = Vec::new();
+    for due in collect_due(conn, from, now)? {
+        if let Some(delivery) = record_delivery(conn, &due, now)? {
+            delivered.push(delivery);
+        }
+    }
+    let missed_checks = mark_missed_checks(conn, now)?;
+    save_last_run(conn, now)?;
+    if let Err(e) = generate_due_summaries(conn, now) {
+        eprintln!("Summary generation failed: {}", e);
+    }
+
+    let (caught_up, on_time): (Vec<_>, Vec<_>) = delivered.into_iter().partition(|d| d.is_catch_up);
+
+    for delivery in &on_time {
+        notify(app, &delivery.title, delivery.body.as_deref());
+    }
+    if caught_up.len() > CATCH_UP_SUMMARY_THRESHOLD {
+        notify(app, "错过的提醒", Some(&format!("应用关闭期间有 {} 条提醒未能按时送达", caught_up.len())));
+    } else {
+        for delivery in &caught_up {
+            notify(app, &delivery.title, delivery.body.as_deref());
+        }
+    }
+
+    for delivery in on_time.iter().chain(caught_up.iter()) {
+        let _ = app.emit(REMINDER_FIRED_EVENT, delivery.clone());
+    }
+    for check in missed_checks {
+        let _ = app.emit(GLUCOSE_CHECK_MISSED_EVENT, check);
+    }
+
+    Ok(())
+}
+
+/// 在后台线程中启动提醒调度器，首次运行时补发应用关闭期间错过的提醒
+pub fn start_scheduler(app: AppHandle) {
+    thread::spawn(move || {
+        if let Err(e) = open_conn().and_then(|conn| create_tables(&conn)) {
+            eprintln!("Reminder scheduler failed to initialize database: {}", e);
+        }
+
+        loop {
+            if let Err(e) = open_conn().and_then(|conn| tick(&app, &conn)) {
+                eprintln!("Reminder scheduler tick failed: {}", e);
+            }
+            thread::sleep(StdDuration::from_secs(TICK_INTERVAL_SECS));
+        }
+    });
+}
+
+// ============ Reminder Commands ============
+
+#[tauri::command]
+pub async fn reminder_rule_create(rule: ReminderRule) -> Result<ApiResponse<ReminderRule>, String> {
+    rule.validate()?;
+    let conn = open_conn()?;
+
+    conn.execute(
+        r#"INSERT INTO ReminderRules (id, user_id, created_at, kind, title, body, trigger_type,
+            time_of_day, offset_minutes, interval_minutes, meal_type, measurement_time, enabled)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)"#,
+        params![
+            rule.id, rule.user_id, rule.created_at, rule.kind, rule.title, rule.body, rule.trigger_type,
+            rule.time_of_day, rule.offset_minutes, rule.interval_minutes, rule.meal_type,
+            rule.measurement_time, if rule.enabled { 1i32 } else { 0i32 }
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(rule),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn reminder_rules_get(user_id: String) -> Result<ApiResponse<Vec<ReminderRule>>, String> {
+    let conn = open_conn()?;
+
+    let mut stmt = conn
+        .prepare("SELECT * FROM ReminderRules WHERE user_id = ?1 ORDER BY created_at ASC")
+        .map_err(|e| e.to_string())?;
+    let rules: Vec<ReminderRule> = stmt
+        .query_map(params![user_id], ReminderRule::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(rules),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn reminder_rule_update(rule: ReminderRule) -> Result<ApiResponse<ReminderRule>, String> {
+    rule.validate()?;
+    let conn = open_conn()?;
+
+    conn.execute(
+        r#"UPDATE ReminderRules SET
+            kind = ?2, title = ?3, body = ?4, trigger_type = ?5, time_of_day = ?6,
+            offset_minutes = ?7, interval_minutes = ?8, meal_type = ?9, measurement_time = ?10,
+            enabled = ?11
+        WHERE id = ?1"#,
+        params![
+            rule.id, rule.kind, rule.title, rule.body, rule.trigger_type, rule.time_of_day,
+            rule.offset_minutes, rule.interval_minutes, rule.meal_type, rule.measurement_time,
+            if rule.enabled { 1i32 } else { 0i32 }
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(rule),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn reminder_rule_delete(id: String) -> Result<ApiResponse<String>, String> {
+    let conn = open_conn()?;
+
+    conn.execute("DELETE FROM ReminderRules WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some("Deleted".to_string()),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn reminder_deliveries_get(
+    user_id: String,
+    start_date: String,
+    end_date: String,
+    page: i64,
+    page_size: i64,
+) -> Result<ApiResponse<ApiPagedResult<ReminderDelivery>>, String> {
+    let conn = open_conn()?;
+
+    let offset = (page - 1) * page_size;
+
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM ReminderDeliveries
+            WHERE user_id = ?1 AND due_at >= ?2 AND due_at < ?3
+            ORDER BY due_at DESC
+            LIMIT ?4 OFFSET ?5"#,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let items: Vec<ReminderDelivery> = stmt
+        .query_map(
+            params![user_id, start_date, end_date, page_size, offset],
+            ReminderDelivery::from_row,
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let total: i64 = conn
+        .query_row(
+            r#"SELECT COUNT(*) FROM ReminderDeliveries
+            WHERE user_id = ?1 AND due_at >= ?2 AND due_at < ?3"#,
+            params![user_id, start_date, end_date],
+            |row| row.get(0),
+        )
+        .unwrap_or(0);
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(ApiPagedResult {
+            items,
+            total,
+            page,
+            page_size,
+        }),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn reminder_acknowledge(id: String, acknowledged_at: String) -> Result<ApiResponse<String>, String> {
+    let conn = open_conn()?;
+
+    conn.execute(
+        "UPDATE ReminderDeliveries SET status = ?2, acknowledged_at = ?3 WHERE id = ?1",
+        params![id, STATUS_ACKNOWLEDGED, acknowledged_at],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some("Acknowledged".to_string()),
+        message: None,
+    })
+}
@@ -0,0 +1,862 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, Timelike, Utc};
+use printpdf::path::{PaintMode, WindingOrder};
+use printpdf::{
+    Color, IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Point, Polygon, Rect,
+    Rgb,
+};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use crate::database::{data_dir, open_conn, ApiResponse, BloodGlucose, FoodEntry, Medication, User};
+use crate::datetime::{local_midnight, parse_date_arg, parse_timestamp};
+use crate::enums::{
+    diabetes_type_label, meal_type_label, measurement_time_label, treatment_plan_label, GENDER_FEMALE, GENDER_MALE, MEAL_BREAKFAST,
+    MEAL_DINNER, MEAL_LUNCH, MEAL_SNACK,
+};
+use crate::health_context::whole_years;
+use crate::logbook::{build_logbook, GlucoseLogbook, LogbookReading};
+use crate::meal_pairing::load_readings;
+use crate::sick_days::load_sick_periods;
+use crate::targets::{load_targets, GlucoseTargetProfile, TargetHistory};
+
+pub const SECTION_PROFILE: &str = "profile";
+pub const SECTION_GLUCOSE_STATS: &str = "glucose_stats";
+pub const SECTION_LOGBOOK: &str = "logbook";
+pub const SECTION_AGP: &str = "agp";
+pub const SECTION_MEALS: &str = "meals";
+pub const SECTION_MEDICATIONS: &str = "medications";
+
+const ALL_SECTIONS: [&str; 6] = [
+    SECTION_PROFILE,
+    SECTION_GLUCOSE_STATS,
+    SECTION_LOGBOOK,
+    SECTION_AGP,
+    SECTION_MEALS,
+    SECTION_MEDICATIONS,
+];
+
+const MAX_REPORT_DAYS: i64 = 366;
+
+// 国际共识（2019）五档血糖范围中的两端，中间三档按个人目标的 TIR 范围划分，单位 mmol/L
+const VERY_LOW: f64 = 3.0;
+const VERY_HIGH: f64 = 13.9;
+const MMOL_TO_MG_DL: f64 = 18.0;
+// 读数少于该值时 GMI 没有参考意义
+const MIN_GMI_READINGS: usize = 14;
+// 指血读数稀疏，每小时读数不足时该时段不画 AGP
+const MIN_AGP_READINGS: usize = 3;
+const AGP_MAX: f64 = 22.2;
+
+// A4 纵向，单位 mm
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 15.0;
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - MARGIN * 2.0;
+const ROW_HEIGHT: f32 = 6.0;
+const PT_TO_MM: f32 = 0.3528;
+
+const TITLE_SIZE: f32 = 16.0;
+const HEADING_SIZE: f32 = 12.0;
+const BODY_SIZE: f32 = 9.0;
+const SMALL_SIZE: f32 = 7.5;
+
+type Rgb3 = (f32, f32, f32);
+const BLACK: Rgb3 = (0.0, 0.0, 0.0);
+const GRAY: Rgb3 = (0.45, 0.45, 0.45);
+const RULE: Rgb3 = (0.8, 0.8, 0.8);
+const HEADER_FILL: Rgb3 = (0.92, 0.92, 0.92);
+const VERY_LOW_COLOR: Rgb3 = (0.55, 0.0, 0.0);
+const LOW_COLOR: Rgb3 = (0.87, 0.2, 0.2);
+const IN_RANGE_COLOR: Rgb3 = (0.2, 0.62, 0.3);
+const HIGH_COLOR: Rgb3 = (0.98, 0.7, 0.2);
+const VERY_HIGH_COLOR: Rgb3 = (0.93, 0.45, 0.1);
+const TARGET_FILL: Rgb3 = (0.88, 0.96, 0.88);
+const AGP_OUTER: Rgb3 = (0.75, 0.83, 0.95);
+const AGP_INNER: Rgb3 = (0.45, 0.6, 0.88);
+const AGP_MEDIAN: Rgb3 = (0.1, 0.25, 0.6);
+
+// 只使用单个 TrueType 文件，TTC/CFF 字体直接嵌入 PDF 后部分阅读器无法显示
+const FONT_CANDIDATES: [&str; 7] = [
+    "C:\\Windows\\Fonts\\simhei.ttf",
+    "C:\\Windows\\Fonts\\Deng.ttf",
+    "C:\\Windows\\Fonts\\simkai.ttf",
+    "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+    "/usr/share/fonts/truetype/noto/NotoSansSC-Regular.ttf",
+];
+
+const MEAL_COLUMNS: [i32; 4] = [MEAL_BREAKFAST, MEAL_LUNCH, MEAL_DINNER, MEAL_SNACK];
+
+struct ReportData {
+    user: User,
+    start: NaiveDate,
+    end: NaiveDate,
+    /// 不含病假日期间的读数，生病时的血糖不计入统计与 AGP
+    readings: Vec<(DateTime<Local>, BloodGlucose)>,
+    sick_reading_count: usize,
+    logbook: GlucoseLogbook,
+    foods: Vec<(DateTime<Local>, FoodEntry)>,
+    doses: Vec<Medication>,
+    targets: TargetHistory,
+}
+
+impl ReportData {
+    /// 报告期末适用的血糖目标，用于图例与说明文字
+    fn current_targets(&self) -> &GlucoseTargetProfile {
+        self.targets.at(local_midnight(self.end + Duration::days(1)) - Duration::seconds(1))
+    }
+}
+
+struct Cell {
+    text: String,
+    color: Rgb3,
+}
+
+impl Cell {
+    fn plain(text: impl Into<String>) -> Cell {
+        Cell { text: text.into(), color: BLACK }
+    }
+}
+
+fn line_height(size: f32) -> f32 {
+    size * PT_TO_MM * 1.5
+}
+
+// 粗略估计文字宽度：中文按一个字号宽，其余按半个字号宽
+fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().map(|c| if c.is_ascii() { 0.5 } else { 1.0 }).sum::<f32>() * size * PT_TO_MM
+}
+
+fn fit_text(text: &str, width: f32, size: f32) -> String {
+    if text_width(text, size) <= width {
+        return text.to_string();
+    }
+    let mut fitted = String::new();
+    for c in text.chars() {
+        if text_width(&fitted, size) + text_width(&format!("{}…", c), size) > width {
+            break;
+        }
+        fitted.push(c);
+    }
+    fitted.push('…');
+    fitted
+}
+
+fn rgb(color: Rgb3) -> Color {
+    Color::Rgb(Rgb::new(color.0, color.1, color.2, None))
+}
+
+fn point(x: f32, y: f32) -> (Point, bool) {
+    (Point::new(Mm(x), Mm(y)), false)
+}
+
+/// 按页写入内容，y 为下一行顶部到页面底边的距离
+struct ReportWriter {
+    doc: PdfDocumentReference,
+    font: IndirectFontRef,
+    layer: PdfLayerReference,
+    pages: usize,
+    y: f32,
+    footer: String,
+}
+
+impl ReportWriter {
+    fn new(title: &str, font_path: &Path, footer: String) -> Result<ReportWriter, String> {
+        let (doc, page, layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "content");
+        let font_file = File::open(font_path).map_err(|e| format!("{}: {}", font_path.display(), e))?;
+        let font = doc.add_external_font(font_file).map_err(|e| e.to_string())?;
+        let layer = doc.get_page(page).get_layer(layer);
+
+        let writer = ReportWriter {
+            doc,
+            font,
+            layer,
+            pages: 1,
+            y: PAGE_HEIGHT - MARGIN,
+            footer,
+        };
+        writer.draw_footer();
+        Ok(writer)
+    }
+
+    fn new_page(&mut self) {
+        let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "content");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.pages += 1;
+        self.y = PAGE_HEIGHT - MARGIN;
+        self.draw_footer();
+    }
+
+    fn draw_footer(&self) {
+        let text = format!("{} · 第 {} 页", self.footer, self.pages);
+        self.text_at(MARGIN, MARGIN / 2.0, SMALL_SIZE, &text, GRAY);
+    }
+
+    fn ensure(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn text_at(&self, x: f32, baseline: f32, size: f32, text: &str, color: Rgb3) {
+        self.layer.set_fill_color(rgb(color));
+        self.layer.use_text(text, size, Mm(x), Mm(baseline), &self.font);
+    }
+
+    fn paragraph(&mut self, size: f32, text: &str, color: Rgb3) {
+        let height = line_height(size);
+        self.ensure(height);
+        self.text_at(MARGIN, self.y - size * PT_TO_MM, size, text, color);
+        self.y -= height;
+    }
+
+    fn title(&mut self, text: &str) {
+        self.paragraph(TITLE_SIZE, text, BLACK);
+        self.y -= 2.0;
+    }
+
+    // 标题至少与后面的一段内容放在同一页
+    fn heading(&mut self, text: &str) {
+        self.ensure(line_height(HEADING_SIZE) + 24.0);
+        self.y -= 4.0;
+        self.paragraph(HEADING_SIZE, text, BLACK);
+        self.polyline(&[(MARGIN, self.y), (PAGE_WIDTH - MARGIN, self.y)], RULE, 0.6);
+        self.y -= 3.0;
+    }
+
+    fn fill_rect(&self, x: f32, y: f32, width: f32, height: f32, color: Rgb3) {
+        self.layer.set_fill_color(rgb(color));
+        self.layer
+            .add_rect(Rect::new(Mm(x), Mm(y), Mm(x + width), Mm(y + height)).with_mode(PaintMode::Fill));
+    }
+
+    fn polyline(&self, points: &[(f32, f32)], color: Rgb3, thickness: f32) {
+        self.layer.set_outline_color(rgb(color));
+        self.layer.set_outline_thickness(thickness);
+        self.layer.add_line(Line {
+            points: points.iter().map(|(x, y)| point(*x, *y)).collect(),
+            is_closed: false,
+        });
+    }
+
+    fn polygon(&self, points: &[(f32, f32)], color: Rgb3) {
+        self.layer.set_fill_color(rgb(color));
+        self.layer.add_polygon(Polygon {
+            rings: vec![points.iter().map(|(x, y)| point(*x, *y)).collect()],
+            mode: PaintMode::Fill,
+            winding_order: WindingOrder::NonZero,
+        });
+    }
+
+    fn table_header(&mut self, widths: &[f32], header: &[&str]) {
+        self.fill_rect(MARGIN, self.y - ROW_HEIGHT, widths.iter().sum(), ROW_HEIGHT, HEADER_FILL);
+        let mut x = MARGIN;
+        for (width, text) in widths.iter().zip(header) {
+            self.text_at(x + 1.5, self.y - 4.2, SMALL_SIZE, &fit_text(text, width - 3.0, SMALL_SIZE), BLACK);
+            x += width;
+        }
+        self.y -= ROW_HEIGHT;
+    }
+
+    /// 跨页时在新页重复表头
+    fn table(&mut self, widths: &[f32], header: &[&str], rows: &[Vec<Cell>]) {
+        self.ensure(ROW_HEIGHT * 2.0);
+        self.table_header(widths, header);
+        let right = MARGIN + widths.iter().sum::<f32>();
+
+        for row in rows {
+            if self.y - ROW_HEIGHT < MARGIN {
+                self.new_page();
+                self.table_header(widths, header);
+            }
+            let mut x = MARGIN;
+            for (width, cell) in widths.iter().zip(row) {
+                let text = fit_text(&cell.text, width - 3.0, SMALL_SIZE);
+                self.text_at(x + 1.5, self.y - 4.2, SMALL_SIZE, &text, cell.color);
+                x += width;
+            }
+            self.y -= ROW_HEIGHT;
+            self.polyline(&[(MARGIN, self.y), (right, self.y)], RULE, 0.3);
+        }
+    }
+
+    fn save(self, dest: &Path) -> Result<(), String> {
+        let file = File::create(dest).map_err(|e| format!("{}: {}", dest.display(), e))?;
+        self.doc.save(&mut BufWriter::new(file)).map_err(|e| e.to_string())
+    }
+}
+
+/// 数据目录 fonts 下用户放入的字体优先，其次是各平台自带的中文字体
+fn find_font() -> Result<PathBuf, String> {
+    let custom_dir = data_dir().join("fonts");
+    if let Ok(entries) = fs::read_dir(&custom_dir) {
+        let mut fonts: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().map(|ext| ext.eq_ignore_ascii_case("ttf")).unwrap_or(false))
+            .collect();
+        fonts.sort();
+        if let Some(font) = fonts.into_iter().next() {
+            return Ok(font);
+        }
+    }
+
+    FONT_CANDIDATES
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.exists())
+        .ok_or_else(|| format!("No Chinese TrueType font found; put a .ttf font in {}", custom_dir.display()))
+}
+
+fn load_report_data(conn: &Connection, user_id: &str, start: NaiveDate, end: NaiveDate) -> Result<ReportData, String> {
+    let user = conn
+        .query_row("SELECT * FROM Users WHERE id = ?1", params![user_id], User::from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("User not found: {}", user_id))?;
+
+    let from = local_midnight(start);
+    let to = local_midnight(end + Duration::days(1));
+    let coarse_from = from.format("%Y-%m-%d").to_string();
+    let coarse_to = (to + Duration::days(1)).format("%Y-%m-%d").to_string();
+    let in_range = |t: &DateTime<Utc>| *t >= from && *t < to;
+
+    let sick = load_sick_periods(conn, user_id)?;
+    let (sick_readings, readings): (Vec<_>, Vec<_>) = load_readings(conn, user_id, from, to)?
+        .into_iter()
+        .filter(|(t, _)| in_range(t))
+        .partition(|(t, _)| sick.contains(*t));
+    let readings = readings.into_iter().map(|(t, r)| (t.with_timezone(&Local), r)).collect();
+
+    let mut stmt = conn
+        .prepare("SELECT * FROM FoodEntries WHERE user_id = ?1 AND meal_time >= ?2 AND meal_time <= ?3")
+        .map_err(|e| e.to_string())?;
+    let foods = stmt
+        .query_map(params![user_id, coarse_from, coarse_to], FoodEntry::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|entry| Some((parse_timestamp(&entry.meal_time)?, entry)))
+        .filter(|(t, _)| in_range(t))
+        .map(|(t, entry)| (t.with_timezone(&Local), entry))
+        .collect();
+
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM Medications
+            WHERE user_id = ?1 AND scheduled_time >= ?2 AND scheduled_time <= ?3
+            ORDER BY drug_name ASC, scheduled_time ASC"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let doses = stmt
+        .query_map(params![user_id, coarse_from, coarse_to], Medication::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter(|m| matches!(parse_timestamp(&m.scheduled_time), Some(t) if in_range(&t)))
+        .collect();
+
+    Ok(ReportData {
+        user,
+        start,
+        end,
+        readings,
+        sick_reading_count: sick_readings.len(),
+        logbook: build_logbook(conn, user_id, start, end)?,
+        foods,
+        doses,
+        targets: load_targets(conn, user_id)?,
+    })
+}
+
+/// low、high 为该读数适用的目标范围
+fn glucose_color(value: f64, (low, high): (f64, f64)) -> Rgb3 {
+    if value < VERY_LOW {
+        VERY_LOW_COLOR
+    } else if value < low {
+        LOW_COLOR
+    } else if value <= high {
+        BLACK
+    } else if value <= VERY_HIGH {
+        HIGH_COLOR
+    } else {
+        VERY_HIGH_COLOR
+    }
+}
+
+/// 线性插值的百分位数，values 须已排序
+fn percentile(values: &[f64], p: f64) -> f64 {
+    let rank = p * (values.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    values[lower] + (values[upper] - values[lower]) * (rank - lower as f64)
+}
+
+fn render_profile(w: &mut ReportWriter, data: &ReportData) {
+    let user = &data.user;
+    w.heading("基本信息与目标");
+
+    let mut basics = vec![format!("姓名：{}", user.username)];
+    match user.gender {
+        GENDER_MALE => basics.push("性别：男".to_string()),
+        GENDER_FEMALE => basics.push("性别：女".to_string()),
+        _ => {}
+    }
+    if let Some(birthday) = user.birthday.as_deref().and_then(parse_timestamp) {
+        basics.push(format!("年龄：{} 岁", whole_years(birthday.date_naive(), data.end)));
+    }
+    if user.height > 0.0 {
+        basics.push(format!("身高：{:.0} cm", user.height));
+    }
+    w.paragraph(BODY_SIZE, &basics.join("    "), BLACK);
+
+    let mut disease = vec![
+        format!("诊断：{}", diabetes_type_label(user.diabetes_type)),
+        format!("治疗：{}", treatment_plan_label(user.treatment_plan)),
+    ];
+    if let Some(diagnosed) = user.diagnosis_date.as_deref().and_then(parse_timestamp) {
+        disease.push(format!("确诊日期：{}", diagnosed.with_timezone(&Local).format("%Y-%m-%d")));
+    }
+    w.paragraph(BODY_SIZE, &disease.join("    "), BLACK);
+
+    let mut targets = Vec::new();
+    if let Some(a1c) = user.target_hb_a1c {
+        targets.push(format!("HbA1c < {:.1}%", a1c));
+    }
+    if let Some(weight) = user.target_weight {
+        targets.push(format!("体重 {:.1} kg", weight));
+    }
+    if let Some(calories) = user.target_calories {
+        targets.push(format!("每日热量 {:.0} kcal", calories));
+    }
+    if let Some(carbs) = user.target_carbohydrates {
+        targets.push(format!("每日碳水 {:.0} g", carbs));
+    }
+    let targets = if targets.is_empty() { "未设置".to_string() } else { targets.join("，") };
+    w.paragraph(BODY_SIZE, &format!("目标：{}", targets), BLACK);
+
+    let glucose = data.current_targets();
+    let ranges: Vec<String> = glucose
+        .ranges
+        .iter()
+        .map(|r| format!("{} {:.1}–{:.1}", measurement_time_label(r.measurement_time), r.low, r.high))
+        .collect();
+    w.paragraph(
+        BODY_SIZE,
+        &format!(
+            "血糖目标（mmol/L）：{}；{} > {:.0}%",
+            ranges.join("，"),
+            glucose.tir_label(),
+            glucose.tir_goal_percent
+        ),
+        BLACK,
+    );
+}
+
+fn render_glucose_stats(w: &mut ReportWriter, data: &ReportData) {
+    w.heading("血糖统计");
+    if data.readings.is_empty() {
+        if data.sick_reading_count > 0 {
+            let text = format!("该时间段的 {} 次读数均在病假日期间，未计入统计", data.sick_reading_count);
+            w.paragraph(BODY_SIZE, &text, GRAY);
+        } else {
+            w.paragraph(BODY_SIZE, "该时间段没有血糖记录", GRAY);
+        }
+        return;
+    }
+
+    let values: Vec<f64> = data.readings.iter().map(|(_, r)| r.value).collect();
+    let count = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / count;
+    let sd = if values.len() > 1 {
+        (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (count - 1.0)).sqrt()
+    } else {
+        0.0
+    };
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let days = (data.end - data.start).num_days() + 1;
+
+    w.paragraph(
+        BODY_SIZE,
+        &format!(
+            "读数 {} 次（日均 {:.1} 次）    平均 {:.1} mmol/L    标准差 {:.1}    变异系数 {:.0}%    范围 {:.1}–{:.1}",
+            values.len(),
+            count / days as f64,
+            mean,
+            sd,
+            sd / mean * 100.0,
+            min,
+            max
+        ),
+        BLACK,
+    );
+    if values.len() >= MIN_GMI_READINGS {
+        let gmi = 3.31 + 0.02392 * mean * MMOL_TO_MG_DL;
+        w.paragraph(BODY_SIZE, &format!("估算 GMI {:.1}%（由平均血糖推算，仅供参考）", gmi), BLACK);
+    }
+
+    // 五档范围的占比条，每个读数按当时适用的目标归档
+    let current = data.current_targets();
+    let count_where = |f: fn(&GlucoseTargetProfile, f64) -> bool| {
+        data.readings
+            .iter()
+            .filter(|(t, r)| f(data.targets.at(t.with_timezone(&Utc)), r.value))
+            .count()
+    };
+    let bands: [(String, Rgb3, usize); 5] = [
+        (
+            format!("极低 <{:.1}", VERY_LOW),
+            VERY_LOW_COLOR,
+            count_where(|_, v| v < VERY_LOW),
+        ),
+        (
+            format!("低 {:.1}–{:.1}", VERY_LOW, current.tir_low),
+            LOW_COLOR,
+            count_where(|p, v| v >= VERY_LOW && p.is_hypo(v)),
+        ),
+        (
+            format!("目标 {:.1}–{:.1}", current.tir_low, current.tir_high),
+            IN_RANGE_COLOR,
+            count_where(|p, v| p.in_tir(v)),
+        ),
+        (
+            format!("高 {:.1}–{:.1}", current.tir_high, VERY_HIGH),
+            HIGH_COLOR,
+            count_where(|p, v| p.is_hyper(v) && v <= VERY_HIGH),
+        ),
+        (
+            format!("极高 >{:.1}", VERY_HIGH),
+            VERY_HIGH_COLOR,
+            count_where(|_, v| v > VERY_HIGH),
+        ),
+    ];
+    let bar_height = 8.0;
+    w.ensure(bar_height + line_height(SMALL_SIZE) + 4.0);
+    w.y -= 2.0;
+    let mut x = MARGIN;
+    for (_, color, n) in &bands {
+        let width = CONTENT_WIDTH * (*n as f32 / values.len() as f32);
+        if width > 0.0 {
+            w.fill_rect(x, w.y - bar_height, width, bar_height, *color);
+        }
+        x += width;
+    }
+    w.y -= bar_height + 1.0;
+
+    let legend_width = CONTENT_WIDTH / bands.len() as f32;
+    let baseline = w.y - SMALL_SIZE * PT_TO_MM;
+    for (i, (label, color, n)) in bands.iter().enumerate() {
+        let x = MARGIN + legend_width * i as f32;
+        w.fill_rect(x, baseline, 2.5, 2.5, *color);
+        let text = format!("{} {:.0}%", label, *n as f64 / count * 100.0);
+        w.text_at(x + 3.5, baseline, SMALL_SIZE, &text, BLACK);
+    }
+    w.y -= line_height(SMALL_SIZE) + 1.0;
+    w.paragraph(
+        SMALL_SIZE,
+        &format!(
+            "目标：TIR > {:.0}%，低于 {:.1} < 4%，低于 {:.1} < 1%（指血读数的占比，仅供参考）",
+            current.tir_goal_percent, current.tir_low, VERY_LOW
+        ),
+        GRAY,
+    );
+    if data.sick_reading_count > 0 {
+        let text = format!("另有 {} 次读数在病假日期间，未计入以上统计和 AGP", data.sick_reading_count);
+        w.paragraph(SMALL_SIZE, &text, GRAY);
+    }
+}
+
+fn render_logbook(w: &mut ReportWriter, data: &ReportData) {
+    w.heading("血糖日志（mmol/L）");
+
+    let mut widths = vec![20.0];
+    widths.extend(data.logbook.column_labels.iter().map(|_| 19.0));
+    widths.push(CONTENT_WIDTH - widths.iter().sum::<f32>());
+    let mut header = vec!["日期"];
+    header.extend(data.logbook.column_labels.iter().map(|label| label.as_str()));
+    header.push("其他");
+
+    // 表格较窄，同一格的多次读数只列数值，颜色取偏离最大的一次，按该读数时段的目标范围着色
+    let cell = |readings: &[LogbookReading]| {
+        let worst = readings
+            .iter()
+            .max_by(|a, b| (a.value - 6.5).abs().total_cmp(&(b.value - 6.5).abs()));
+        let color = worst.map(|r| {
+            let targets = match parse_timestamp(&r.measured_at) {
+                Some(t) => data.targets.at(t),
+                None => data.current_targets(),
+            };
+            glucose_color(r.value, targets.range_for(r.measurement_time))
+        });
+        Cell {
+            text: readings.iter().map(|r| format!("{:.1}", r.value)).collect::<Vec<_>>().join("/"),
+            color: color.unwrap_or(BLACK),
+        }
+    };
+    let rows: Vec<Vec<Cell>> = data
+        .logbook
+        .days
+        .iter()
+        .map(|day| {
+            let date = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d")
+                .map(|d| d.format("%m-%d %a").to_string())
+                .unwrap_or_else(|_| day.date.clone());
+            let mut row = vec![Cell::plain(date)];
+            row.extend(day.cells.iter().map(|readings| cell(readings)));
+            row.push(cell(&day.other));
+            row
+        })
+        .collect();
+    w.table(&widths, &header, &rows);
+}
+
+fn render_agp(w: &mut ReportWriter, data: &ReportData) {
+    w.heading("动态血糖谱（AGP，按小时）");
+
+    let mut by_hour: BTreeMap<u32, Vec<f64>> = BTreeMap::new();
+    for (t, reading) in &data.readings {
+        by_hour.entry(t.hour()).or_default().push(reading.value);
+    }
+    by_hour.retain(|_, values| values.len() >= MIN_AGP_READINGS);
+    if by_hour.is_empty() {
+        w.paragraph(BODY_SIZE, "读数不足，无法绘制 AGP", GRAY);
+        return;
+    }
+
+    let chart_height = 70.0;
+    let axis_width = 10.0;
+    w.ensure(chart_height + 12.0);
+    let left = MARGIN + axis_width;
+    let width = CONTENT_WIDTH - axis_width;
+    let bottom = w.y - chart_height;
+    let y_of = |value: f64| bottom + (value.clamp(0.0, AGP_MAX) / AGP_MAX) as f32 * chart_height;
+    let x_of = |hour: f32| left + width * hour / 24.0;
+
+    let current = data.current_targets();
+    let (low, high) = (current.tir_low, current.tir_high);
+    w.fill_rect(left, y_of(low), width, y_of(high) - y_of(low), TARGET_FILL);
+    for tick in [low, high, VERY_HIGH, 20.0] {
+        w.polyline(&[(left, y_of(tick)), (left + width, y_of(tick))], RULE, 0.3);
+        w.text_at(MARGIN, y_of(tick) - 1.0, SMALL_SIZE, &format!("{:.1}", tick), GRAY);
+    }
+    w.polyline(&[(left, bottom + chart_height), (left, bottom), (left + width, bottom)], GRAY, 0.5);
+
+    let mut medians: Vec<(f32, f32)> = Vec::new();
+    let mut previous_hour: Option<u32> = None;
+    for (hour, values) in &mut by_hour {
+        values.sort_by(|a, b| a.total_cmp(b));
+        let x0 = x_of(*hour as f32 + 0.1);
+        let x1 = x_of(*hour as f32 + 0.9);
+        let band = |low: f64, high: f64| [(x0, y_of(low)), (x1, y_of(low)), (x1, y_of(high)), (x0, y_of(high))];
+        w.polygon(&band(percentile(values, 0.05), percentile(values, 0.95)), AGP_OUTER);
+        w.polygon(&band(percentile(values, 0.25), percentile(values, 0.75)), AGP_INNER);
+
+        // 相邻小时的中位数连成线，中间缺数据时断开
+        let median = (x_of(*hour as f32 + 0.5), y_of(percentile(values, 0.5)));
+        if previous_hour.map(|h| h + 1 != *hour).unwrap_or(false) {
+            if medians.len() > 1 {
+                w.polyline(&medians, AGP_MEDIAN, 1.2);
+            }
+            medians.clear();
+        }
+        medians.push(median);
+        previous_hour = Some(*hour);
+    }
+    if medians.len() > 1 {
+        w.polyline(&medians, AGP_MEDIAN, 1.2);
+    }
+    for (hour, values) in &by_hour {
+        let (x, y) = (x_of(*hour as f32 + 0.5), y_of(percentile(values, 0.5)));
+        w.polygon(&[(x - 0.8, y - 0.8), (x + 0.8, y - 0.8), (x + 0.8, y + 0.8), (x - 0.8, y + 0.8)], AGP_MEDIAN);
+    }
+
+    for hour in (0..=24).step_by(3) {
+        let x = x_of(hour as f32);
+        w.text_at(x - 3.0, bottom - 4.0, SMALL_SIZE, &format!("{:02}:00", hour % 24), GRAY);
+    }
+    w.y = bottom - 6.0;
+    w.paragraph(
+        SMALL_SIZE,
+        &format!(
+            "浅色为 5%–95%，深色为 25%–75%，深色点线为中位数；绿色区域为目标范围 {:.1}–{:.1}；读数少于 {} 次的小时不显示",
+            low, high, MIN_AGP_READINGS
+        ),
+        GRAY,
+    );
+}
+
+fn render_meals(w: &mut ReportWriter, data: &ReportData) {
+    w.heading("每餐碳水（g）");
+    if data.foods.is_empty() {
+        w.paragraph(BODY_SIZE, "该时间段没有饮食记录", GRAY);
+        return;
+    }
+
+    let mut totals: BTreeMap<NaiveDate, [f64; 4]> = BTreeMap::new();
+    for (t, entry) in &data.foods {
+        let column = MEAL_COLUMNS.iter().position(|m| *m == entry.meal_type).unwrap_or(MEAL_COLUMNS.len() - 1);
+        totals.entry(t.date_naive()).or_default()[column] += entry.carbohydrates;
+    }
+
+    let mut header = vec!["日期"];
+    header.extend(MEAL_COLUMNS.iter().map(|meal| meal_type_label(*meal)));
+    header.push("合计");
+    let widths = [30.0, 30.0, 30.0, 30.0, 30.0, 30.0];
+    let target = data.user.target_carbohydrates;
+
+    let rows: Vec<Vec<Cell>> = totals
+        .iter()
+        .map(|(day, meals)| {
+            let mut row = vec![Cell::plain(day.format("%m-%d %a").to_string())];
+            row.extend(meals.iter().map(|carbs| {
+                Cell::plain(if *carbs > 0.0 { format!("{:.0}", carbs) } else { String::new() })
+            }));
+            let total: f64 = meals.iter().sum();
+            row.push(Cell {
+                text: format!("{:.0}", total),
+                color: if target.map(|t| total > t).unwrap_or(false) { HIGH_COLOR } else { BLACK },
+            });
+            row
+        })
+        .collect();
+    w.table(&widths, &header, &rows);
+
+    let average = totals.values().map(|meals| meals.iter().sum::<f64>()).sum::<f64>() / totals.len() as f64;
+    let mut summary = format!("记录 {} 天，日均碳水 {:.0} g", totals.len(), average);
+    if let Some(target) = target {
+        summary.push_str(&format!("（目标 {:.0} g，超出目标的日期以橙色标出）", target));
+    }
+    w.y -= 1.0;
+    w.paragraph(SMALL_SIZE, &summary, GRAY);
+}
+
+fn render_medications(w: &mut ReportWriter, data: &ReportData) {
+    w.heading("用药与依从性");
+    if data.doses.is_empty() {
+        w.paragraph(BODY_SIZE, "该时间段没有用药记录", GRAY);
+        return;
+    }
+
+    // 同一药品按剂量分别统计
+    let mut adherence: BTreeMap<(String, String), (usize, usize, Vec<String>)> = BTreeMap::new();
+    for dose in &data.doses {
+        let key = (dose.drug_name.clone(), format!("{} {}", dose.dose, dose.unit));
+        let entry = adherence.entry(key).or_default();
+        entry.1 += 1;
+        if dose.is_taken {
+            entry.0 += 1;
+        }
+        if let Some(t) = parse_timestamp(&dose.scheduled_time) {
+            let time = t.with_timezone(&Local).format("%H:%M").to_string();
+            if !entry.2.contains(&time) {
+                entry.2.push(time);
+            }
+        }
+    }
+
+    let header = ["药品", "剂量", "时间", "应服", "已服", "依从率"];
+    let widths = [50.0, 26.0, 44.0, 20.0, 20.0, 20.0];
+    let rows: Vec<Vec<Cell>> = adherence
+        .into_iter()
+        .map(|((drug, dose), (taken, total, mut times))| {
+            times.sort();
+            let rate = taken as f64 / total as f64 * 100.0;
+            vec![
+                Cell::plain(drug),
+                Cell::plain(dose),
+                Cell::plain(times.join(" ")),
+                Cell::plain(total.to_string()),
+                Cell::plain(taken.to_string()),
+                Cell {
+                    text: format!("{:.0}%", rate),
+                    color: if rate < 80.0 { LOW_COLOR } else { BLACK },
+                },
+            ]
+        })
+        .collect();
+    w.table(&widths, &header, &rows);
+}
+
+fn validate_sections(sections: &[String]) -> Result<Vec<&'static str>, String> {
+    if sections.is_empty() {
+        return Ok(ALL_SECTIONS.to_vec());
+    }
+    if let Some(unknown) = sections.iter().find(|s| !ALL_SECTIONS.contains(&s.as_str())) {
+        return Err(format!("Unknown report section: {}", unknown));
+    }
+    // 按固定顺序输出，与传入顺序无关
+    Ok(ALL_SECTIONS
+        .iter()
+        .copied()
+        .filter(|section| sections.iter().any(|s| s == section))
+        .collect())
+}
+
+/// 生成 PDF 报告并写入 dest
+pub(crate) fn write_report(
+    conn: &Connection,
+    user_id: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+    sections: &[&str],
+    font_path: &Path,
+    dest: &Path,
+) -> Result<(), String> {
+    let data = load_report_data(conn, user_id, start, end)?;
+    let period = format!("{} 至 {}", start.format("%Y-%m-%d"), end.format("%Y-%m-%d"));
+    let footer = format!("VitaNote 健康报告 · {} · {}", data.user.username, period);
+
+    let mut w = ReportWriter::new("VitaNote 健康报告", font_path, footer)?;
+    w.title("糖尿病自我管理报告");
+    w.paragraph(
+        BODY_SIZE,
+        &format!("统计周期：{}    生成时间：{}", period, Local::now().format("%Y-%m-%d %H:%M")),
+        GRAY,
+    );
+    w.paragraph(SMALL_SIZE, "数据来自患者在 VitaNote 中的自我记录，不能替代医疗诊断。", GRAY);
+
+    for section in sections {
+        match *section {
+            SECTION_PROFILE => render_profile(&mut w, &data),
+            SECTION_GLUCOSE_STATS => render_glucose_stats(&mut w, &data),
+            SECTION_LOGBOOK => render_logbook(&mut w, &data),
+            SECTION_AGP => render_agp(&mut w, &data),
+            SECTION_MEALS => render_meals(&mut w, &data),
+            _ => render_medications(&mut w, &data),
+        }
+    }
+    w.save(dest)
+}
+
+// ============ Report Commands ============
+
+#[tauri::command]
+pub async fn report_generate_pdf(
+    user_id: String,
+    start: String,
+    end: String,
+    sections: Vec<String>,
+    dest: String,
+) -> Result<ApiResponse<String>, String> {
+    let start_date = parse_date_arg("start", &start)?;
+    let end_date = parse_date_arg("end", &end)?;
+    if end_date < start_date {
+        return Err("end must not be before start".to_string());
+    }
+    if (end_date - start_date).num_days() >= MAX_REPORT_DAYS {
+        return Err(format!("Report range must not exceed {} days", MAX_REPORT_DAYS));
+    }
+    let sections = validate_sections(&sections)?;
+    let font_path = find_font()?;
+
+    let conn = open_conn()?;
+    write_report(&conn, &user_id, start_date, end_date, &sections, &font_path, Path::new(&dest))?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(dest),
+        message: None,
+    })
+}
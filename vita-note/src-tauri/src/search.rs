@@ -0,0 +1,312 @@
+use rusqlite::{params, params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::database::{open_conn, ApiResponse};
+use crate::html::escape_html;
+
+pub const KIND_CHAT: &str = "chat";
+pub const KIND_FOOD: &str = "food_entry";
+pub const KIND_GLUCOSE: &str = "blood_glucose";
+pub const KIND_MEDICATION: &str = "medication";
+pub const KIND_JOURNAL: &str = "journal";
+
+const MAX_HITS: i64 = 50;
+// trigram 分词只能匹配不少于 3 个字符的词，更短的词（如“血糖”）改用 LIKE 扫描
+const MIN_TRIGRAM_CHARS: usize = 3;
+const SNIPPET_TOKENS: i64 = 24;
+const SNIPPET_CONTEXT_CHARS: usize = 24;
+
+// 先用私有区字符标记命中位置，转义后再换成 <mark>，避免记录内容中的 HTML 被前端渲染
+const MARK_START: char = '\u{E000}';
+const MARK_END: char = '\u{E001}';
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub kind: String,
+    pub record_id: String,
+    pub created_at: String,
+    pub title: Option<String>,
+    pub snippet: String,
+    pub score: f64,
+}
+
+/// 被索引的记录：标题列（可选）与正文列
+struct IndexedSource {
+    kind: &'static str,
+    table: &'static str,
+    title: Option<&'static str>,
+    body: &'static str,
+}
+
+const SOURCES: [IndexedSource; 5] = [
+    IndexedSource { kind: KIND_CHAT, table: "ChatMessages", title: None, body: "content" },
+    IndexedSource { kind: KIND_FOOD, table: "FoodEntries", title: Some("food_name"), body: "notes" },
+    IndexedSource { kind: KIND_GLUCOSE, table: "BloodGlucose", title: None, body: "notes" },
+    IndexedSource { kind: KIND_MEDICATION, table: "Medications", title: Some("drug_name"), body: "notes" },
+    IndexedSource { kind: KIND_JOURNAL, table: "JournalEntries", title: None, body: "notes" },
+];
+
+// 标题或正文不为空的记录才索引；row 为触发器中的 new 或回填时的表名，filter 为附加条件
+fn index_insert(source: &IndexedSource, row: &str, from: &str, filter: &str) -> String {
+    let title = source
+        .title
+        .map(|column| format!("{}.{}", row, column))
+        .unwrap_or_else(|| "NULL".to_string());
+    let non_empty = |column: &str| {
+        format!("({row}.{column} IS NOT NULL AND {row}.{column} <> '')", row = row, column = column)
+    };
+    let condition = match source.title {
+        Some(column) => format!("({} OR {})", non_empty(column), non_empty(source.body)),
+        None => non_empty(source.body),
+    };
+    format!(
+        "INSERT INTO SearchIndex (kind, record_id, user_id, created_at, title, body) \
+        SELECT '{kind}', {row}.id, {row}.user_id, {row}.created_at, {title}, COALESCE({row}.{body}, '') {from} \
+        WHERE {condition}{filter};",
+        kind = source.kind,
+        row = row,
+        title = title,
+        body = source.body,
+        from = from,
+        condition = condition,
+        filter = filter,
+    )
+}
+
+/// 创建全文索引和维护索引的触发器，并把尚未索引的已有记录补进索引
+pub(crate) fn create_search_index(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        r#"CREATE VIRTUAL TABLE IF NOT EXISTS SearchIndex USING fts5(
+            kind UNINDEXED,
+            record_id UNINDEXED,
+            user_id UNINDEXED,
+            created_at UNINDEXED,
+            title,
+            body,
+            tokenize = 'trigram'
+        )"#,
+        params![],
+    )
+    .map_err(|e| e.to_string())?;
+
+    for source in &SOURCES {
+        let remove = format!("DELETE FROM SearchIndex WHERE kind = '{}' AND record_id = old.id;", source.kind);
+        // 触发器每次启动时重建，索引条件调整后旧数据库也能生效
+        for event in ["insert", "update", "delete"] {
+            conn.execute(&format!("DROP TRIGGER IF EXISTS search_{}_{}", source.kind, event), params![])
+                .map_err(|e| e.to_string())?;
+        }
+        let triggers = [
+            format!(
+                "CREATE TRIGGER search_{}_insert AFTER INSERT ON {} BEGIN {} END",
+                source.kind,
+                source.table,
+                index_insert(source, "new", "", "")
+            ),
+            format!(
+                "CREATE TRIGGER search_{}_update AFTER UPDATE ON {} BEGIN {} {} END",
+                source.kind,
+                source.table,
+                remove,
+                index_insert(source, "new", "", "")
+            ),
+            format!(
+                "CREATE TRIGGER search_{}_delete AFTER DELETE ON {} BEGIN {} END",
+                source.kind, source.table, remove
+            ),
+        ];
+        for trigger in triggers {
+            conn.execute(&trigger, params![]).map_err(|e| e.to_string())?;
+        }
+    }
+
+    for source in &SOURCES {
+        let missing = format!(
+            " AND {}.id NOT IN (SELECT record_id FROM SearchIndex WHERE kind = '{}')",
+            source.table, source.kind
+        );
+        let backfill = index_insert(source, source.table, &format!("FROM {}", source.table), &missing);
+        conn.execute(&backfill, params![]).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// 每个词加引号按短语匹配，多个词之间为 AND
+fn fts_query(terms: &[&str]) -> String {
+    terms
+        .iter()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+// 转义后再把命中标记换成 <mark>
+fn highlight(text: &str) -> String {
+    escape_html(text).replace(MARK_START, "<mark>").replace(MARK_END, "</mark>")
+}
+
+/// LIKE 匹配没有 snippet()，在第一个命中词附近截取一段并标记所有命中
+fn like_snippet(text: &str, terms: &[&str]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let lowered: Vec<char> = chars.iter().map(|c| c.to_ascii_lowercase()).collect();
+    let patterns: Vec<Vec<char>> = terms.iter().map(|t| t.chars().map(|c| c.to_ascii_lowercase()).collect()).collect();
+
+    let matches_at = |pos: usize| {
+        patterns
+            .iter()
+            .filter(|p| !p.is_empty())
+            .find(|p| lowered[pos..].starts_with(p))
+            .map(|p| p.len())
+    };
+    let first = (0..chars.len()).find(|pos| matches_at(*pos).is_some()).unwrap_or(0);
+    let start = first.saturating_sub(SNIPPET_CONTEXT_CHARS);
+    let end = (first + SNIPPET_CONTEXT_CHARS * 2).min(chars.len());
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    let mut pos = start;
+    while pos < end {
+        match matches_at(pos) {
+            Some(len) => {
+                snippet.push(MARK_START);
+                snippet.extend(&chars[pos..(pos + len).min(chars.len())]);
+                snippet.push(MARK_END);
+                pos += len;
+            }
+            None => {
+                snippet.push(chars[pos]);
+                pos += 1;
+            }
+        }
+    }
+    if pos < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+/// 在用户的聊天记录和备注中搜索；kinds 为空时搜索全部类别
+pub(crate) fn search(conn: &Connection, user_id: &str, query: &str, kinds: &[String]) -> Result<Vec<SearchHit>, String> {
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    let kinds: Vec<String> = if kinds.is_empty() {
+        SOURCES.iter().map(|s| s.kind.to_string()).collect()
+    } else {
+        if let Some(unknown) = kinds.iter().find(|k| !SOURCES.iter().any(|s| s.kind == k.as_str())) {
+            return Err(format!("Unknown search kind: {}", unknown));
+        }
+        kinds.to_vec()
+    };
+    let kind_placeholders = vec!["?"; kinds.len()].join(", ");
+
+    if terms.iter().all(|t| t.chars().count() >= MIN_TRIGRAM_CHARS) {
+        // bm25() 越小越相关，返回时取反使分数越大越相关
+        let sql = format!(
+            r#"SELECT kind, record_id, created_at, title,
+                snippet(SearchIndex, -1, char(57344), char(57345), '…', {tokens}),
+                bm25(SearchIndex)
+            FROM SearchIndex
+            WHERE SearchIndex MATCH ? AND user_id = ? AND kind IN ({kinds})
+            ORDER BY bm25(SearchIndex)
+            LIMIT {limit}"#,
+            tokens = SNIPPET_TOKENS,
+            kinds = kind_placeholders,
+            limit = MAX_HITS,
+        );
+        let mut args = vec![fts_query(&terms), user_id.to_string()];
+        args.extend(kinds);
+
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let hits = stmt
+            .query_map(params_from_iter(args), |row| {
+                Ok(SearchHit {
+                    kind: row.get(0)?,
+                    record_id: row.get(1)?,
+                    created_at: row.get(2)?,
+                    title: row.get(3)?,
+                    snippet: highlight(&row.get::<_, String>(4)?),
+                    score: -row.get::<_, f64>(5)?,
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        return Ok(hits);
+    }
+
+    let term_filters = vec!["(body LIKE ? ESCAPE '\\' OR title LIKE ? ESCAPE '\\')"; terms.len()].join(" AND ");
+    let sql = format!(
+        r#"SELECT kind, record_id, created_at, title, body
+        FROM SearchIndex
+        WHERE user_id = ? AND kind IN ({kinds}) AND {terms}
+        ORDER BY created_at DESC
+        LIMIT {limit}"#,
+        kinds = kind_placeholders,
+        terms = term_filters,
+        limit = MAX_HITS,
+    );
+    let mut args = vec![user_id.to_string()];
+    args.extend(kinds);
+    for term in &terms {
+        let pattern = format!("%{}%", escape_like(term));
+        args.push(pattern.clone());
+        args.push(pattern);
+    }
+
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows: Vec<(String, String, String, Option<String>, String)> = stmt
+        .query_map(params_from_iter(args), |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    // 没有 bm25 时以命中次数作为分数，次数相同的按时间倒序
+    let mut hits: Vec<SearchHit> = rows
+        .into_iter()
+        .map(|(kind, record_id, created_at, title, body)| {
+            let haystack = format!("{} {}", title.as_deref().unwrap_or_default(), body).to_ascii_lowercase();
+            let score = terms
+                .iter()
+                .map(|t| haystack.matches(&t.to_ascii_lowercase()).count())
+                .sum::<usize>() as f64;
+            SearchHit {
+                kind,
+                record_id,
+                created_at,
+                title,
+                snippet: highlight(&like_snippet(&body, &terms)),
+                score,
+            }
+        })
+        .collect();
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(hits)
+}
+
+// ============ Search Commands ============
+
+#[tauri::command]
+pub async fn search_all(
+    user_id: String,
+    query: String,
+    kinds: Option<Vec<String>>,
+) -> Result<ApiResponse<Vec<SearchHit>>, String> {
+    let conn = open_conn()?;
+    let hits = search(&conn, &user_id, &query, &kinds.unwrap_or_default())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(hits),
+        message: None,
+    })
+}
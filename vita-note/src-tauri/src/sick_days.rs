@@ -0,0 +1,393 @@
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::database::{open_conn, ApiResponse, User};
+use crate::datetime::{format_timestamp, parse_timestamp, parse_timestamp_arg};
+use crate::enums::{DIABETES_TYPE1, TREATMENT_COMBINED, TREATMENT_INSULIN, TREATMENT_ORAL_MEDICATION};
+use crate::reminders::interval_occurrences;
+
+// 生病期间使用胰岛素者每 2 小时测一次血糖，其他人每 4 小时一次
+const INSULIN_GLUCOSE_INTERVAL_MINUTES: i32 = 120;
+const GLUCOSE_INTERVAL_MINUTES: i32 = 240;
+const KETONE_INTERVAL_MINUTES: i32 = 240;
+
+/// 一次病假日记录；ended_at 为空表示仍在生病期间
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SickDayEpisode {
+    pub id: String,
+    pub user_id: String,
+    pub created_at: String,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub glucose_interval_minutes: i32,
+    pub ketone_interval_minutes: i32,
+    /// 开始时是否使用胰岛素，决定检测频率和剂量调整提示
+    pub uses_insulin: bool,
+    pub notes: Option<String>,
+}
+
+impl SickDayEpisode {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SickDayEpisode> {
+        Ok(SickDayEpisode {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            created_at: row.get(2)?,
+            started_at: row.get(3)?,
+            ended_at: row.get(4)?,
+            glucose_interval_minutes: row.get(5)?,
+            ketone_interval_minutes: row.get(6)?,
+            uses_insulin: row.get::<_, i32>(7)? == 1,
+            notes: row.get(8)?,
+        })
+    }
+
+    /// 病假日的时间范围，未结束时 end 为 None
+    pub(crate) fn bounds(&self) -> Option<(DateTime<Utc>, Option<DateTime<Utc>>)> {
+        let start = parse_timestamp(&self.started_at)?;
+        let end = self.ended_at.as_deref().and_then(parse_timestamp);
+        Some((start, end))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SickDayChecklistItem {
+    pub code: String,
+    pub title: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SickDayStatus {
+    pub episode: SickDayEpisode,
+    pub checklist: Vec<SickDayChecklistItem>,
+    pub next_glucose_check: Option<String>,
+    pub next_ketone_check: Option<String>,
+}
+
+/// 用户所有病假日的时间范围，统计时用来把生病期间的记录与平时分开
+pub(crate) struct SickPeriods(Vec<(DateTime<Utc>, Option<DateTime<Utc>>)>);
+
+impl SickPeriods {
+    pub fn contains(&self, t: DateTime<Utc>) -> bool {
+        self.episode_start(t).is_some()
+    }
+
+    /// t 所在病假日的开始时间
+    pub fn episode_start(&self, t: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.0
+            .iter()
+            .find(|(start, end)| t >= *start && end.map(|e| t < e).unwrap_or(true))
+            .map(|(start, _)| *start)
+    }
+}
+
+pub(crate) fn load_sick_periods(conn: &Connection, user_id: &str) -> Result<SickPeriods, String> {
+    let mut stmt = conn
+        .prepare("SELECT * FROM SickDayEpisodes WHERE user_id = ?1 ORDER BY started_at ASC")
+        .map_err(|e| e.to_string())?;
+    let periods = stmt
+        .query_map(params![user_id], SickDayEpisode::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|episode| episode.bounds())
+        .collect();
+    Ok(SickPeriods(periods))
+}
+
+/// 与 (from, to] 有重叠的病假日，供提醒调度展开检测提醒
+pub(crate) fn episodes_between(
+    conn: &Connection,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<SickDayEpisode>, String> {
+    let mut stmt = conn
+        .prepare("SELECT * FROM SickDayEpisodes WHERE ended_at IS NULL OR ended_at >= ?1")
+        .map_err(|e| e.to_string())?;
+
+    // 先按日期前缀粗筛，再精确比较时间
+    let items = stmt
+        .query_map(params![from.format("%Y-%m-%d").to_string()], SickDayEpisode::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter(|episode| match episode.bounds() {
+            Some((start, end)) => start <= to && end.map(|e| e > from).unwrap_or(true),
+            None => false,
+        })
+        .collect();
+    Ok(items)
+}
+
+fn active_episode(conn: &Connection, user_id: &str) -> Result<Option<SickDayEpisode>, String> {
+    conn.query_row(
+        "SELECT * FROM SickDayEpisodes WHERE user_id = ?1 AND ended_at IS NULL ORDER BY started_at DESC LIMIT 1",
+        params![user_id],
+        SickDayEpisode::from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+fn uses_insulin(user: &User) -> bool {
+    user.diabetes_type == DIABETES_TYPE1 || matches!(user.treatment_plan, TREATMENT_INSULIN | TREATMENT_COMBINED)
+}
+
+fn item(code: &str, title: &str, detail: String) -> SickDayChecklistItem {
+    SickDayChecklistItem {
+        code: code.to_string(),
+        title: title.to_string(),
+        detail,
+    }
+}
+
+/// 病假日注意事项，按用户的治疗方式给出；剂量调整只给原则，具体数值以医生制定的方案为准
+fn sick_day_checklist(episode: &SickDayEpisode, treatment_plan: i32) -> Vec<SickDayChecklistItem> {
+    let mut items = vec![
+        item(
+            "glucose",
+            "增加血糖检测",
+            format!("每 {} 小时测一次血糖，夜间也不要中断", episode.glucose_interval_minutes / 60),
+        ),
+        item(
+            "ketones",
+            "检测酮体",
+            format!(
+                "每 {} 小时检测血酮或尿酮，血糖高于 13.9 mmol/L 时务必检测",
+                episode.ketone_interval_minutes / 60
+            ),
+        ),
+        item("fluids", "补充水分", "每小时小口补充 150–250 mL 无糖液体，预防脱水".to_string()),
+        item(
+            "carbohydrates",
+            "保证碳水摄入",
+            "无法正常进食时，每 3–4 小时通过粥、汤面或含糖饮料补充约 45–50 g 碳水化合物".to_string(),
+        ),
+    ];
+
+    if episode.uses_insulin {
+        items.push(item(
+            "basal_insulin",
+            "不要停用基础胰岛素",
+            "即使进食减少也不要自行停用长效胰岛素，低血糖时再与医生商量减量".to_string(),
+        ));
+        items.push(item(
+            "correction_dose",
+            "按方案追加胰岛素",
+            "血糖持续升高或酮体阳性时，按医生制定的病假日方案追加速效胰岛素，并记录每次剂量".to_string(),
+        ));
+    }
+    if matches!(treatment_plan, TREATMENT_ORAL_MEDICATION | TREATMENT_COMBINED) {
+        items.push(item(
+            "oral_medication",
+            "口服药调整",
+            "呕吐、腹泻或明显脱水时暂停二甲双胍和 SGLT2 抑制剂，并尽快咨询医生".to_string(),
+        ));
+    }
+
+    items.push(item(
+        "seek_care",
+        "及时就医",
+        "持续呕吐或腹泻超过 6 小时、血糖反复高于 13.9 或低于 3.9 mmol/L、血酮 ≥ 1.5 mmol/L 或尿酮 ++ 以上、\
+        呼吸深快或意识模糊时，请立即就医"
+            .to_string(),
+    ));
+    items
+}
+
+fn next_check(anchor: DateTime<Utc>, interval_minutes: i32, now: DateTime<Utc>) -> Option<String> {
+    let interval = interval_minutes as i64;
+    interval_occurrences(anchor, interval, now, now + Duration::minutes(interval))
+        .first()
+        .map(format_timestamp)
+}
+
+fn build_status(episode: SickDayEpisode, user: &User, now: DateTime<Utc>) -> SickDayStatus {
+    let checklist = sick_day_checklist(&episode, user.treatment_plan);
+    let (next_glucose_check, next_ketone_check) = match episode.bounds() {
+        Some((start, None)) => (
+            next_check(start, episode.glucose_interval_minutes, now),
+            next_check(start, episode.ketone_interval_minutes, now),
+        ),
+        _ => (None, None),
+    };
+    SickDayStatus {
+        episode,
+        checklist,
+        next_glucose_check,
+        next_ketone_check,
+    }
+}
+
+fn load_user(conn: &Connection, user_id: &str) -> Result<User, String> {
+    conn.query_row("SELECT * FROM Users WHERE id = ?1", params![user_id], User::from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("User not found: {}", user_id))
+}
+
+// ============ Sick Day Commands ============
+
+#[tauri::command]
+pub async fn sick_day_start(
+    user_id: String,
+    started_at: Option<String>,
+    notes: Option<String>,
+) -> Result<ApiResponse<SickDayStatus>, String> {
+    let conn = open_conn()?;
+    let user = load_user(&conn, &user_id)?;
+    if active_episode(&conn, &user_id)?.is_some() {
+        return Err("A sick-day episode is already active".to_string());
+    }
+
+    let now = Utc::now();
+    let started = match started_at.as_deref() {
+        Some(value) => parse_timestamp_arg("started_at", value)?,
+        None => now,
+    };
+    let insulin = uses_insulin(&user);
+    let episode = SickDayEpisode {
+        id: Uuid::new_v4().to_string(),
+        user_id,
+        created_at: format_timestamp(&now),
+        started_at: format_timestamp(&started),
+        ended_at: None,
+        glucose_interval_minutes: if insulin { INSULIN_GLUCOSE_INTERVAL_MINUTES } else { GLUCOSE_INTERVAL_MINUTES },
+        ketone_interval_minutes: KETONE_INTERVAL_MINUTES,
+        uses_insulin: insulin,
+        notes,
+    };
+
+    conn.execute(
+        r#"INSERT INTO SickDayEpisodes (id, user_id, created_at, started_at, ended_at,
+            glucose_interval_minutes, ketone_interval_minutes, uses_insulin, notes)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"#,
+        params![
+            episode.id, episode.user_id, episode.created_at, episode.started_at, episode.ended_at,
+            episode.glucose_interval_minutes, episode.ketone_interval_minutes,
+            if episode.uses_insulin { 1i32 } else { 0i32 }, episode.notes
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(build_status(episode, &user, now)),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn sick_day_end(user_id: String, ended_at: Option<String>) -> Result<ApiResponse<SickDayEpisode>, String> {
+    let conn = open_conn()?;
+    let mut episode = active_episode(&conn, &user_id)?.ok_or_else(|| "No active sick-day episode".to_string())?;
+
+    let ended = match ended_at.as_deref() {
+        Some(value) => parse_timestamp_arg("ended_at", value)?,
+        None => Utc::now(),
+    };
+    if episode.bounds().map(|(start, _)| ended < start).unwrap_or(false) {
+        return Err("ended_at must not be earlier than started_at".to_string());
+    }
+    episode.ended_at = Some(format_timestamp(&ended));
+
+    conn.execute(
+        "UPDATE SickDayEpisodes SET ended_at = ?2 WHERE id = ?1",
+        params![episode.id, episode.ended_at],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(episode),
+        message: None,
+    })
+}
+
+/// 当前病假日状态和注意事项，不在生病期间时返回空
+#[tauri::command]
+pub async fn sick_day_status(user_id: String) -> Result<ApiResponse<Option<SickDayStatus>>, String> {
+    let conn = open_conn()?;
+    let user = load_user(&conn, &user_id)?;
+    let status = active_episode(&conn, &user_id)?.map(|episode| build_status(episode, &user, Utc::now()));
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(status),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn sick_days_get(user_id: String) -> Result<ApiResponse<Vec<SickDayEpisode>>, String> {
+    let conn = open_conn()?;
+
+    let mut stmt = conn
+        .prepare("SELECT * FROM SickDayEpisodes WHERE user_id = ?1 ORDER BY started_at DESC")
+        .map_err(|e| e.to_string())?;
+    let items: Vec<SickDayEpisode> = stmt
+        .query_map(params![user_id], SickDayEpisode::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(items),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn sick_day_delete(id: String) -> Result<ApiResponse<String>, String> {
+    let conn = open_conn()?;
+
+    conn.execute("DELETE FROM SickDayEpisodes WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some("Deleted".to_string()),
+        message: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        parse_timestamp(value).unwrap()
+    }
+
+    fn periods() -> SickPeriods {
+        SickPeriods(vec![
+            (at("2026-01-02T08:00:00.000Z"), Some(at("2026-01-04T08:00:00.000Z"))),
+            (at("2026-01-10T00:00:00.000Z"), None),
+        ])
+    }
+
+    #[test]
+    fn contains_is_half_open() {
+        let sick = periods();
+        assert!(!sick.contains(at("2026-01-02T07:59:59.000Z")));
+        assert!(sick.contains(at("2026-01-02T08:00:00.000Z")));
+        assert!(sick.contains(at("2026-01-04T07:59:59.000Z")));
+        assert!(!sick.contains(at("2026-01-04T08:00:00.000Z")));
+    }
+
+    #[test]
+    fn ongoing_episode_has_no_end() {
+        let sick = periods();
+        assert!(!sick.contains(at("2026-01-09T23:59:59.000Z")));
+        assert!(sick.contains(at("2027-01-01T00:00:00.000Z")));
+        assert_eq!(sick.episode_start(at("2026-01-12T00:00:00.000Z")), Some(at("2026-01-10T00:00:00.000Z")));
+    }
+
+    #[test]
+    fn no_episodes_contain_nothing() {
+        let sick = SickPeriods(Vec::new());
+        assert!(!sick.contains(at("2026-01-02T08:00:00.000Z")));
+        assert_eq!(sick.episode_start(at("2026-01-02T08:00:00.000Z")), None);
+    }
+}
@@ -0,0 +1,656 @@
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use uuid::Uuid;
+
+use crate::chat::{load_default_provider, ROLE_USER};
+use crate::database::{open_conn, ApiResponse, FoodEntry, Medication, User};
+use crate::datetime::{format_timestamp, local_midnight, parse_timestamp};
+use crate::enums::measurement_time_label;
+use crate::goals::attainment_for_period;
+use crate::health_context::{
+    is_local_provider, load_settings, CATEGORY_GLUCOSE, CATEGORY_MEDICATIONS, CATEGORY_NUTRITION,
+};
+use crate::llm::{complete, ChatRequest, LlmMessage};
+use crate::meal_pairing::load_readings;
+use crate::sick_days::{load_sick_periods, SickPeriods};
+use crate::targets::{load_targets, GlucoseTargetProfile, TargetHistory};
+
+pub const PERIOD_DAILY: &str = "daily";
+pub const PERIOD_WEEKLY: &str = "weekly";
+pub const PERIOD_MONTHLY: &str = "monthly";
+
+const MAX_SUMMARIES: i64 = 100;
+const LAST_RUN_KEY: &str = "summaries.last_run_date";
+// 应用长时间未打开时最多补生成一年内的总结
+const MAX_BACKFILL_DAYS: i64 = 366;
+
+const PROSE_PROMPT: &str = "你是 VitaNote 的糖尿病健康助手。请根据给出的统计数据，用简洁、友善的中文写一段 150 字以内的健康总结：\
+先概括整体情况，再指出一到两个亮点和一个最值得改进的地方。只使用给出的数据，不要编造数字，不要给出用药调整建议。";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MeasurementTimeMean {
+    pub measurement_time: i32,
+    pub label: String,
+    pub count: usize,
+    pub mean: f64,
+    /// 落在该时段目标范围内的读数占比
+    #[serde(default)]
+    pub in_target_percent: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GlucoseSummary {
+    pub count: usize,
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    pub time_in_range_percent: f64,
+    /// 按各测量时段的个人目标范围统计的达标率
+    #[serde(default)]
+    pub in_target_percent: f64,
+    pub hypo_count: usize,
+    pub hyper_count: usize,
+    pub by_measurement_time: Vec<MeasurementTimeMean>,
+    /// 病假日期间的读数单独计数，不计入以上统计
+    #[serde(default)]
+    pub sick_day_count: usize,
+}
+
+/// 按记录了饮食的天数求日均
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NutritionSummary {
+    pub logged_days: usize,
+    pub avg_calories: f64,
+    pub avg_carbohydrates: f64,
+    pub avg_protein: f64,
+    pub avg_fat: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MedicationSummary {
+    pub scheduled: usize,
+    pub taken: usize,
+    pub adherence_percent: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GoalAttainment {
+    pub goal: String,
+    pub label: String,
+    // 目标依据的数据类别，生成文字总结时按隐私设置过滤
+    pub category: String,
+    pub target: f64,
+    pub actual: f64,
+    pub met: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SummaryData {
+    pub glucose: Option<GlucoseSummary>,
+    pub nutrition: Option<NutritionSummary>,
+    pub medication: Option<MedicationSummary>,
+    pub goals: Vec<GoalAttainment>,
+}
+
+impl SummaryData {
+    fn is_empty(&self) -> bool {
+        self.glucose.is_none() && self.nutrition.is_none() && self.medication.is_none()
+    }
+}
+
+/// 一个周期的健康总结；period_start 与 period_end 为本地日期，均包含在周期内
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HealthSummary {
+    pub id: String,
+    pub user_id: String,
+    pub period_type: String,
+    pub period_start: String,
+    pub period_end: String,
+    pub generated_at: String,
+    pub data: SummaryData,
+    pub prose: Option<String>,
+    pub model: Option<String>,
+}
+
+impl HealthSummary {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<HealthSummary> {
+        Ok(HealthSummary {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            period_type: row.get(2)?,
+            period_start: row.get(3)?,
+            period_end: row.get(4)?,
+            generated_at: row.get(5)?,
+            data: serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or_default(),
+            prose: row.get(7)?,
+            model: row.get(8)?,
+        })
+    }
+}
+
+fn validate_period(period_type: &str) -> Result<(), String> {
+    match period_type {
+        PERIOD_DAILY | PERIOD_WEEKLY | PERIOD_MONTHLY => Ok(()),
+        other => Err(format!("Unknown summary period: {}", other)),
+    }
+}
+
+/// 返回包含 date 的周期 [start, end)，周从周一开始
+fn period_bounds(period_type: &str, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    match period_type {
+        PERIOD_WEEKLY => {
+            let start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+            (start, start + Duration::days(7))
+        }
+        PERIOD_MONTHLY => {
+            let start = date.with_day(1).unwrap_or(date);
+            (start, start + Months::new(1))
+        }
+        _ => (date, date + Duration::days(1)),
+    }
+}
+
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+// TIR、低血糖与高血糖按读数当时适用的目标统计
+fn glucose_summary(
+    conn: &Connection,
+    user_id: &str,
+    targets: &TargetHistory,
+    sick: &SickPeriods,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Option<GlucoseSummary>, String> {
+    let (sick_readings, readings): (Vec<_>, Vec<_>) = load_readings(conn, user_id, from, to)?
+        .into_iter()
+        .filter(|(t, _)| *t < to)
+        .partition(|(t, _)| sick.contains(*t));
+    if readings.is_empty() {
+        return Ok(None);
+    }
+
+    let values: Vec<f64> = readings.iter().map(|(_, r)| r.value).collect();
+    let count = values.len() as f64;
+    let count_where = |f: fn(&GlucoseTargetProfile, f64) -> bool| {
+        readings.iter().filter(|(t, r)| f(targets.at(*t), r.value)).count()
+    };
+    let mut by_time: BTreeMap<i32, Vec<(f64, bool)>> = BTreeMap::new();
+    for (t, reading) in &readings {
+        by_time
+            .entry(reading.measurement_time)
+            .or_default()
+            .push((reading.value, targets.in_target(*t, reading)));
+    }
+    let in_target = readings.iter().filter(|(t, r)| targets.in_target(*t, r)).count();
+
+    Ok(Some(GlucoseSummary {
+        count: values.len(),
+        mean: round1(values.iter().sum::<f64>() / count),
+        min: values.iter().cloned().fold(f64::INFINITY, f64::min),
+        max: values.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+        time_in_range_percent: round1(count_where(|p, v| p.in_tir(v)) as f64 / count * 100.0),
+        in_target_percent: round1(in_target as f64 / count * 100.0),
+        hypo_count: count_where(|p, v| p.is_hypo(v)),
+        hyper_count: count_where(|p, v| p.is_hyper(v)),
+        by_measurement_time: by_time
+            .into_iter()
+            .map(|(time, values)| MeasurementTimeMean {
+                measurement_time: time,
+                label: measurement_time_label(time).to_string(),
+                count: values.len(),
+                mean: round1(values.iter().map(|(v, _)| v).sum::<f64>() / values.len() as f64),
+                in_target_percent: round1(
+                    values.iter().filter(|(_, ok)| *ok).count() as f64 / values.len() as f64 * 100.0,
+                ),
+            })
+            .collect(),
+        sick_day_count: sick_readings.len(),
+    }))
+}
+
+fn nutrition_summary(
+    conn: &Connection,
+    user_id: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Option<NutritionSummary>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM FoodEntries
+            WHERE user_id = ?1 AND meal_time >= ?2 AND meal_time <= ?3"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let entries: Vec<(DateTime<Utc>, FoodEntry)> = stmt
+        .query_map(
+            params![
+                user_id,
+                from.format("%Y-%m-%d").to_string(),
+                (to + Duration::days(1)).format("%Y-%m-%d").to_string()
+            ],
+            FoodEntry::from_row,
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|entry| Some((parse_timestamp(&entry.meal_time)?, entry)))
+        .filter(|(t, _)| *t >= from && *t < to)
+        .collect();
+    if entries.is_empty() {
+        return Ok(None);
+    }
+
+    let logged_days: HashSet<NaiveDate> =
+        entries.iter().map(|(t, _)| t.with_timezone(&Local).date_naive()).collect();
+    let day_count = logged_days.len() as f64;
+    let average = |f: fn(&FoodEntry) -> f64| round1(entries.iter().map(|(_, e)| f(e)).sum::<f64>() / day_count);
+
+    Ok(Some(NutritionSummary {
+        logged_days: logged_days.len(),
+        avg_calories: average(|e| e.calories),
+        avg_carbohydrates: average(|e| e.carbohydrates),
+        avg_protein: average(|e| e.protein),
+        avg_fat: average(|e| e.fat),
+    }))
+}
+
+fn medication_summary(
+    conn: &Connection,
+    user_id: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Option<MedicationSummary>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM Medications
+            WHERE user_id = ?1 AND scheduled_time >= ?2 AND scheduled_time <= ?3"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let doses: Vec<Medication> = stmt
+        .query_map(
+            params![
+                user_id,
+                from.format("%Y-%m-%d").to_string(),
+                (to + Duration::days(1)).format("%Y-%m-%d").to_string()
+            ],
+            Medication::from_row,
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter(|m| matches!(parse_timestamp(&m.scheduled_time), Some(t) if t >= from && t < to))
+        .collect();
+    if doses.is_empty() {
+        return Ok(None);
+    }
+
+    let taken = doses.iter().filter(|m| m.is_taken).count();
+    Ok(Some(MedicationSummary {
+        scheduled: doses.len(),
+        taken,
+        adherence_percent: round1(taken as f64 / doses.len() as f64 * 100.0),
+    }))
+}
+
+// TIR 目标取周期结束时适用的目标；热量与碳水目标按日均不超过目标值计算
+fn goal_attainment(user: &User, targets: &GlucoseTargetProfile, data: &SummaryData) -> Vec<GoalAttainment> {
+    let mut goals = Vec::new();
+    if let Some(glucose) = &data.glucose {
+        goals.push(GoalAttainment {
+            goal: "time_in_range".to_string(),
+            label: targets.tir_label(),
+            category: CATEGORY_GLUCOSE.to_string(),
+            target: targets.tir_goal_percent,
+            actual: glucose.time_in_range_percent,
+            met: glucose.time_in_range_percent >= targets.tir_goal_percent,
+        });
+    }
+    if let Some(nutrition) = &data.nutrition {
+        if let Some(target) = user.target_calories {
+            goals.push(GoalAttainment {
+                goal: "calories".to_string(),
+                label: "日均热量".to_string(),
+                category: CATEGORY_NUTRITION.to_string(),
+                target,
+                actual: nutrition.avg_calories,
+                met: nutrition.avg_calories <= target,
+            });
+        }
+        if let Some(target) = user.target_carbohydrates {
+            goals.push(GoalAttainment {
+                goal: "carbohydrates".to_string(),
+                label: "日均碳水".to_string(),
+                category: CATEGORY_NUTRITION.to_string(),
+                target,
+                actual: nutrition.avg_carbohydrates,
+                met: nutrition.avg_carbohydrates <= target,
+            });
+        }
+    }
+    goals
+}
+
+/// 计算一个周期的结构化总结，结果只取决于记录本身
+pub(crate) fn compute_summary(
+    conn: &Connection,
+    user_id: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<SummaryData, String> {
+    let user = conn
+        .query_row("SELECT * FROM Users WHERE id = ?1", params![user_id], User::from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("User not found: {}", user_id))?;
+
+    let from = local_midnight(start);
+    let to = local_midnight(end);
+    let targets = load_targets(conn, user_id)?;
+    let sick = load_sick_periods(conn, user_id)?;
+    let mut data = SummaryData {
+        glucose: glucose_summary(conn, user_id, &targets, &sick, from, to)?,
+        nutrition: nutrition_summary(conn, user_id, from, to)?,
+        medication: medication_summary(conn, user_id, from, to)?,
+        goals: Vec::new(),
+    };
+    data.goals = goal_attainment(&user, targets.at(to - Duration::seconds(1)), &data);
+    data.goals.extend(attainment_for_period(conn, user_id, start, end)?);
+    Ok(data)
+}
+
+fn load_summary(
+    conn: &Connection,
+    user_id: &str,
+    period_type: &str,
+    start: NaiveDate,
+) -> Result<Option<HealthSummary>, String> {
+    conn.query_row(
+        "SELECT * FROM Summaries WHERE user_id = ?1 AND period_type = ?2 AND period_start = ?3",
+        params![user_id, period_type, start.format("%Y-%m-%d").to_string()],
+        HealthSummary::from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// 生成并保存周期总结；重新生成时覆盖旧数据并清除文字总结
+pub(crate) fn save_summary(
+    conn: &Connection,
+    user_id: &str,
+    period_type: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+    data: &SummaryData,
+) -> Result<HealthSummary, String> {
+    let json = serde_json::to_string(data).map_err(|e| e.to_string())?;
+    conn.execute(
+        r#"INSERT INTO Summaries (id, user_id, period_type, period_start, period_end, generated_at, data)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+        ON CONFLICT (user_id, period_type, period_start) DO UPDATE SET
+            generated_at = excluded.generated_at, data = excluded.data, prose = NULL, model = NULL"#,
+        params![
+            Uuid::new_v4().to_string(),
+            user_id,
+            period_type,
+            start.format("%Y-%m-%d").to_string(),
+            (end - Duration::days(1)).format("%Y-%m-%d").to_string(),
+            format_timestamp(&Utc::now()),
+            json
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    load_summary(conn, user_id, period_type, start)?
+        .ok_or_else(|| "Failed to save summary".to_string())
+}
+
+/// 需要补生成的第一个周期的开始日期：上次保存的总结与上次运行已处理的周期之后，
+/// 两者都没有时只生成刚结束的周期
+fn first_due_period(
+    conn: &Connection,
+    user_id: &str,
+    period_type: &str,
+    last_run: Option<NaiveDate>,
+    today: NaiveDate,
+) -> Result<NaiveDate, String> {
+    let latest: Option<String> = conn
+        .query_row(
+            "SELECT MAX(period_start) FROM Summaries WHERE user_id = ?1 AND period_type = ?2",
+            params![user_id, period_type],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let latest = latest.and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok());
+
+    let after = [
+        latest.map(|d| period_bounds(period_type, d).1),
+        last_run.map(|d| period_bounds(period_type, d - Duration::days(1)).1),
+    ]
+    .into_iter()
+    .flatten()
+    .max();
+    let (current, _) = period_bounds(period_type, today);
+    Ok(match after {
+        Some(after) => after.max(period_bounds(period_type, today - Duration::days(MAX_BACKFILL_DAYS)).0),
+        None => period_bounds(period_type, current - Duration::days(1)).0,
+    })
+}
+
+/// 每天第一次运行时为所有用户补上已结束但还没有总结的日、周、月周期（包括应用关闭期间结束的周期）；
+/// 没有任何记录的周期不生成
+pub(crate) fn generate_due_summaries(conn: &Connection, now: DateTime<Utc>) -> Result<usize, String> {
+    let today = now.with_timezone(&Local).date_naive();
+    let today_key = today.format("%Y-%m-%d").to_string();
+    let last_run: Option<String> = conn
+        .query_row("SELECT value FROM SchedulerState WHERE key = ?1", params![LAST_RUN_KEY], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if last_run.as_deref() == Some(today_key.as_str()) {
+        return Ok(0);
+    }
+    let last_run = last_run.and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok());
+
+    let mut stmt = conn.prepare("SELECT id FROM Users").map_err(|e| e.to_string())?;
+    let users: Vec<String> = stmt
+        .query_map(params![], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut generated = 0;
+    for user_id in &users {
+        for period_type in [PERIOD_DAILY, PERIOD_WEEKLY, PERIOD_MONTHLY] {
+            let (current, _) = period_bounds(period_type, today);
+            let mut start = first_due_period(conn, user_id, period_type, last_run, today)?;
+            while start < current {
+                let (_, end) = period_bounds(period_type, start);
+                if load_summary(conn, user_id, period_type, start)?.is_none() {
+                    let data = compute_summary(conn, user_id, start, end)?;
+                    if !data.is_empty() {
+                        save_summary(conn, user_id, period_type, start, end, &data)?;
+                        generated += 1;
+                    }
+                }
+                start = end;
+            }
+        }
+    }
+
+    conn.execute(
+        "INSERT OR REPLACE INTO SchedulerState (key, value) VALUES (?1, ?2)",
+        params![LAST_RUN_KEY, today_key],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(generated)
+}
+
+fn period_label(summary: &HealthSummary) -> String {
+    match summary.period_type.as_str() {
+        PERIOD_DAILY => format!("{} 的每日", summary.period_start),
+        PERIOD_WEEKLY => format!("{} 至 {} 的每周", summary.period_start, summary.period_end),
+        _ => format!("{} 至 {} 的每月", summary.period_start, summary.period_end),
+    }
+}
+
+/// 把结构化总结写成提示词，只包含隐私设置允许发送的类别
+fn summary_prompt(summary: &HealthSummary, categories: &[&str]) -> Option<String> {
+    let data = &summary.data;
+    let mut lines = Vec::new();
+
+    if let Some(glucose) = data.glucose.as_ref().filter(|_| categories.contains(&CATEGORY_GLUCOSE)) {
+        lines.push(format!(
+            "血糖：共 {} 次，平均 {:.1} mmol/L，范围 {:.1}–{:.1}，TIR {:.1}%，个人目标达标率 {:.1}%，低血糖 {} 次，高血糖 {} 次",
+            glucose.count, glucose.mean, glucose.min, glucose.max, glucose.time_in_range_percent,
+            glucose.in_target_percent, glucose.hypo_count, glucose.hyper_count
+        ));
+        let per_time: Vec<String> = glucose
+            .by_measurement_time
+            .iter()
+            .map(|m| format!("{}平均 {:.1}（{} 次）", m.label, m.mean, m.count))
+            .collect();
+        lines.push(per_time.join("；"));
+        if glucose.sick_day_count > 0 {
+            lines.push(format!("另有 {} 次读数在病假日期间，未计入以上统计", glucose.sick_day_count));
+        }
+    }
+    if let Some(nutrition) = data.nutrition.as_ref().filter(|_| categories.contains(&CATEGORY_NUTRITION)) {
+        lines.push(format!(
+            "饮食（记录 {} 天）：日均 {:.0} kcal，碳水 {:.0} g，蛋白质 {:.0} g，脂肪 {:.0} g",
+            nutrition.logged_days, nutrition.avg_calories, nutrition.avg_carbohydrates, nutrition.avg_protein,
+            nutrition.avg_fat
+        ));
+    }
+    if let Some(medication) = data.medication.as_ref().filter(|_| categories.contains(&CATEGORY_MEDICATIONS)) {
+        lines.push(format!(
+            "用药：应服 {} 次，已服 {} 次，依从率 {:.0}%",
+            medication.scheduled, medication.taken, medication.adherence_percent
+        ));
+    }
+    for goal in data.goals.iter().filter(|g| categories.contains(&g.category.as_str())) {
+        lines.push(format!(
+            "目标 {}：目标 {:.0}，实际 {:.1}，{}",
+            goal.label,
+            goal.target,
+            goal.actual,
+            if goal.met { "已达成" } else { "未达成" }
+        ));
+    }
+
+    if lines.is_empty() {
+        return None;
+    }
+    Some(format!("以下是用户 {}健康数据：\n{}", period_label(summary), lines.join("\n")))
+}
+
+// ============ Summary Commands ============
+
+#[tauri::command]
+pub async fn summary_generate(
+    user_id: String,
+    period_type: String,
+    date: String,
+    with_prose: bool,
+    regenerate: bool,
+) -> Result<ApiResponse<HealthSummary>, String> {
+    validate_period(&period_type)?;
+    let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|_| format!("Invalid date: {}", date))?;
+    let (start, end) = period_bounds(&period_type, date);
+    if local_midnight(end) > Utc::now() {
+        return Err("Period has not ended yet".to_string());
+    }
+
+    // 数据库连接不跨越 await，请求前后分别打开
+    let (summary, config, prompt) = {
+        let conn = open_conn()?;
+        let summary = match load_summary(&conn, &user_id, &period_type, start)? {
+            Some(summary) if !regenerate => summary,
+            _ => {
+                let data = compute_summary(&conn, &user_id, start, end)?;
+                save_summary(&conn, &user_id, &period_type, start, end, &data)?
+            }
+        };
+        if !with_prose || summary.prose.is_some() {
+            return Ok(ApiResponse {
+                success: true,
+                data: Some(summary),
+                message: None,
+            });
+        }
+
+        let config = load_default_provider(&conn)?;
+        let categories = load_settings(&conn, &user_id)?.allowed_categories(is_local_provider(&config));
+        let prompt = summary_prompt(&summary, &categories)
+            .ok_or_else(|| "No shareable data for a written summary".to_string())?;
+        (summary, config, prompt)
+    };
+
+    let request = ChatRequest {
+        system: Some(PROSE_PROMPT.to_string()),
+        messages: vec![LlmMessage::text(ROLE_USER, prompt)],
+        tools: Vec::new(),
+        disable_tool_calls: false,
+    };
+    // 文字总结失败时仍返回结构化总结
+    let completion = match complete(&config, &request).await {
+        Ok(completion) => completion,
+        Err(e) => {
+            eprintln!("Summary prose generation failed: {}", e);
+            return Ok(ApiResponse {
+                success: true,
+                data: Some(summary),
+                message: Some(e),
+            });
+        }
+    };
+
+    let conn = open_conn()?;
+    let prose = completion.content.trim().to_string();
+    conn.execute(
+        "UPDATE Summaries SET prose = ?1, model = ?2 WHERE id = ?3",
+        params![prose, completion.model, summary.id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(HealthSummary {
+            prose: Some(prose),
+            model: Some(completion.model),
+            ..summary
+        }),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn summaries_get(
+    user_id: String,
+    period_type: Option<String>,
+    limit: i64,
+) -> Result<ApiResponse<Vec<HealthSummary>>, String> {
+    if let Some(period_type) = period_type.as_deref() {
+        validate_period(period_type)?;
+    }
+    let conn = open_conn()?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"SELECT * FROM Summaries
+            WHERE user_id = ?1 AND (?2 IS NULL OR period_type = ?2)
+            ORDER BY period_start DESC, period_type ASC
+            LIMIT ?3"#,
+        )
+        .map_err(|e| e.to_string())?;
+    let summaries: Vec<HealthSummary> = stmt
+        .query_map(params![user_id, period_type, limit.clamp(1, MAX_SUMMARIES)], HealthSummary::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(summaries),
+        message: None,
+    })
+}
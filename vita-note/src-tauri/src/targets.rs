@@ -0,0 +1,477 @@
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::database::{open_conn, ApiResponse, BloodGlucose, User};
+use crate::datetime::{format_timestamp, parse_timestamp, parse_timestamp_arg};
+use crate::enums::{
+    measurement_time_label, DIABETES_GESTATIONAL, DIABETES_TYPE1, MEASUREMENT_FASTING, MEASUREMENT_RANDOM,
+};
+use crate::gestational::Pregnancy;
+use crate::health_context::whole_years;
+
+pub const PRESET_TYPE1: &str = "type1";
+pub const PRESET_TYPE2: &str = "type2";
+pub const PRESET_GESTATIONAL: &str = "gestational";
+pub const PRESET_OLDER_ADULT: &str = "older_adult";
+
+// 年满 65 岁且未单独设置时采用老年人的宽松目标
+const OLDER_ADULT_AGE: i32 = 65;
+const MIN_TARGET: f64 = 1.0;
+const MAX_TARGET: f64 = 33.3;
+
+struct Preset {
+    code: &'static str,
+    label: &'static str,
+    tir_low: f64,
+    tir_high: f64,
+    tir_goal_percent: f64,
+    /// 按 MeasurementTimeType 取值顺序：空腹、餐前、餐后1h、餐后2h、睡前、夜间、随机
+    ranges: [(f64, f64); 7],
+}
+
+/// 单位 mmol/L；依据《中国 2 型糖尿病防治指南（2020 年版）》、ADA 标准、
+/// 《妊娠期高血糖诊治指南（2022）》与《中国老年糖尿病诊疗指南（2021 年版）》
+const PRESETS: [Preset; 4] = [
+    Preset {
+        code: PRESET_TYPE2,
+        label: "2 型糖尿病",
+        tir_low: 3.9,
+        tir_high: 10.0,
+        tir_goal_percent: 70.0,
+        ranges: [(4.4, 7.0), (4.4, 7.0), (4.4, 10.0), (4.4, 10.0), (4.4, 10.0), (4.4, 7.0), (4.4, 10.0)],
+    },
+    Preset {
+        code: PRESET_TYPE1,
+        label: "1 型糖尿病",
+        tir_low: 3.9,
+        tir_high: 10.0,
+        tir_goal_percent: 70.0,
+        ranges: [(4.4, 7.0), (4.4, 7.0), (4.4, 10.0), (4.4, 10.0), (5.0, 8.3), (4.4, 8.3), (4.4, 10.0)],
+    },
+    Preset {
+        code: PRESET_GESTATIONAL,
+        label: "妊娠期糖尿病",
+        tir_low: 3.5,
+        tir_high: 7.8,
+        tir_goal_percent: 70.0,
+        ranges: [(3.3, 5.3), (3.3, 5.3), (3.3, 7.8), (3.3, 6.7), (3.3, 6.7), (3.3, 6.7), (3.3, 7.8)],
+    },
+    Preset {
+        code: PRESET_OLDER_ADULT,
+        label: "老年患者（宽松）",
+        tir_low: 3.9,
+        tir_high: 10.0,
+        tir_goal_percent: 50.0,
+        ranges: [(5.0, 8.3), (5.0, 8.3), (5.0, 11.1), (5.0, 11.1), (5.6, 10.0), (5.0, 8.3), (5.0, 11.1)],
+    },
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GlucoseTargetRange {
+    pub measurement_time: i32,
+    pub low: f64,
+    pub high: f64,
+}
+
+/// 一次目标设置；修改目标时新增一条，按 effective_from 保留历史
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GlucoseTargetProfile {
+    pub id: String,
+    pub user_id: String,
+    pub created_at: String,
+    /// 基于的预设，完全自定义时为空
+    pub preset: Option<String>,
+    /// TIR 统计范围，低于 tir_low 计为低血糖，高于 tir_high 计为高血糖
+    pub tir_low: f64,
+    pub tir_high: f64,
+    pub tir_goal_percent: f64,
+    /// 各测量时段的目标范围，未列出的时段使用 TIR 范围
+    pub ranges: Vec<GlucoseTargetRange>,
+    pub effective_from: String,
+    pub notes: Option<String>,
+}
+
+impl GlucoseTargetProfile {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<GlucoseTargetProfile> {
+        Ok(GlucoseTargetProfile {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            created_at: row.get(2)?,
+            preset: row.get(3)?,
+            tir_low: row.get(4)?,
+            tir_high: row.get(5)?,
+            tir_goal_percent: row.get(6)?,
+            ranges: row
+                .get::<_, Option<String>>(7)?
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            effective_from: row.get(8)?,
+            notes: row.get(9)?,
+        })
+    }
+
+    fn from_preset(preset: &Preset, user_id: &str) -> GlucoseTargetProfile {
+        GlucoseTargetProfile {
+            id: String::new(),
+            user_id: user_id.to_string(),
+            created_at: String::new(),
+            preset: Some(preset.code.to_string()),
+            tir_low: preset.tir_low,
+            tir_high: preset.tir_high,
+            tir_goal_percent: preset.tir_goal_percent,
+            ranges: preset_ranges(preset),
+            effective_from: String::new(),
+            notes: None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        parse_timestamp_arg("effective_from", &self.effective_from)?;
+        if let Some(preset) = &self.preset {
+            find_preset(preset)?;
+        }
+        let check = |name: &str, low: f64, high: f64| {
+            if !(MIN_TARGET..=MAX_TARGET).contains(&low) || !(MIN_TARGET..=MAX_TARGET).contains(&high) || low >= high {
+                Err(format!("Invalid {} target range: {}–{}", name, low, high))
+            } else {
+                Ok(())
+            }
+        };
+        check("TIR", self.tir_low, self.tir_high)?;
+        if !(0.0..=100.0).contains(&self.tir_goal_percent) {
+            return Err(format!("Invalid tir_goal_percent: {}", self.tir_goal_percent));
+        }
+        for (i, range) in self.ranges.iter().enumerate() {
+            if !(MEASUREMENT_FASTING..=MEASUREMENT_RANDOM).contains(&range.measurement_time) {
+                return Err(format!("Invalid measurement_time: {}", range.measurement_time));
+            }
+            if self.ranges[..i].iter().any(|r| r.measurement_time == range.measurement_time) {
+                return Err(format!("Duplicate measurement_time: {}", range.measurement_time));
+            }
+            check(measurement_time_label(range.measurement_time), range.low, range.high)?;
+        }
+        Ok(())
+    }
+
+    fn ranges_json(&self) -> Result<Option<String>, String> {
+        if self.ranges.is_empty() {
+            return Ok(None);
+        }
+        serde_json::to_string(&self.ranges).map(Some).map_err(|e| e.to_string())
+    }
+
+    /// 某个测量时段的目标范围 (low, high)
+    pub fn range_for(&self, measurement_time: i32) -> (f64, f64) {
+        self.ranges
+            .iter()
+            .find(|r| r.measurement_time == measurement_time)
+            .map(|r| (r.low, r.high))
+            .unwrap_or((self.tir_low, self.tir_high))
+    }
+
+    pub fn in_tir(&self, value: f64) -> bool {
+        value >= self.tir_low && value <= self.tir_high
+    }
+
+    pub fn is_hypo(&self, value: f64) -> bool {
+        value < self.tir_low
+    }
+
+    pub fn is_hyper(&self, value: f64) -> bool {
+        value > self.tir_high
+    }
+
+    /// 读数是否落在其测量时段的目标范围内
+    pub fn in_target(&self, reading: &BloodGlucose) -> bool {
+        let (low, high) = self.range_for(reading.measurement_time);
+        reading.value >= low && reading.value <= high
+    }
+
+    pub fn tir_label(&self) -> String {
+        format!("TIR({:.1}–{:.1})", self.tir_low, self.tir_high)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GlucoseTargetPreset {
+    pub code: String,
+    pub label: String,
+    pub tir_low: f64,
+    pub tir_high: f64,
+    pub tir_goal_percent: f64,
+    pub ranges: Vec<GlucoseTargetRange>,
+}
+
+/// 用户的目标历史，按生效时间查询当时适用的目标
+pub(crate) struct TargetHistory {
+    profiles: Vec<(DateTime<Utc>, GlucoseTargetProfile)>,
+    /// 首次设置目标之前（或从未设置时）按糖尿病类型和年龄取的预设
+    fallback: GlucoseTargetProfile,
+    /// 登记了预产期时的妊娠期范围 [start, end)
+    pregnancy: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    gestational: GlucoseTargetProfile,
+}
+
+impl TargetHistory {
+    /// 妊娠期间自动使用妊娠期目标，除非孕期内另行设置过目标
+    pub(crate) fn at(&self, time: DateTime<Utc>) -> &GlucoseTargetProfile {
+        let stored = self.profiles.iter().rev().find(|(from, _)| *from <= time);
+        if let Some((start, end)) = self.pregnancy {
+            if time >= start && time < end && stored.map(|(from, _)| *from < start).unwrap_or(true) {
+                return &self.gestational;
+            }
+        }
+        stored.map(|(_, profile)| profile).unwrap_or(&self.fallback)
+    }
+
+    pub(crate) fn in_target(&self, time: DateTime<Utc>, reading: &BloodGlucose) -> bool {
+        self.at(time).in_target(reading)
+    }
+}
+
+fn preset_ranges(preset: &Preset) -> Vec<GlucoseTargetRange> {
+    preset
+        .ranges
+        .iter()
+        .zip(MEASUREMENT_FASTING..=MEASUREMENT_RANDOM)
+        .map(|((low, high), measurement_time)| GlucoseTargetRange {
+            measurement_time,
+            low: *low,
+            high: *high,
+        })
+        .collect()
+}
+
+fn find_preset(code: &str) -> Result<&'static Preset, String> {
+    PRESETS
+        .iter()
+        .find(|p| p.code == code)
+        .ok_or_else(|| format!("Unknown target preset: {}", code))
+}
+
+/// 未设置目标时的默认预设：妊娠期、1 型按类型，其余 65 岁以上用老年目标
+pub(crate) fn default_preset(user: &User, today: NaiveDate) -> &'static str {
+    let age = user
+        .birthday
+        .as_deref()
+        .and_then(parse_timestamp)
+        .map(|birthday| whole_years(birthday.date_naive(), today));
+    match user.diabetes_type {
+        DIABETES_GESTATIONAL => PRESET_GESTATIONAL,
+        DIABETES_TYPE1 => PRESET_TYPE1,
+        _ if matches!(age, Some(age) if age >= OLDER_ADULT_AGE) => PRESET_OLDER_ADULT,
+        _ => PRESET_TYPE2,
+    }
+}
+
+pub(crate) fn load_targets(conn: &Connection, user_id: &str) -> Result<TargetHistory, String> {
+    let user = conn
+        .query_row("SELECT * FROM Users WHERE id = ?1", params![user_id], User::from_row)
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let preset = match &user {
+        Some(user) => default_preset(user, Local::now().date_naive()),
+        None => PRESET_TYPE2,
+    };
+
+    let mut stmt = conn
+        .prepare("SELECT * FROM GlucoseTargetProfiles WHERE user_id = ?1 ORDER BY effective_from ASC")
+        .map_err(|e| e.to_string())?;
+    let mut profiles: Vec<(DateTime<Utc>, GlucoseTargetProfile)> = stmt
+        .query_map(params![user_id], GlucoseTargetProfile::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|profile| Some((parse_timestamp(&profile.effective_from)?, profile)))
+        .collect();
+    // 旧数据的 effective_from 可能带不同时区偏移，按字符串排序不一定是时间顺序
+    profiles.sort_by_key(|(from, _)| *from);
+
+    Ok(TargetHistory {
+        profiles,
+        fallback: GlucoseTargetProfile::from_preset(find_preset(preset)?, user_id),
+        pregnancy: user.as_ref().and_then(Pregnancy::from_user).map(|p| p.bounds()),
+        gestational: GlucoseTargetProfile::from_preset(find_preset(PRESET_GESTATIONAL)?, user_id),
+    })
+}
+
+// ============ Glucose Target Commands ============
+
+#[tauri::command]
+pub async fn glucose_target_presets_get() -> Result<ApiResponse<Vec<GlucoseTargetPreset>>, String> {
+    let presets = PRESETS
+        .iter()
+        .map(|p| GlucoseTargetPreset {
+            code: p.code.to_string(),
+            label: p.label.to_string(),
+            tir_low: p.tir_low,
+            tir_high: p.tir_high,
+            tir_goal_percent: p.tir_goal_percent,
+            ranges: preset_ranges(p),
+        })
+        .collect();
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(presets),
+        message: None,
+    })
+}
+
+/// 返回 at（默认当前）时适用的目标；从未设置时返回默认预设，id 为空
+#[tauri::command]
+pub async fn glucose_targets_get(
+    user_id: String,
+    at: Option<String>,
+) -> Result<ApiResponse<GlucoseTargetProfile>, String> {
+    let at = match at {
+        Some(at) => parse_timestamp_arg("at", &at)?,
+        None => Utc::now(),
+    };
+    let conn = open_conn()?;
+    let targets = load_targets(&conn, &user_id)?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(targets.at(at).clone()),
+        message: None,
+    })
+}
+
+/// 保存一次新的目标设置；只给出 preset 而 ranges 为空时按预设填充
+#[tauri::command]
+pub async fn glucose_targets_set(profile: GlucoseTargetProfile) -> Result<ApiResponse<GlucoseTargetProfile>, String> {
+    let mut profile = profile;
+    if profile.ranges.is_empty() {
+        if let Some(code) = &profile.preset {
+            let preset = find_preset(code)?;
+            profile.tir_low = preset.tir_low;
+            profile.tir_high = preset.tir_high;
+            profile.tir_goal_percent = preset.tir_goal_percent;
+            profile.ranges = preset_ranges(preset);
+        }
+    }
+    profile.validate()?;
+    // 统一存为 UTC 格式，保证按 effective_from 排序即按时间先后
+    profile.effective_from = format_timestamp(&parse_timestamp_arg("effective_from", &profile.effective_from)?);
+    let conn = open_conn()?;
+
+    conn.execute(
+        r#"INSERT INTO GlucoseTargetProfiles (id, user_id, created_at, preset, tir_low, tir_high,
+            tir_goal_percent, ranges, effective_from, notes)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"#,
+        params![
+            profile.id, profile.user_id, profile.created_at, profile.preset, profile.tir_low, profile.tir_high,
+            profile.tir_goal_percent, profile.ranges_json()?, profile.effective_from, profile.notes
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(profile),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn glucose_targets_history(user_id: String) -> Result<ApiResponse<Vec<GlucoseTargetProfile>>, String> {
+    let conn = open_conn()?;
+
+    let mut stmt = conn
+        .prepare("SELECT * FROM GlucoseTargetProfiles WHERE user_id = ?1 ORDER BY effective_from DESC")
+        .map_err(|e| e.to_string())?;
+    let mut items: Vec<GlucoseTargetProfile> = stmt
+        .query_map(params![user_id], GlucoseTargetProfile::from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    items.sort_by_key(|profile| std::cmp::Reverse(parse_timestamp(&profile.effective_from)));
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some(items),
+        message: None,
+    })
+}
+
+#[tauri::command]
+pub async fn glucose_targets_delete(id: String) -> Result<ApiResponse<String>, String> {
+    let conn = open_conn()?;
+
+    conn.execute("DELETE FROM GlucoseTargetProfiles WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(ApiResponse {
+        success: true,
+        data: Some("Deleted".to_string()),
+        message: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        parse_timestamp(value).unwrap()
+    }
+
+    fn preset(code: &str) -> GlucoseTargetProfile {
+        GlucoseTargetProfile::from_preset(find_preset(code).unwrap(), "u1")
+    }
+
+    fn history(pregnancy: Option<(&str, &str)>) -> TargetHistory {
+        TargetHistory {
+            profiles: vec![
+                (at("2026-01-01T00:00:00.000Z"), preset(PRESET_TYPE1)),
+                (at("2026-03-01T00:00:00.000Z"), preset(PRESET_OLDER_ADULT)),
+            ],
+            fallback: preset(PRESET_TYPE2),
+            pregnancy: pregnancy.map(|(start, end)| (at(start), at(end))),
+            gestational: preset(PRESET_GESTATIONAL),
+        }
+    }
+
+    fn preset_at(history: &TargetHistory, time: &str) -> Option<String> {
+        history.at(at(time)).preset.clone()
+    }
+
+    #[test]
+    fn uses_fallback_before_first_profile() {
+        assert_eq!(preset_at(&history(None), "2025-12-31T23:59:59.000Z").as_deref(), Some(PRESET_TYPE2));
+    }
+
+    #[test]
+    fn uses_latest_profile_in_effect() {
+        let history = history(None);
+        assert_eq!(preset_at(&history, "2026-01-01T00:00:00.000Z").as_deref(), Some(PRESET_TYPE1));
+        assert_eq!(preset_at(&history, "2026-02-28T23:59:59.000Z").as_deref(), Some(PRESET_TYPE1));
+        assert_eq!(preset_at(&history, "2026-03-01T00:00:00.000Z").as_deref(), Some(PRESET_OLDER_ADULT));
+    }
+
+    #[test]
+    fn pregnancy_switches_to_gestational_targets() {
+        let history = history(Some(("2026-04-01T00:00:00.000Z", "2027-01-01T00:00:00.000Z")));
+        assert_eq!(preset_at(&history, "2026-03-31T23:59:59.000Z").as_deref(), Some(PRESET_OLDER_ADULT));
+        assert_eq!(preset_at(&history, "2026-04-01T00:00:00.000Z").as_deref(), Some(PRESET_GESTATIONAL));
+        assert_eq!(preset_at(&history, "2027-01-01T00:00:00.000Z").as_deref(), Some(PRESET_OLDER_ADULT));
+    }
+
+    #[test]
+    fn profile_set_during_pregnancy_overrides_gestational() {
+        // 孕期内设置的目标优先于自动切换的妊娠期目标
+        let history = history(Some(("2026-02-01T00:00:00.000Z", "2026-11-01T00:00:00.000Z")));
+        assert_eq!(preset_at(&history, "2026-02-15T00:00:00.000Z").as_deref(), Some(PRESET_GESTATIONAL));
+        assert_eq!(preset_at(&history, "2026-03-15T00:00:00.000Z").as_deref(), Some(PRESET_OLDER_ADULT));
+    }
+
+    #[test]
+    fn range_falls_back_to_tir() {
+        let mut profile = preset(PRESET_TYPE2);
+        assert_eq!(profile.range_for(MEASUREMENT_FASTING), (4.4, 7.0));
+        profile.ranges.clear();
+        assert_eq!(profile.range_for(MEASUREMENT_FASTING), (3.9, 10.0));
+        assert!(profile.is_hypo(3.8) && profile.in_tir(3.9) && profile.in_tir(10.0) && profile.is_hyper(10.1));
+    }
+}